@@ -0,0 +1,95 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct AcarsLabel {
+    pub label: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sublabel: Option<&'static str>,
+
+    pub description: &'static str,
+}
+
+const fn label(label: &'static str, description: &'static str) -> AcarsLabel {
+    AcarsLabel {
+        label,
+        sublabel: None,
+        description,
+    }
+}
+
+const fn sublabel(
+    label: &'static str,
+    sublabel: &'static str,
+    description: &'static str,
+) -> AcarsLabel {
+    AcarsLabel {
+        label,
+        sublabel: Some(sublabel),
+        description,
+    }
+}
+
+pub const ACARS_LABELS: &'static [AcarsLabel] = &[
+    label("_d", "General response, no information to transmit"),
+    label("00", "Emergency situation report"),
+    label("51", "Ground GMT request/response"),
+    label("54", "Aircrew initiated voice contact request"),
+    label("5D", "ATIS request"),
+    label("5P", "Temporary suspension of ACARS"),
+    label("5U", "Weather request"),
+    label("5V", "VDL switch advisory"),
+    label("5Z", "Airline designated downlink"),
+    label("A6", "FANS-1/A ADS-C uplink"),
+    label("AA", "FANS-1/A CPDLC uplink"),
+    label("B6", "FANS-1/A ADS-C downlink"),
+    label("BA", "FANS-1/A CPDLC downlink"),
+    label("C0", "Uplink message to all cockpit printers"),
+    label("C1", "Uplink message to cockpit printer #1"),
+    label("H1", "Message to/from an onboard terminal"),
+    sublabel("H1", "CF", "Message to/from the central fault display system"),
+    sublabel("H1", "DF", "Message to/from the digital flight data acquisition unit"),
+    sublabel("H1", "EC", "Message to/from the engine display system"),
+    sublabel("H1", "EI", "Engine report"),
+    sublabel("H1", "M1", "Message to/from the left flight management computer"),
+    sublabel("H1", "M2", "Message to/from the right flight management computer"),
+    sublabel("H1", "M3", "Message to/from the center flight management computer"),
+    label("H2", "Meteorological report"),
+    label("Q0", "Link test"),
+    label("QA", "OUT/fuel report"),
+    label("QB", "OFF report"),
+    label("QC", "ON report"),
+    label("QD", "IN/fuel report"),
+    label("QE", "OUT/fuel/destination report"),
+    label("QF", "OFF/destination report"),
+    label("QG", "OUT/return IN report"),
+    label("QH", "OUT report"),
+    label("QK", "Landing report"),
+    label("QL", "Arrival report"),
+    label("QM", "Arrival information report"),
+    label("QN", "Diversion report"),
+    label("QP", "OUT report"),
+    label("QQ", "OFF report"),
+    label("QR", "ON report"),
+    label("QS", "IN report"),
+    label("QT", "OUT/return IN report"),
+    label("SA", "Media advisory"),
+    label("SQ", "Squitter message"),
+];
+
+// NOTE: label/sublabel pairs take precedence over the plain label description
+pub fn describe_label(label: &str, sublabel: Option<&str>) -> Option<&'static str> {
+    if let Some(sublabel) = sublabel {
+        if let Some(entry) = ACARS_LABELS
+            .iter()
+            .find(|x| x.label == label && x.sublabel == Some(sublabel))
+        {
+            return Some(entry.description);
+        }
+    }
+
+    ACARS_LABELS
+        .iter()
+        .find(|x| x.label == label && x.sublabel.is_none())
+        .map(|x| x.description)
+}
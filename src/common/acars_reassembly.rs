@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::*;
+use tokio::time::Instant;
+
+use super::frame::CommonFrame;
+
+pub const DEFAULT_REASSEMBLY_TIMEOUT_SECS: u64 = 120;
+
+struct PendingMessage {
+    first_seen: Instant,
+    blocks: Vec<(String, String)>,
+}
+
+// NOTE: multi-block ACARS messages are keyed by (tail or ICAO, label, msg_num); blocks are ordered by msg_num_seq
+pub struct AcarsReassembler {
+    pending: HashMap<(String, String, String), PendingMessage>,
+    timeout: Duration,
+}
+
+impl AcarsReassembler {
+    pub fn new(timeout: Duration) -> AcarsReassembler {
+        AcarsReassembler {
+            pending: HashMap::new(),
+            timeout,
+        }
+    }
+
+    fn key(frame: &CommonFrame) -> Option<(String, String, String)> {
+        let acars = frame.acars.as_ref()?;
+        let ident = acars
+            .tail
+            .as_ref()
+            .filter(|x| !x.trim().is_empty())
+            .or(frame.src.icao.as_ref())?;
+        let msg_num = acars.msg_num.as_ref()?;
+
+        Some((ident.trim().to_string(), acars.label.clone(), msg_num.clone()))
+    }
+
+    // NOTE: returns a new frame carrying the joined text once the final block of a multi-block message arrives
+    pub fn push(&mut self, frame: &CommonFrame) -> Option<CommonFrame> {
+        self.expire();
+
+        let key = Self::key(frame)?;
+        let acars = frame.acars.as_ref()?;
+        let seq = acars.msg_num_seq.clone().unwrap_or_default();
+        let text = acars.text.clone().unwrap_or_default();
+
+        if acars.more {
+            let pending = self.pending.entry(key).or_insert(PendingMessage {
+                first_seen: Instant::now(),
+                blocks: Vec::new(),
+            });
+            match pending.blocks.iter_mut().find(|(x, _)| *x == seq) {
+                Some(block) => block.1 = text,
+                None => pending.blocks.push((seq, text)),
+            }
+            return None;
+        }
+
+        let mut pending = self.pending.remove(&key)?;
+        if !pending.blocks.iter().any(|(x, _)| *x == seq) {
+            pending.blocks.push((seq, text));
+        }
+        pending.blocks.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut reassembled = frame.clone();
+        if let Some(ref mut acars) = reassembled.acars {
+            acars.text = Some(
+                pending
+                    .blocks
+                    .iter()
+                    .map(|(_, text)| text.as_str())
+                    .collect::<String>(),
+            );
+            acars.msg_num_seq = None;
+        }
+        reassembled.indexed.reassembled_blocks = Some(pending.blocks.len() as u32);
+
+        debug!(
+            "Reassembled {} ACARS blocks for {} label {} msg {}",
+            pending.blocks.len(),
+            key.0,
+            key.1,
+            key.2
+        );
+
+        Some(reassembled)
+    }
+
+    pub fn expire(&mut self) {
+        let timeout = self.timeout;
+        self.pending.retain(|key, pending| {
+            let keep = pending.first_seen.elapsed() < timeout;
+            if !keep {
+                debug!(
+                    "Dropping incomplete ACARS message for {} label {} msg {} with {} block(s)",
+                    key.0,
+                    key.1,
+                    key.2,
+                    pending.blocks.len()
+                );
+            }
+            keep
+        });
+    }
+}
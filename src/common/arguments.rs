@@ -1,20 +1,61 @@
+use std::env;
+use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::{arg, ArgMatches, Command};
+use tokio::io;
+
+use crate::server::db::summary_cache::DEFAULT_SUMMARY_CACHE_TTL_SECS;
+use crate::utils::logging::{LogSettings, DEFAULT_LOG_FILE_COUNT, DEFAULT_LOG_FILE_MAX_BYTES};
+
+use super::frame::cff_field_paths;
+use super::frame_queue::{BackpressurePolicy, BACKPRESSURE_BLOCK};
+use super::http::{default_user_agent, DEFAULT_HTTP_TIMEOUT_SECS};
+
+pub const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_ELASTIC_INDEX: &'static str = "xng_acars_db";
+pub const DEFAULT_MIN_EVENT_INTERVAL_SECS: u64 = 0;
+pub const DEFAULT_AUTO_VACUUM_INTERVAL_HOURS: u64 = 0;
+pub const DEFAULT_MAX_FUTURE_SKEW_SECS: u64 = 5;
+
+const MAX_COORD_PRECISION: u32 = 15;
+const MAX_AUTO_VACUUM_INTERVAL_HOURS: u64 = 24 * 365;
+const ENV_XNG_API_TOKEN: &'static str = "XNG_API_TOKEN";
 
 pub fn register_common_arguments(cmd: Command) -> Command {
     cmd.args(&[
         arg!(-q --quiet "Silence all output"),
         arg!(-v --verbose ... "Verbose level"),
-        arg!(--"api-token" <TOKEN> "Sets up an authentication token for API server access"),
+        arg!(--"log-file" <PATH> "Also write log output to the given file, rotated by size"),
+        arg!(--"log-file-max-bytes" <BYTES> "Size in bytes at which the log file is rotated, 0 disables rotation (default: 10485760)"),
+        arg!(--"log-file-count" <COUNT> "Number of rotated log files to retain (default: 5)"),
+        arg!(--"log-file-only" "Only write log output to the log file, not stderr"),
+        arg!(--"api-token" <TOKEN> "Sets up an authentication token for API server access (visible to other local users, prefer --api-token-file or XNG_API_TOKEN)"),
+        arg!(--"api-token-file" <PATH> "Read the API server authentication token from a file, takes precedence over XNG_API_TOKEN and --api-token"),
+        arg!(--"swarm-token" <TOKEN> "Shared secret feeders authenticate to the swarm server's frame ingest with"),
         arg!(--"disable-cross-site" "Disable cross site requests"),
         arg!(--"listen-host" <HOST> "Host for API server to listen on"),
         arg!(--"listen-port" <PORT> "Port for API server to listen on"),
         arg!(--elastic <URL> "Export processed common JSON frames to ElasticSearch"),
-        arg!(--"elastic-index" <INDEXNAME> "ElasticSearch Index name to use for storing common JSON frames"),
+        arg!(--"elastic-index" <INDEXNAME> "ElasticSearch Index name to use for storing common JSON frames. Defaults to xng_<MODULE> for listening modules and xng_acars_db for the server"),
         arg!(--"validate-es-cert" "Validate ElasticSearch server certificate"),
+        arg!(--"es-exclude-fields" <FIELDS> "Comma separated dotted frame field paths to strip before indexing to ElasticSearch, e.g. acars.text,paths"),
+        arg!(--"coord-precision" <DECIMALS> "Round latitudes and longitudes to this many decimal places before storing them in the state DB or indexing them to ElasticSearch (default: full precision)"),
+        arg!(--"http-timeout-secs" <SECONDS> "Connect and request timeout for outbound HTTP calls (default: 30)"),
+        arg!(--"user-agent" <AGENT> "User-Agent sent with outbound HTTP calls, also sent to ElasticSearch as X-Opaque-Id (default: xng/<VERSION>)"),
         arg!(--"state-db" <URL> "SQLite3 database to store state metrics. URL should begin with sqlite://"),
         arg!(--"disable-state-db" "Disables SQLite3 database to store state metrics."),
+        arg!(--"state-db-read-pool" "Serve API queries from a separate read-only SQLite3 connection pool (enables WAL journal mode)"),
+        arg!(--"state-db-auto-recover" "Reconnect to (and recreate if missing) the SQLite3 database after repeated write failures"),
+        arg!(--"auto-vacuum-interval-hours" <HOURS> "Periodically VACUUM the SQLite3 database to reclaim space freed by cleanups, 0 disables (default: 0, max: 8760)"),
+        arg!(--"keep-broadcast" "Record frames addressed to the broadcast address (FFFFFF) as an aircraft in the SQLite3 database"),
+        arg!(--"min-event-interval-secs" <SECONDS> "Minimum time between stored position events per aircraft in the SQLite3 database, 0 stores every event (default: 0)"),
+        arg!(--"max-future-skew-secs" <SECONDS> "Reject frames timestamped more than this many seconds ahead of the local clock, 0 disables (default: 5)"),
+        arg!(--"frame-buffer" <SIZE> "Number of frames buffered between frame intake and processing"),
+        arg!(--"on-backpressure" <POLICY> "Policy when the frame buffer is full. Valid policies: block (default), drop-oldest, drop-newest"),
+        arg!(--"summary-cache-ttl-secs" <SECONDS> "Interval at which the cached latest position per aircraft used by the flight summary API is reloaded, 0 disables the cache (default: 60, max: 86400)"),
+        arg!(--"shutdown-timeout-secs" <SECONDS> "Maximum time to wait for pending work to finish on exit before remaining tasks are aborted (default: 30)"),
     ])
 }
 
@@ -33,8 +74,41 @@ pub fn extract_soapysdr_driver(args: &Vec<String>) -> Option<String> {
         .find(|x| x.to_ascii_lowercase().starts_with("driver="))
 }
 
-pub fn parse_api_token(args: &ArgMatches) -> Option<&String> {
-    args.get_one::<String>("api-token")
+// NOTE: precedence is --api-token-file, then the XNG_API_TOKEN env var, then --api-token
+pub fn parse_api_token(args: &ArgMatches) -> Result<Option<String>, io::Error> {
+    if let Some(path) = args.get_one::<String>("api-token-file") {
+        let token = match fs::read_to_string(path) {
+            Ok(x) => x.trim().to_string(),
+            Err(e) => {
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("Unable to read API token file {}: {}", path, e.to_string()),
+                ))
+            }
+        };
+        if token.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("API token file {} is empty", path),
+            ));
+        }
+        return Ok(Some(token));
+    }
+
+    if let Ok(token) = env::var(ENV_XNG_API_TOKEN) {
+        if !token.trim().is_empty() {
+            return Ok(Some(token.trim().to_string()));
+        }
+    }
+
+    Ok(args
+        .get_one::<String>("api-token")
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty()))
+}
+
+pub fn parse_swarm_token(args: &ArgMatches) -> Option<&String> {
+    args.get_one::<String>("swarm-token")
 }
 
 pub fn parse_disable_cross_site(args: &ArgMatches) -> bool {
@@ -58,9 +132,75 @@ pub fn parse_elastic_url(args: &ArgMatches) -> Option<&String> {
     args.get_one::<String>("elastic")
 }
 
-pub fn parse_elastic_index(args: &ArgMatches) -> String {
+pub fn parse_elastic_index(args: &ArgMatches, default_index: &str) -> String {
     args.get_one::<String>("elastic-index")
-        .unwrap_or(&String::from("xng_acars_db"))
+        .unwrap_or(&String::from(default_index))
+        .clone()
+}
+
+pub fn parse_es_exclude_fields(args: &ArgMatches) -> Result<Vec<String>, io::Error> {
+    let Some(raw_fields) = args.get_one::<String>("es-exclude-fields") else {
+        return Ok(Vec::new());
+    };
+
+    let known_fields = cff_field_paths();
+    let mut fields: Vec<String> = Vec::new();
+    for field in raw_fields.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        if !known_fields.iter().any(|x| x == field) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown common frame field in --es-exclude-fields: {}", field),
+            ));
+        }
+        fields.push(field.to_string());
+    }
+
+    Ok(fields)
+}
+
+pub fn parse_coord_precision(args: &ArgMatches) -> Result<Option<u32>, io::Error> {
+    let Some(raw_precision) = args.get_one::<String>("coord-precision") else {
+        return Ok(None);
+    };
+
+    match raw_precision.trim().parse::<u32>() {
+        Ok(x) if x <= MAX_COORD_PRECISION => Ok(Some(x)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid --coord-precision, expected 0 to {} decimal places: {}", MAX_COORD_PRECISION, raw_precision),
+        )),
+    }
+}
+
+pub fn parse_http_timeout(args: &ArgMatches) -> Result<Duration, io::Error> {
+    let Some(raw_timeout) = args.get_one::<String>("http-timeout-secs") else {
+        return Ok(Duration::from_secs(DEFAULT_HTTP_TIMEOUT_SECS));
+    };
+
+    // NOTE: a zero timeout would fail every outbound request immediately
+    match raw_timeout.trim().parse::<u64>() {
+        Ok(x) if x > 0 => Ok(Duration::from_secs(x)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid --http-timeout-secs, expected a positive number of seconds: {}", raw_timeout),
+        )),
+    }
+}
+
+pub fn parse_user_agent(args: &ArgMatches) -> String {
+    args.get_one::<String>("user-agent")
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+        .unwrap_or(default_user_agent())
+}
+
+pub fn parse_kafka_brokers(args: &ArgMatches) -> Option<&String> {
+    args.get_one::<String>("kafka-brokers")
+}
+
+pub fn parse_kafka_topic(args: &ArgMatches) -> String {
+    args.get_one::<String>("kafka-topic")
+        .unwrap_or(&String::from("xng_frames"))
         .clone()
 }
 
@@ -74,6 +214,98 @@ pub fn parse_disable_state_db(args: &ArgMatches) -> bool {
     args.get_flag("disable-state-db")
 }
 
+pub fn parse_state_db_read_pool(args: &ArgMatches) -> bool {
+    args.get_flag("state-db-read-pool")
+}
+
+pub fn parse_state_db_auto_recover(args: &ArgMatches) -> bool {
+    args.get_flag("state-db-auto-recover")
+}
+
+pub fn parse_auto_vacuum_interval(args: &ArgMatches) -> Option<Duration> {
+    let hours = args
+        .get_one::<String>("auto-vacuum-interval-hours")
+        .unwrap_or(&String::from("default"))
+        .parse::<u64>()
+        .unwrap_or(DEFAULT_AUTO_VACUUM_INTERVAL_HOURS);
+    if hours == 0 {
+        return None;
+    }
+
+    // NOTE: capped since the first tick is scheduled as now + interval, which panics on overflow
+    let hours = hours.min(MAX_AUTO_VACUUM_INTERVAL_HOURS);
+
+    Some(Duration::from_secs(hours * 3600))
+}
+
+pub fn parse_keep_broadcast(args: &ArgMatches) -> bool {
+    args.get_flag("keep-broadcast")
+}
+
+pub fn parse_min_event_interval(args: &ArgMatches) -> u64 {
+    args.get_one::<String>("min-event-interval-secs")
+        .unwrap_or(&String::from("default"))
+        .parse::<u64>()
+        .unwrap_or(DEFAULT_MIN_EVENT_INTERVAL_SECS)
+}
+
+pub fn parse_max_future_skew(args: &ArgMatches) -> u64 {
+    args.get_one::<String>("max-future-skew-secs")
+        .unwrap_or(&String::from("default"))
+        .parse::<u64>()
+        .unwrap_or(DEFAULT_MAX_FUTURE_SKEW_SECS)
+}
+
+pub fn parse_frame_buffer(args: &ArgMatches, default_size: usize) -> usize {
+    args.get_one::<String>("frame-buffer")
+        .unwrap_or(&String::from("default"))
+        .parse::<usize>()
+        .unwrap_or(default_size)
+}
+
+pub fn parse_backpressure_policy(args: &ArgMatches) -> Result<BackpressurePolicy, io::Error> {
+    BackpressurePolicy::from_str(
+        args.get_one::<String>("on-backpressure")
+            .unwrap_or(&String::from(BACKPRESSURE_BLOCK))
+            .as_str(),
+    )
+}
+
+pub fn parse_summary_cache_ttl(args: &ArgMatches) -> u64 {
+    args.get_one::<String>("summary-cache-ttl-secs")
+        .unwrap_or(&String::from("default"))
+        .parse::<u64>()
+        .unwrap_or(DEFAULT_SUMMARY_CACHE_TTL_SECS)
+}
+
+pub fn parse_shutdown_timeout(args: &ArgMatches) -> Duration {
+    Duration::from_secs(
+        args.get_one::<String>("shutdown-timeout-secs")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+    )
+}
+
+pub fn parse_log_settings(args: &ArgMatches) -> LogSettings {
+    LogSettings {
+        quiet: args.get_flag("quiet"),
+        verbose_level: *args.get_one::<u8>("verbose").unwrap_or(&0),
+        log_file: args.get_one::<String>("log-file").map(PathBuf::from),
+        log_file_only: args.get_flag("log-file-only"),
+        max_bytes: args
+            .get_one::<String>("log-file-max-bytes")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_LOG_FILE_MAX_BYTES),
+        max_files: args
+            .get_one::<String>("log-file-count")
+            .unwrap_or(&String::from("default"))
+            .parse::<usize>()
+            .unwrap_or(DEFAULT_LOG_FILE_COUNT),
+    }
+}
+
 pub fn parse_bin_path(args: &ArgMatches, default_path: &str) -> PathBuf {
     PathBuf::from(
         args.get_one::<String>("bin")
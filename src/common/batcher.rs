@@ -7,24 +7,43 @@ use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 
-use super::es_utils::bulk_index;
+use super::es_utils::{bulk_index, strip_frame_fields};
 use super::frame::CommonFrame;
 
 pub fn create_es_batch_task(
     client: &Elasticsearch,
     index: &String,
+    exclude_fields: &Vec<String>,
+    coord_precision: Option<u32>,
     batch: Data<Mutex<Vec<CommonFrame>>>,
     duration: Duration,
 ) -> JoinHandle<()> {
     let client = client.clone();
     let index = index.clone();
+    let exclude_fields = exclude_fields.clone();
 
     tokio::spawn(async move {
         sleep(duration).await;
 
         let mut batch = batch.lock().await;
 
-        if let Err(e) = bulk_index(&client, &index, batch.as_ref()).await {
+        let result = if exclude_fields.is_empty() && coord_precision.is_none() {
+            bulk_index(&client, &index, batch.as_ref()).await
+        } else {
+            let mut docs = Vec::with_capacity(batch.len());
+            for frame in batch.iter() {
+                let mut frame = frame.clone();
+                if let Some(decimals) = coord_precision {
+                    frame.round_coords(decimals);
+                }
+                match strip_frame_fields(&frame, &exclude_fields) {
+                    Ok(x) => docs.push(x),
+                    Err(e) => warn!("Failed to serialize frame for bulk index: {}", e.to_string()),
+                }
+            }
+            bulk_index(&client, &index, &docs).await
+        };
+        if let Err(e) = result {
             warn!("Bulk index ran into some issues - {}", e.to_string());
         }
 
@@ -1,17 +1,49 @@
 use elasticsearch::auth::Credentials;
 use elasticsearch::cert::CertificateValidation;
 use elasticsearch::http::transport::{SingleNodeConnectionPool, TransportBuilder};
+use elasticsearch::indices::{IndicesCreateParts, IndicesDeleteParts, IndicesExistsParts};
 use elasticsearch::{BulkOperation, Elasticsearch};
 
-use reqwest::Url;
+use log::*;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{StatusCode, Url};
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::io;
+use tokio::time::sleep;
 
 use super::frame::CommonFrame;
 
+pub const DEFAULT_ES_ADMIN_RETRIES: u32 = 2;
+
+const DEFAULT_ES_ADMIN_RETRY_BASE_SECS: u64 = 1;
+const DEFAULT_ES_ADMIN_RETRY_MAX_SECS: u64 = 30;
+
+// NOTE: index frames are bulk indexed into, shared with the API so the index can be created at runtime
+pub struct EsIndexTarget {
+    pub client: Elasticsearch,
+    pub index: String,
+}
+
+impl EsIndexTarget {
+    pub fn new(es_url: &Url, validate: bool, timeout: Duration, user_agent: &str, index: &String) -> Result<EsIndexTarget, elasticsearch::Error> {
+        let mut es_url = es_url.clone();
+        Ok(EsIndexTarget {
+            client: create_es_client(&mut es_url, validate, timeout, user_agent)?,
+            index: index.clone(),
+        })
+    }
+}
+
 pub fn create_es_client(
     es_url: &mut Url,
     validate: bool,
+    timeout: Duration,
+    user_agent: &str,
 ) -> Result<Elasticsearch, elasticsearch::Error> {
     let credentials = match (es_url.username(), es_url.password()) {
         ("", _) | (_, None) => None,
@@ -19,7 +51,13 @@ pub fn create_es_client(
     };
 
     let conn_pool = SingleNodeConnectionPool::new(es_url.clone());
-    let mut builder = TransportBuilder::new(conn_pool);
+    let mut builder = TransportBuilder::new(conn_pool).timeout(timeout);
+
+    // NOTE: the Elasticsearch client always sends its own User-Agent, X-Opaque-Id shows up in ES task and slow logs instead
+    match HeaderValue::from_str(user_agent) {
+        Ok(x) => builder = builder.header(HeaderName::from_static("x-opaque-id"), x),
+        Err(e) => warn!("Not sending X-Opaque-Id to Elasticsearch, invalid user agent {}: {}", user_agent, e.to_string()),
+    }
 
     builder = match credentials {
         Some(c) => {
@@ -42,10 +80,46 @@ pub fn create_es_client(
     Ok(Elasticsearch::new(transport))
 }
 
-pub async fn bulk_index(
+// NOTE: arrays along the path, such as paths, have the field removed from each of their elements
+pub fn strip_field(doc: &mut Value, path: &str) {
+    let (head, rest) = match path.split_once('.') {
+        Some((x, y)) => (x, Some(y)),
+        None => (path, None),
+    };
+
+    match doc {
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                strip_field(item, path);
+            }
+        }
+        Value::Object(fields) => match rest {
+            Some(rest) => {
+                if let Some(child) = fields.get_mut(head) {
+                    strip_field(child, rest);
+                }
+            }
+            None => {
+                fields.remove(head);
+            }
+        },
+        _ => {}
+    }
+}
+
+pub fn strip_frame_fields(frame: &CommonFrame, exclude_fields: &Vec<String>) -> Result<Value, io::Error> {
+    let mut doc = serde_json::to_value(frame)?;
+    for path in exclude_fields.iter() {
+        strip_field(&mut doc, path);
+    }
+
+    Ok(doc)
+}
+
+pub async fn bulk_index<T: Serialize>(
     client: &Elasticsearch,
     index: &String,
-    frames: &Vec<CommonFrame>,
+    frames: &Vec<T>,
 ) -> Result<(), io::Error> {
     let body: Vec<BulkOperation<_>> = frames
         .iter()
@@ -94,12 +168,164 @@ pub async fn bulk_index(
     Ok(())
 }
 
+pub async fn es_index_exists(client: &Elasticsearch, index: &str) -> Result<bool, io::Error> {
+    let response = match client
+        .indices()
+        .exists(IndicesExistsParts::Index(&[index]))
+        .send()
+        .await
+    {
+        Ok(x) => x,
+        Err(e) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to determine existence of index {}: {}", index, e.to_string()),
+            ))
+        }
+    };
+
+    match response.status_code() {
+        x if x.is_success() => Ok(true),
+        StatusCode::NOT_FOUND => Ok(false),
+        x => Err(io::Error::new(
+            es_status_error_kind(x, ""),
+            format!("Unexpected status while checking existence of index {}: {:?}", index, x),
+        )),
+    }
+}
+
+// NOTE: InvalidInput marks requests Elasticsearch rejected outright, which retrying will not fix. AlreadyExists
+//       and NotFound are split out so retry_es_call can tell a create or delete that already went through
+fn es_status_error_kind(status: StatusCode, body: &str) -> io::ErrorKind {
+    if status == StatusCode::NOT_FOUND {
+        io::ErrorKind::NotFound
+    } else if status == StatusCode::BAD_REQUEST && body.contains("resource_already_exists_exception") {
+        io::ErrorKind::AlreadyExists
+    } else if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+        io::ErrorKind::InvalidInput
+    } else {
+        io::ErrorKind::Other
+    }
+}
+
+// NOTE: an attempt whose response was lost may still have gone through, so a retry failing with settled_kind
+//       (the index already existing after a create, or already gone after a delete) counts as success
+pub async fn retry_es_call<T, F, Fut>(what: &str, retries: u32, settled_kind: Option<io::ErrorKind>, mut call: F) -> Result<T, io::Error>
+where
+    T: Default,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, io::Error>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match call().await {
+            Ok(x) => {
+                if attempt > 0 {
+                    info!("{} succeeded after {} attempts", what, attempt + 1);
+                }
+                return Ok(x);
+            }
+            Err(e) if attempt > 0 && Some(e.kind()) == settled_kind => {
+                info!("{} had already gone through before attempt {} ({})", what, attempt + 1, e.to_string());
+                return Ok(T::default());
+            }
+            Err(e)
+                if matches!(e.kind(), io::ErrorKind::InvalidInput | io::ErrorKind::AlreadyExists | io::ErrorKind::NotFound)
+                    || attempt >= retries =>
+            {
+                return Err(e)
+            }
+            Err(e) => {
+                let wait_secs = (DEFAULT_ES_ADMIN_RETRY_BASE_SECS << attempt.min(16)).min(DEFAULT_ES_ADMIN_RETRY_MAX_SECS);
+                warn!("{} failed ({}), retrying in {} seconds ({}/{})", what, e.to_string(), wait_secs, attempt + 1, retries);
+                sleep(Duration::from_secs(wait_secs)).await;
+
+                attempt += 1;
+            }
+        }
+    }
+}
+
+pub async fn create_es_index(client: &Elasticsearch, index: &str, mapping: Value) -> Result<(), io::Error> {
+    let response = match client
+        .indices()
+        .create(IndicesCreateParts::Index(index))
+        .body(mapping)
+        .send()
+        .await
+    {
+        Ok(x) => x,
+        Err(e) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to create index {} with mapping: {}", index, e.to_string()),
+            ))
+        }
+    };
+
+    let status = response.status_code();
+    let body = response.text().await.unwrap_or(String::from("None"));
+    trace!("Response:\n{}", body);
+
+    if !status.is_success() {
+        return Err(io::Error::new(
+            es_status_error_kind(status, &body),
+            format!("Creation of index {} failed: error code = {:?}, response = {}", index, status, body),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn delete_es_index(client: &Elasticsearch, index: &str) -> Result<(), io::Error> {
+    let response = match client
+        .indices()
+        .delete(IndicesDeleteParts::Index(&[index]))
+        .send()
+        .await
+    {
+        Ok(x) => x,
+        Err(e) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to send index {} deletion request: {}", index, e.to_string()),
+            ))
+        }
+    };
+
+    let status = response.status_code();
+    let body = response.text().await.unwrap_or(String::from("None"));
+    trace!("Response:\n{}", body);
+
+    if !status.is_success() {
+        return Err(io::Error::new(
+            es_status_error_kind(status, &body),
+            format!("Deletion of index {} failed: error code = {:?}, response = {}", index, status, body),
+        ));
+    }
+
+    Ok(())
+}
+
 pub fn get_xng_index_mapping() -> Value {
     json!({
         "mappings": {
             "dynamic": "true",
             "date_detection": false,
             "numeric_detection": false,
+            "properties": {
+                "indexed": {
+                    "properties": {
+                        "flight_id": {
+                            "type": "keyword"
+                        },
+                        "flight_leg_num": {
+                            "type": "integer"
+                        }
+                    }
+                }
+            },
             "dynamic_templates": [
                 {
                     "frequency": {
@@ -143,3 +369,53 @@ pub fn get_xng_index_mapping() -> Value {
         }
     })
 }
+
+pub fn merge_json(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+pub fn load_xng_index_mapping(mapping_file: Option<&PathBuf>) -> Result<Value, io::Error> {
+    let mut mapping = get_xng_index_mapping();
+
+    if let Some(path) = mapping_file {
+        let contents = fs::read_to_string(path)?;
+        let overrides: Value = match serde_json::from_str(contents.as_str()) {
+            Ok(x) => x,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Mapping file {} is not valid JSON: {}",
+                        path.to_string_lossy(),
+                        e.to_string()
+                    ),
+                ))
+            }
+        };
+        if !overrides.is_object() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Mapping file {} must contain a JSON object",
+                    path.to_string_lossy()
+                ),
+            ));
+        }
+
+        merge_json(&mut mapping, overrides);
+    }
+
+    Ok(mapping)
+}
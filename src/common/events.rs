@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct GroundStationChangeEvent {
     pub ts: String,
     pub id: Value,
@@ -18,7 +19,35 @@ impl GroundStationChangeEvent {
         }
     }
 
+    // NOTE: HFDL reports ground station IDs as numbers while VDL2 uses hexadecimal strings
+    pub fn gs_id(&self) -> Option<u64> {
+        match &self.id {
+            Value::Number(x) => x.as_u64(),
+            Value::String(x) => u64::from_str_radix(x, 16).ok(),
+            _ => None,
+        }
+    }
+
     pub fn pretty_name(&self) -> String {
         self.name.clone().unwrap_or(String::from("No Name"))
     }
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Heartbeat {
+    pub ts: String,
+    pub module: String,
+    pub listening_band: Value,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_uptime_secs: Option<i64>,
+
+    pub session_frames: u64,
+    pub uptime_secs: u64,
+}
+
+// NOTE: wrapped so heartbeats can never be mistaken for a common frame by swarm servers or webhooks
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HeartbeatMessage {
+    pub heartbeat: Heartbeat,
+}
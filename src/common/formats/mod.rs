@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use serde_valid::Validate;
 
+pub const RESERVED_ENTITY_KIND: &'static str = "Reserved";
+
 pub enum EntityType {
     Aircraft,
     GroundStation,
@@ -38,6 +40,6 @@ pub fn validate_entity_type(val: &String) -> Result<(), serde_valid::validation:
     }
 
     Err(serde_valid::validation::Error::Custom(
-        "Entity type should be \"Aircraft\" or \"Ground station\"".to_string(),
+        "Entity type should be \"Aircraft\", \"Ground station\" or \"Reserved\"".to_string(),
     ))
 }
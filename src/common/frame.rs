@@ -2,10 +2,56 @@ use super::{
     formats::validate_entity_type,
     wkt::{WKTPoint, WKTPolyline},
 };
+use crate::utils::geo::round_coord;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_valid::Validate;
+use sha2::{Digest, Sha256};
+
+pub const REDACTED_TEXT_PREFIX: &'static str = "sha256:";
+pub const BROADCAST_ADDR: &'static str = "FFFFFF";
+
+// NOTE: bump whenever a field is added to or changed in CommonFrame or its nested structs.
+//       Frames without a schema_version predate versioning and deserialize as 0.
+pub const CFF_SCHEMA_VERSION: u32 = 6;
+
+const ENTITY_FIELDS: [&'static str; 7] = ["type", "icao", "gs", "id", "callsign", "tail", "coords"];
+
+// NOTE: dotted paths of every CommonFrame field as serialized, keep in sync whenever CFF_SCHEMA_VERSION is bumped
+pub fn cff_field_paths() -> Vec<String> {
+    let mut paths: Vec<String> = [
+        "schema_version", "timestamp", "freq", "signal", "err",
+        "paths", "paths.freqs", "paths.path", "paths.party",
+        "app", "app.name", "app.version",
+        "src", "dst",
+        "indexed", "indexed.timestamp", "indexed.dst_airport", "indexed.src_airport", "indexed.flight_id",
+        "indexed.flight_leg_num", "indexed.label_description", "indexed.reassembled_blocks",
+        "metadata", "metadata.hfdl", "metadata.hfdl.kind", "metadata.hfdl.heard_on", "metadata.hfdl.heard_on.kind",
+        "metadata.hfdl.heard_on.id", "metadata.hfdl.heard_on.gs", "metadata.hfdl.heard_on.freqs", "metadata.hfdl.reason",
+        "metadata.hfdl.request_data", "metadata.hfdl.request_data.code", "metadata.hfdl.request_data.bits",
+        "metadata.hfdl.bit_rate", "metadata.hfdl.slot",
+        "metadata.vdl2", "metadata.vdl2.kind", "metadata.vdl2.x25", "metadata.vdl2.x25.pkt_type",
+        "metadata.vdl2.x25.pkt_type_name", "metadata.vdl2.x25.chan_group", "metadata.vdl2.x25.chan_num", "metadata.vdl2.x25.more",
+        "acars", "acars.mode", "acars.more", "acars.label", "acars.ack", "acars.blk_id", "acars.msg_num",
+        "acars.msg_num_seq", "acars.tail", "acars.flight", "acars.sublabel", "acars.mfi", "acars.cfi", "acars.text",
+        "from_ground_station", "raw",
+    ]
+    .iter()
+    .map(|x| x.to_string())
+    .collect();
+
+    for entity in ["src", "dst", "paths.party"] {
+        paths.extend(ENTITY_FIELDS.iter().map(|x| format!("{}.{}", entity, x)));
+    }
+
+    paths
+}
+
+pub const HFDL_FREQ_BOUNDS_MHZ: (f64, f64) = (2.0, 22.0);
+pub const VHF_FREQ_BOUNDS_MHZ: (f64, f64) = (118.0, 137.0);
+pub const L_BAND_FREQ_BOUNDS_MHZ: (f64, f64) = (1525.0, 1660.5);
 
-#[derive(Debug, Deserialize, Serialize, Validate)]
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
 pub struct ACARS {
     #[validate(min_length = 1)]
     #[validate(max_length = 1)]
@@ -58,7 +104,21 @@ pub struct ACARS {
     pub text: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl ACARS {
+    pub fn redact_text(&mut self) {
+        if let Some(ref text) = self.text {
+            if !text.starts_with(REDACTED_TEXT_PREFIX) {
+                self.text = Some(format!(
+                    "{}{}",
+                    REDACTED_TEXT_PREFIX,
+                    hex::encode(Sha256::digest(text.as_bytes()))
+                ));
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AppInfo {
     pub name: String,
     pub version: String,
@@ -94,12 +154,18 @@ impl Entity {
     pub fn is_ground_station(&self) -> bool {
         self.kind.to_lowercase() == "ground station"
     }
+
+    pub fn is_reserved(&self) -> bool {
+        self.kind.to_lowercase() == "reserved"
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        self.icao.as_ref().map_or(false, |x| x.eq_ignore_ascii_case(BROADCAST_ADDR))
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize, Validate)]
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
 pub struct PropagationPath {
-    #[validate(minimum = 2.0)]
-    #[validate(maximum = 1630.0)]
     pub freqs: Vec<f64>,
 
     pub path: WKTPolyline,
@@ -108,7 +174,7 @@ pub struct PropagationPath {
     pub party: Entity,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize, Validate)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Validate)]
 pub struct Indexed {
     #[validate(
         pattern = r"^20[1-4][0-9]-(0[0-9]|1[0-2])-([0-2][0-9]|3[0-1])T([0-1][0-9]|2[0-3]):[0-5][0-9]:[0-5][0-9]\.[0-9]{3,6}Z$"
@@ -117,20 +183,73 @@ pub struct Indexed {
 
     pub dst_airport: Option<String>,
     pub src_airport: Option<String>,
+
+    pub flight_id: Option<String>,
+    pub flight_leg_num: Option<u32>,
+
+    pub label_description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reassembled_blocks: Option<u32>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Validate)]
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
 pub struct HFDLGSEntry {
     pub kind: String,
     pub id: u8,
     pub gs: String,
 
     #[validate(minimum = 2.0)]
-    #[validate(maximum = 1630.0)]
+    #[validate(maximum = 22.0)]
     pub freqs: Vec<f64>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Validate)]
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+pub struct HFDLRequestData {
+    pub code: u16,
+    pub bits: Vec<u8>,
+}
+
+pub const HFDL_SLOT_SINGLE: &'static str = "single";
+pub const HFDL_SLOT_DOUBLE: &'static str = "double";
+
+// NOTE: slot types xng does not know about are kept as reported by the decoder
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum HFDLSlot {
+    Single,
+    Double,
+    Raw(String),
+}
+
+impl HFDLSlot {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HFDLSlot::Single => HFDL_SLOT_SINGLE,
+            HFDLSlot::Double => HFDL_SLOT_DOUBLE,
+            HFDLSlot::Raw(x) => x.as_str(),
+        }
+    }
+}
+
+// NOTE: dumphfdl and frames from older feeders report the slot as a single letter, both map to the same variants
+impl From<String> for HFDLSlot {
+    fn from(value: String) -> HFDLSlot {
+        match value.trim().to_lowercase().as_str() {
+            HFDL_SLOT_SINGLE | "s" => HFDLSlot::Single,
+            HFDL_SLOT_DOUBLE | "d" => HFDLSlot::Double,
+            _ => HFDLSlot::Raw(value),
+        }
+    }
+}
+
+impl From<HFDLSlot> for String {
+    fn from(value: HFDLSlot) -> String {
+        value.as_str().to_string()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
 pub struct HFDLMetadata {
     pub kind: String,
 
@@ -140,24 +259,88 @@ pub struct HFDLMetadata {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub request_data: Option<HFDLRequestData>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bit_rate: Option<u16>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub slot: Option<HFDLSlot>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Validate)]
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+pub struct VDL2X25Packet {
+    pub pkt_type: u32,
+    pub pkt_type_name: String,
+    pub chan_group: u32,
+    pub chan_num: u16,
+    pub more: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+pub struct VDL2Metadata {
+    pub kind: String,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub x25: Option<VDL2X25Packet>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
 pub struct Metadata {
     #[validate]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hfdl: Option<HFDLMetadata>,
+
+    #[validate]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub vdl2: Option<VDL2Metadata>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Validate)]
+impl Metadata {
+    // NOTE: only HFDL and VDL2 frames identify their bearer, any other frame may be on any supported band
+    pub fn freq_bounds_mhz(&self) -> Vec<(f64, f64)> {
+        if self.hfdl.is_some() {
+            vec![HFDL_FREQ_BOUNDS_MHZ]
+        } else if self.vdl2.is_some() {
+            vec![VHF_FREQ_BOUNDS_MHZ]
+        } else {
+            vec![HFDL_FREQ_BOUNDS_MHZ, VHF_FREQ_BOUNDS_MHZ, L_BAND_FREQ_BOUNDS_MHZ]
+        }
+    }
+}
+
+pub fn validate_bearer_freqs(
+    freq: &f64,
+    paths: &Vec<PropagationPath>,
+    metadata: &Metadata,
+) -> Result<(), serde_valid::validation::Error> {
+    let bounds = metadata.freq_bounds_mhz();
+
+    for x in std::iter::once(freq).chain(paths.iter().flat_map(|x| x.freqs.iter())) {
+        if !bounds.iter().any(|(min, max)| x >= min && x <= max) {
+            return Err(serde_valid::validation::Error::Custom(format!(
+                "Frequency {} MHz is outside of the bearer's bounds {:?}",
+                x, bounds
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+#[rule(validate_bearer_freqs(freq, paths, metadata))]
 pub struct CommonFrame {
+    #[serde(default)]
+    pub schema_version: u32,
+
     #[validate(
         pattern = r"^20[1-4][0-9]-(0[0-9]|1[0-2])-([0-2][0-9]|3[0-1])T([0-1][0-9]|2[0-3]):[0-5][0-9]:[0-5][0-9]\.[0-9]{3,6}Z$"
     )]
     pub timestamp: String,
 
-    #[validate(minimum = 2.0)]
-    #[validate(maximum = 1630.0)]
     pub freq: f64,
     pub signal: f32,
     pub err: bool,
@@ -183,4 +366,190 @@ pub struct CommonFrame {
     #[validate]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub acars: Option<ACARS>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub from_ground_station: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub raw: Option<String>,
+}
+
+impl CommonFrame {
+    // NOTE: empty frames carry no ACARS content and no coordinates for either entity
+    pub fn is_empty(&self) -> bool {
+        self.acars.is_none()
+            && self.src.coords.is_none()
+            && self.dst.as_ref().map_or(true, |x| x.coords.is_none())
+    }
+
+    pub fn has_reserved_entity(&self) -> bool {
+        self.src.is_reserved() || self.dst.as_ref().map_or(false, |x| x.is_reserved())
+    }
+
+    // NOTE: the later of timestamp and indexed.timestamp, unparseable timestamps are never considered ahead
+    pub fn secs_ahead_of(&self, now: DateTime<Utc>) -> i64 {
+        [self.timestamp.as_str(), self.indexed.timestamp.as_str()]
+            .iter()
+            .filter_map(|x| DateTime::parse_from_rfc3339(x).ok())
+            .map(|x| (x.with_timezone(&Utc) - now).num_seconds())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // NOTE: places the aircraft in src and the ground station in dst, recording the original direction instead
+    pub fn normalize_direction(&mut self) {
+        let from_ground_station = self.src.is_ground_station();
+        if from_ground_station {
+            if let Some(dst) = self.dst.take() {
+                self.dst = Some(std::mem::replace(&mut self.src, dst));
+            }
+        }
+        self.from_ground_station = Some(from_ground_station);
+    }
+
+    // NOTE: altitude is left alone, only latitude and longitude carry false precision
+    pub fn round_coords(&mut self, decimals: u32) {
+        let round_point = |pt: &mut WKTPoint| {
+            pt.x = round_coord(pt.x, decimals);
+            pt.y = round_coord(pt.y, decimals);
+        };
+
+        if let Some(ref mut coords) = self.src.coords {
+            round_point(coords);
+        }
+        if let Some(ref mut coords) = self.dst.as_mut().and_then(|x| x.coords.as_mut()) {
+            round_point(coords);
+        }
+        for path in self.paths.iter_mut() {
+            if let Some(ref mut coords) = path.party.coords {
+                round_point(coords);
+            }
+            for pt in path.path.points.iter_mut() {
+                pt.0 = round_coord(pt.0, decimals);
+                pt.1 = round_coord(pt.1, decimals);
+            }
+        }
+    }
+
+    // NOTE: raw decoder output also contains the message text so it is dropped as well
+    pub fn redact_acars_text(&mut self) {
+        if let Some(ref mut acars) = self.acars {
+            acars.redact_text();
+        }
+        self.raw = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON_MHZ: f64 = 0.001;
+
+    fn hfdl_metadata() -> Metadata {
+        Metadata {
+            hfdl: Some(HFDLMetadata {
+                kind: String::from("Aircraft"),
+                heard_on: Vec::new(),
+                reason: None,
+                request_data: None,
+                bit_rate: None,
+                slot: None,
+            }),
+            vdl2: None,
+        }
+    }
+
+    fn vdl2_metadata() -> Metadata {
+        Metadata {
+            hfdl: None,
+            vdl2: Some(VDL2Metadata {
+                kind: String::from("ACARS"),
+                x25: None,
+            }),
+        }
+    }
+
+    fn no_metadata() -> Metadata {
+        Metadata { hfdl: None, vdl2: None }
+    }
+
+    fn path_with_freq(freq: f64) -> PropagationPath {
+        PropagationPath {
+            freqs: vec![freq],
+            path: WKTPolyline { points: Vec::new() },
+            party: Entity {
+                kind: String::from("Ground station"),
+                icao: None,
+                gs: None,
+                id: Some(1),
+                callsign: None,
+                tail: None,
+                coords: None,
+            },
+        }
+    }
+
+    fn assert_bounds(metadata: &Metadata, (min, max): (f64, f64)) {
+        for freq in [min, min + EPSILON_MHZ, max - EPSILON_MHZ, max] {
+            assert!(validate_bearer_freqs(&freq, &Vec::new(), metadata).is_ok(), "{} MHz rejected", freq);
+        }
+        for freq in [min - EPSILON_MHZ, max + EPSILON_MHZ] {
+            assert!(validate_bearer_freqs(&freq, &Vec::new(), metadata).is_err(), "{} MHz accepted", freq);
+        }
+    }
+
+    #[test]
+    fn hfdl_frames_are_bound_to_hf() {
+        let metadata = hfdl_metadata();
+        assert_bounds(&metadata, HFDL_FREQ_BOUNDS_MHZ);
+
+        for freq in [VHF_FREQ_BOUNDS_MHZ.0, L_BAND_FREQ_BOUNDS_MHZ.0] {
+            assert!(validate_bearer_freqs(&freq, &Vec::new(), &metadata).is_err());
+        }
+    }
+
+    #[test]
+    fn vdl2_frames_are_bound_to_vhf() {
+        let metadata = vdl2_metadata();
+        assert_bounds(&metadata, VHF_FREQ_BOUNDS_MHZ);
+
+        for freq in [HFDL_FREQ_BOUNDS_MHZ.1, L_BAND_FREQ_BOUNDS_MHZ.0] {
+            assert!(validate_bearer_freqs(&freq, &Vec::new(), &metadata).is_err());
+        }
+    }
+
+    #[test]
+    fn frames_without_bearer_metadata_accept_any_supported_band() {
+        let metadata = no_metadata();
+        for bounds in [HFDL_FREQ_BOUNDS_MHZ, VHF_FREQ_BOUNDS_MHZ, L_BAND_FREQ_BOUNDS_MHZ] {
+            assert_bounds(&metadata, bounds);
+        }
+
+        // NOTE: between the bands rather than just outside of one that another band covers
+        for freq in [(HFDL_FREQ_BOUNDS_MHZ.1 + VHF_FREQ_BOUNDS_MHZ.0) / 2.0, (VHF_FREQ_BOUNDS_MHZ.1 + L_BAND_FREQ_BOUNDS_MHZ.0) / 2.0] {
+            assert!(validate_bearer_freqs(&freq, &Vec::new(), &metadata).is_err());
+        }
+    }
+
+    #[test]
+    fn path_freqs_are_checked_against_the_same_bounds() {
+        let metadata = hfdl_metadata();
+        let (min, max) = HFDL_FREQ_BOUNDS_MHZ;
+
+        assert!(validate_bearer_freqs(&min, &vec![path_with_freq(max)], &metadata).is_ok());
+        assert!(validate_bearer_freqs(&min, &vec![path_with_freq(max + EPSILON_MHZ)], &metadata).is_err());
+        assert!(validate_bearer_freqs(&max, &vec![path_with_freq(min - EPSILON_MHZ)], &metadata).is_err());
+    }
+
+    #[test]
+    fn hfdl_slot_letters_and_names_share_a_vocabulary() {
+        for (raw, slot) in [("S", HFDLSlot::Single), ("single", HFDLSlot::Single), ("D", HFDLSlot::Double), (" Double", HFDLSlot::Double)] {
+            assert_eq!(HFDLSlot::from(raw.to_string()), slot);
+        }
+        assert_eq!(HFDLSlot::from(String::from("X")).as_str(), "X");
+
+        let slot: HFDLSlot = serde_json::from_str("\"S\"").unwrap();
+        assert_eq!(serde_json::to_string(&slot).unwrap(), format!("\"{}\"", HFDL_SLOT_SINGLE));
+    }
 }
@@ -0,0 +1,246 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io;
+use tokio::sync::Notify;
+
+use super::frame::CommonFrame;
+
+pub const BACKPRESSURE_BLOCK: &'static str = "block";
+pub const BACKPRESSURE_DROP_OLDEST: &'static str = "drop-oldest";
+pub const BACKPRESSURE_DROP_NEWEST: &'static str = "drop-newest";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackpressurePolicy {
+    Block,
+    DropOldest,
+    DropNewest,
+}
+
+impl BackpressurePolicy {
+    pub fn from_str(value: &str) -> Result<BackpressurePolicy, io::Error> {
+        match value.to_lowercase().as_str() {
+            BACKPRESSURE_BLOCK => Ok(BackpressurePolicy::Block),
+            BACKPRESSURE_DROP_OLDEST => Ok(BackpressurePolicy::DropOldest),
+            BACKPRESSURE_DROP_NEWEST => Ok(BackpressurePolicy::DropNewest),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown backpressure policy {}, expected: {:?}",
+                    value,
+                    [BACKPRESSURE_BLOCK, BACKPRESSURE_DROP_OLDEST, BACKPRESSURE_DROP_NEWEST]
+                ),
+            )),
+        }
+    }
+}
+
+struct FrameQueue {
+    frames: Mutex<VecDeque<CommonFrame>>,
+    capacity: usize,
+    policy: BackpressurePolicy,
+
+    senders: AtomicUsize,
+    closed: AtomicBool,
+    dropped: AtomicU64,
+
+    frame_available: Notify,
+    space_available: Notify,
+}
+
+pub struct FrameSender {
+    queue: Arc<FrameQueue>,
+}
+
+pub struct FrameReceiver {
+    queue: Arc<FrameQueue>,
+}
+
+pub fn frame_channel(capacity: usize, policy: BackpressurePolicy) -> (FrameSender, FrameReceiver) {
+    let queue = Arc::new(FrameQueue {
+        frames: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity: capacity.max(1),
+        policy,
+        senders: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+        dropped: AtomicU64::new(0),
+        frame_available: Notify::new(),
+        space_available: Notify::new(),
+    });
+
+    (
+        FrameSender {
+            queue: queue.clone(),
+        },
+        FrameReceiver { queue },
+    )
+}
+
+impl FrameSender {
+    // NOTE: returns true if a frame had to be dropped to honor the backpressure policy
+    pub async fn send(&self, frame: CommonFrame) -> Result<bool, io::Error> {
+        let mut frame = Some(frame);
+
+        loop {
+            // NOTE: registered before checking closed and capacity, the receiver's notify_waiters() on drop stores no permit
+            let space_available = self.queue.space_available.notified();
+            tokio::pin!(space_available);
+            space_available.as_mut().enable();
+
+            if self.queue.closed.load(Ordering::Acquire) {
+                return Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "Frame receiver has been closed",
+                ));
+            }
+
+            {
+                let mut frames = self.queue.frames.lock().unwrap();
+                if frames.len() < self.queue.capacity {
+                    frames.push_back(frame.take().unwrap());
+                    self.queue.frame_available.notify_one();
+                    return Ok(false);
+                }
+
+                match self.queue.policy {
+                    BackpressurePolicy::Block => {}
+                    BackpressurePolicy::DropOldest => {
+                        frames.pop_front();
+                        frames.push_back(frame.take().unwrap());
+                        self.queue.dropped.fetch_add(1, Ordering::Relaxed);
+                        self.queue.frame_available.notify_one();
+                        return Ok(true);
+                    }
+                    BackpressurePolicy::DropNewest => {
+                        self.queue.dropped.fetch_add(1, Ordering::Relaxed);
+                        return Ok(true);
+                    }
+                }
+            }
+
+            space_available.await;
+        }
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Clone for FrameSender {
+    fn clone(&self) -> Self {
+        self.queue.senders.fetch_add(1, Ordering::AcqRel);
+        FrameSender {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl Drop for FrameSender {
+    fn drop(&mut self) {
+        if self.queue.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.queue.closed.store(true, Ordering::Release);
+            self.queue.frame_available.notify_one();
+        }
+    }
+}
+
+impl FrameReceiver {
+    pub fn is_empty(&self) -> bool {
+        self.queue.frames.lock().unwrap().is_empty()
+    }
+
+    pub async fn recv(&mut self) -> Option<CommonFrame> {
+        loop {
+            {
+                let mut frames = self.queue.frames.lock().unwrap();
+                if let Some(frame) = frames.pop_front() {
+                    self.queue.space_available.notify_one();
+                    return Some(frame);
+                }
+            }
+
+            if self.queue.closed.load(Ordering::Acquire) {
+                return None;
+            }
+
+            self.queue.frame_available.notified().await;
+        }
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        self.queue.closed.store(true, Ordering::Release);
+        self.queue.space_available.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+    use tokio::time::timeout;
+
+    use super::*;
+
+    fn test_frame() -> CommonFrame {
+        serde_json::from_value(json!({
+            "timestamp": "2023-07-01T00:00:00.000Z",
+            "freq": 8.977,
+            "signal": -20.0,
+            "err": false,
+            "paths": [],
+            "app": { "name": "test", "version": "0.0.0" },
+            "src": { "type": "Aircraft" },
+            "indexed": { "timestamp": "2023-07-01T00:00:00.000Z" },
+            "metadata": {},
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn blocked_sender_fails_once_receiver_is_dropped() {
+        let (tx, rx) = frame_channel(1, BackpressurePolicy::Block);
+        assert_eq!(tx.send(test_frame()).await.unwrap(), false);
+
+        let blocked = tokio::spawn(async move { tx.send(test_frame()).await });
+        tokio::task::yield_now().await;
+        drop(rx);
+
+        let result = timeout(Duration::from_secs(5), blocked)
+            .await
+            .expect("sender is still blocked after the receiver was dropped")
+            .unwrap();
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[tokio::test]
+    async fn blocked_sender_resumes_once_frame_is_received() {
+        let (tx, mut rx) = frame_channel(1, BackpressurePolicy::Block);
+        tx.send(test_frame()).await.unwrap();
+
+        let blocked = tokio::spawn(async move { tx.send(test_frame()).await });
+        tokio::task::yield_now().await;
+        assert!(rx.recv().await.is_some());
+
+        let result = timeout(Duration::from_secs(5), blocked).await.unwrap().unwrap();
+        assert_eq!(result.unwrap(), false);
+        assert!(rx.recv().await.is_some());
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn drop_newest_discards_when_full() {
+        let (tx, mut rx) = frame_channel(1, BackpressurePolicy::DropNewest);
+        assert_eq!(tx.send(test_frame()).await.unwrap(), false);
+        assert_eq!(tx.send(test_frame()).await.unwrap(), true);
+        assert_eq!(tx.dropped(), 1);
+
+        drop(tx);
+        assert!(rx.recv().await.is_some());
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::PathBuf;
+
+use jsonschema::JSONSchema;
+use serde_json::Value;
+use tokio::io;
+
+const MAX_REPORTED_SCHEMA_ERRORS: usize = 3;
+
+// NOTE: compiled once at startup, then shared by every ingest client
+pub struct FrameSchema {
+    path: PathBuf,
+    schema: JSONSchema,
+}
+
+impl FrameSchema {
+    pub fn load(path: &PathBuf) -> Result<FrameSchema, io::Error> {
+        let contents = fs::read_to_string(path)?;
+        let raw_schema: Value = match serde_json::from_str(contents.as_str()) {
+            Ok(x) => x,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Frame schema {} is not valid JSON: {}", path.to_string_lossy(), e.to_string()),
+                ))
+            }
+        };
+
+        let schema = match JSONSchema::compile(&raw_schema) {
+            Ok(x) => x,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Frame schema {} is not a valid JSON Schema: {}", path.to_string_lossy(), e.to_string()),
+                ))
+            }
+        };
+
+        Ok(FrameSchema {
+            path: path.clone(),
+            schema,
+        })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    // NOTE: the message is validated as sent by the feeder, so constraints can cover fields CommonFrame does not know about
+    pub fn validate(&self, msg: &str) -> Result<(), String> {
+        let instance: Value = match serde_json::from_str(msg) {
+            Ok(x) => x,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        if let Err(errors) = self.schema.validate(&instance) {
+            let errors: Vec<String> = errors
+                .map(|e| format!("{} at \"{}\"", e, e.instance_path))
+                .collect();
+            let mut message = errors
+                .iter()
+                .take(MAX_REPORTED_SCHEMA_ERRORS)
+                .cloned()
+                .collect::<Vec<String>>()
+                .join("; ");
+            if errors.len() > MAX_REPORTED_SCHEMA_ERRORS {
+                message.push_str(format!(" (and {} more)", errors.len() - MAX_REPORTED_SCHEMA_ERRORS).as_str());
+            }
+            return Err(message);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use reqwest::Client;
+
+pub const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 30;
+
+pub const REQUEST_ID_HEADER: &'static str = "X-Request-Id";
+
+pub fn default_user_agent() -> String {
+    format!("xng/{}", env!("CARGO_PKG_VERSION"))
+}
+
+pub fn create_http_client(timeout: Duration, user_agent: &str) -> reqwest::Result<Client> {
+    Client::builder()
+        .connect_timeout(timeout)
+        .timeout(timeout)
+        .user_agent(user_agent)
+        .build()
+}
+
+// NOTE: a random 128-bit hex ID, so a single request can be found in the remote server's logs
+pub fn generate_request_id() -> String {
+    hex::encode(rand::random::<[u8; 16]>())
+}
@@ -0,0 +1,53 @@
+use log::*;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::KafkaError;
+use rdkafka::producer::{BaseRecord, DeliveryResult, ProducerContext, ThreadedProducer};
+use rdkafka::ClientContext;
+use tokio::io;
+
+use super::frame::CommonFrame;
+
+pub struct KafkaLoggingContext;
+
+impl ClientContext for KafkaLoggingContext {}
+
+impl ProducerContext for KafkaLoggingContext {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _: Self::DeliveryOpaque) {
+        if let Err((e, _)) = result {
+            warn!("Failed to deliver frame to Kafka: {}", e.to_string());
+        }
+    }
+}
+
+pub type KafkaProducer = ThreadedProducer<KafkaLoggingContext>;
+
+pub fn create_kafka_producer(brokers: &String) -> Result<KafkaProducer, KafkaError> {
+    ClientConfig::new()
+        .set("bootstrap.servers", brokers.as_str())
+        .create_with_context(KafkaLoggingContext)
+}
+
+pub fn produce_frame(
+    producer: &KafkaProducer,
+    topic: &String,
+    frame: &CommonFrame,
+) -> Result<(), io::Error> {
+    let payload = serde_json::to_string(frame)?;
+
+    // NOTE: keying by ICAO keeps a given aircraft's frames ordered on a single partition
+    let mut record = BaseRecord::<str, String>::to(topic.as_str()).payload(&payload);
+    if let Some(ref icao) = frame.src.icao {
+        record = record.key(icao.as_str());
+    }
+
+    if let Err((e, _)) = producer.send(record) {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Failed to enqueue frame for Kafka: {}", e.to_string()),
+        ));
+    }
+
+    Ok(())
+}
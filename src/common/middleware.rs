@@ -15,29 +15,40 @@ use crate::modules::settings::ModuleSettings;
 
 pub struct Authorized;
 
+// NOTE: server mode has no ModuleSettings, so its API token is registered as app data on its own
+pub struct ServerApiToken(pub Option<String>);
+
 impl FromRequest for Authorized {
     type Error = Error;
     type Future = Pin<Box<dyn Future<Output = Result<Authorized, Error>>>>;
 
     fn from_request(req: &HttpRequest, pl: &mut Payload) -> Self::Future {
-        // NOTE: if we can't find ModuleSettings, something is very, very wrong
         let module_config = req
             .app_data::<Data<RwLock<ModuleSettings>>>()
-            .unwrap()
-            .clone();
+            .cloned();
+        let server_api_token = req
+            .app_data::<Data<ServerApiToken>>()
+            .and_then(|x| x.0.clone());
         let method = req.method().clone();
         let headers = req.headers().clone();
 
         Box::pin(async move {
-            let module_config = module_config.read().await;
+            let api_token = match module_config {
+                Some(module_config) => {
+                    let module_config = module_config.read().await;
 
-            if (method == Method::POST || method == Method::PATCH)
-                && module_config.disable_api_control
-            {
-                return Err(ErrorExpectationFailed("API Control is disabled"));
-            }
+                    if (method == Method::POST || method == Method::PATCH)
+                        && module_config.disable_api_control
+                    {
+                        return Err(ErrorExpectationFailed("API Control is disabled"));
+                    }
+
+                    module_config.api_token.clone()
+                }
+                None => server_api_token,
+            };
 
-            let Some(ref api_token) = module_config.api_token else {
+            let Some(ref api_token) = api_token else {
                   return Ok(Authorized);
             };
             let Some(ref user_token) = headers.get(header::AUTHORIZATION) else {
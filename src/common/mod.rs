@@ -1,13 +1,24 @@
+pub mod acars_labels;
+pub mod acars_reassembly;
 pub mod arguments;
 pub mod batcher;
 pub mod es_utils;
 pub mod events;
 pub mod formats;
 pub mod frame;
+pub mod frame_queue;
+pub mod frame_schema;
+pub mod http;
+pub mod kafka_utils;
 pub mod middleware;
+pub mod print_sampler;
+pub mod swarm_auth;
+pub mod swarm_target;
+pub mod webhook;
 pub mod wkt;
 
 pub const AIRFRAMESIO_HOST: &'static str = "feed.acars.io";
 
 pub const AIRFRAMESIO_DUMPHFDL_TCP_PORT: u16 = 5556;
 pub const AIRFRAMESIO_DUMPVDL2_UDP_PORT: u16 = 5552;
+pub const AIRFRAMESIO_ACARSDEC_UDP_PORT: u16 = 5550;
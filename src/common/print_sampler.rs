@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+// NOTE: an every_nth of 0 or 1 keeps every frame and a max_per_sec of 0 disables the time based cap
+pub struct PrintSampler {
+    every_nth: u64,
+    max_per_sec: u64,
+
+    seen: u64,
+    window_start: Instant,
+    window_printed: u64,
+}
+
+impl PrintSampler {
+    pub fn new(every_nth: u64, max_per_sec: u64) -> PrintSampler {
+        PrintSampler {
+            every_nth: every_nth.max(1),
+            max_per_sec,
+            seen: 0,
+            window_start: Instant::now(),
+            window_printed: 0,
+        }
+    }
+
+    pub fn should_print(&mut self) -> bool {
+        self.seen += 1;
+        if (self.seen - 1) % self.every_nth != 0 {
+            return false;
+        }
+
+        if self.max_per_sec == 0 {
+            return true;
+        }
+
+        if self.window_start.elapsed() >= Duration::from_secs(1) {
+            self.window_start = Instant::now();
+            self.window_printed = 0;
+        }
+        if self.window_printed >= self.max_per_sec {
+            return false;
+        }
+        self.window_printed += 1;
+
+        true
+    }
+}
@@ -0,0 +1,45 @@
+use sha2::{Digest, Sha256};
+use tokio::io::{self, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+pub const SWARM_AUTH_PREFIX: &'static str = "XNG-AUTH";
+pub const SWARM_MSGPACK_LINE: &'static str = "XNG-MSGPACK";
+pub const DEFAULT_SWARM_AUTH_TIMEOUT_SECS: u64 = 10;
+
+// NOTE: sending a digest keeps the plain token out of captures and server logs, but the digest never changes,
+//       so anyone who captures it can replay it to authenticate just as well as with the token itself
+pub fn swarm_auth_line(token: &str) -> String {
+    format!(
+        "{} {}\n",
+        SWARM_AUTH_PREFIX,
+        hex::encode(Sha256::digest(token.as_bytes()))
+    )
+}
+
+pub fn verify_swarm_auth_line(line: &str, token: &str) -> bool {
+    let Some(digest) = line.trim().strip_prefix(SWARM_AUTH_PREFIX) else {
+        return false;
+    };
+    let digest = digest.trim();
+    let expected = hex::encode(Sha256::digest(token.as_bytes()));
+
+    digest.len() == expected.len()
+        && digest
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+// NOTE: the MessagePack line switches the rest of the connection to length-prefixed MessagePack messages
+pub async fn connect_swarm(target: &str, token: Option<&String>, msgpack: bool) -> Result<TcpStream, io::Error> {
+    let mut stream = TcpStream::connect(target).await?;
+    if let Some(token) = token {
+        stream.write_all(swarm_auth_line(token).as_bytes()).await?;
+    }
+    if msgpack {
+        stream.write_all(format!("{}\n", SWARM_MSGPACK_LINE).as_bytes()).await?;
+    }
+
+    Ok(stream)
+}
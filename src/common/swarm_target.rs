@@ -0,0 +1,218 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::*;
+use reqwest::Url;
+use tokio::io::{self, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{self, error::TrySendError, Sender};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+
+use super::swarm_auth::connect_swarm;
+
+const DEFAULT_SWARM_CONNECT_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_SWARM_WRITE_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_SWARM_RETRY_MAX_SECS: u64 = 60;
+
+pub const DEFAULT_SWARM_QUEUE_SIZE: usize = 1024;
+
+// NOTE: every target keeps its own connection and reconnect backoff
+pub struct SwarmTarget {
+    addr: String,
+    token: Option<String>,
+    msgpack: bool,
+    stream: Option<TcpStream>,
+
+    retry_wait_secs: u64,
+    next_retry: Instant,
+}
+
+impl SwarmTarget {
+    pub fn new(url: &Url, token: Option<&String>, msgpack: bool) -> SwarmTarget {
+        SwarmTarget {
+            addr: format!(
+                "{}:{}",
+                url.host_str().unwrap_or("0.0.0.0"),
+                url.port().unwrap_or(0)
+            ),
+            token: token.cloned(),
+            msgpack,
+            stream: None,
+            retry_wait_secs: 1,
+            next_retry: Instant::now(),
+        }
+    }
+
+    pub fn addr(&self) -> &str {
+        self.addr.as_str()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    pub fn retry_wait_secs(&self) -> u64 {
+        self.retry_wait_secs
+    }
+
+    pub async fn connect(&mut self) -> Result<(), io::Error> {
+        let result = match time::timeout(
+            Duration::from_secs(DEFAULT_SWARM_CONNECT_TIMEOUT_SECS),
+            connect_swarm(self.addr.as_str(), self.token.as_ref(), self.msgpack),
+        )
+        .await
+        {
+            Ok(x) => x,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("Timed out connecting after {} seconds", DEFAULT_SWARM_CONNECT_TIMEOUT_SECS),
+            )),
+        };
+
+        match result {
+            Ok(stream) => {
+                self.stream = Some(stream);
+                self.retry_wait_secs = 1;
+                Ok(())
+            }
+            Err(e) => {
+                self.next_retry = Instant::now() + Duration::from_secs(self.retry_wait_secs);
+                self.retry_wait_secs = (self.retry_wait_secs * 2).min(DEFAULT_SWARM_RETRY_MAX_SECS);
+                Err(e)
+            }
+        }
+    }
+
+    // NOTE: while disconnected, messages are dropped with NotConnected until the next reconnect attempt is due
+    pub async fn send(&mut self, message: &[u8]) -> Result<(), io::Error> {
+        if self.stream.is_none() {
+            if Instant::now() < self.next_retry {
+                return Err(io::Error::new(io::ErrorKind::NotConnected, "Waiting to reconnect"));
+            }
+
+            match self.connect().await {
+                Ok(_) => info!("Reconnected to swarm target {}", self.addr),
+                Err(e) => {
+                    warn!("Failed to reconnect to swarm target {}: {}", self.addr, e.to_string());
+                    return Err(io::Error::new(io::ErrorKind::NotConnected, e.to_string()));
+                }
+            }
+        }
+
+        let Some(ref mut stream) = self.stream else {
+            return Err(io::Error::new(io::ErrorKind::NotConnected, "Not connected"));
+        };
+        let result = match time::timeout(
+            Duration::from_secs(DEFAULT_SWARM_WRITE_TIMEOUT_SECS),
+            stream.write_all(message),
+        )
+        .await
+        {
+            Ok(x) => x,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("Timed out writing after {} seconds", DEFAULT_SWARM_WRITE_TIMEOUT_SECS),
+            )),
+        };
+
+        if result.is_err() {
+            self.stream = None;
+        }
+        result
+    }
+
+    pub async fn shutdown(&mut self) {
+        if let Some(ref mut stream) = self.stream {
+            if let Err(e) = stream.shutdown().await {
+                warn!("Failed to shutdown connection to swarm target {}: {}", self.addr, e.to_string());
+            }
+        }
+        self.stream = None;
+    }
+}
+
+// NOTE: each target is written to from its own task, the processor only enqueues so a dead or slow aggregator
+//       fills (and then drops from) its own queue without holding up frames to the others
+pub struct SwarmSink {
+    addr: String,
+    tx: Sender<Arc<Vec<u8>>>,
+    dropped: Arc<AtomicU64>,
+    connected: Arc<AtomicBool>,
+    task: JoinHandle<()>,
+}
+
+impl SwarmSink {
+    pub fn new(mut target: SwarmTarget, queue_size: usize) -> SwarmSink {
+        let (tx, mut rx) = mpsc::channel::<Arc<Vec<u8>>>(queue_size.max(1));
+        let addr = target.addr().to_string();
+        let connected = Arc::new(AtomicBool::new(target.is_connected()));
+
+        let task_connected = connected.clone();
+        let task = tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                if let Err(e) = target.send(&message).await {
+                    if e.kind() != io::ErrorKind::NotConnected {
+                        warn!("Failed to send to Swarm target {}: {}", target.addr(), e.to_string());
+                    }
+                }
+                task_connected.store(target.is_connected(), Ordering::Relaxed);
+            }
+
+            target.shutdown().await;
+            task_connected.store(false, Ordering::Relaxed);
+            debug!("Swarm target {} queue closed, sender task exiting", target.addr());
+        });
+
+        SwarmSink {
+            addr,
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+            connected,
+            task,
+        }
+    }
+
+    pub fn addr(&self) -> &str {
+        self.addr.as_str()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    // NOTE: returns true if the message was dropped because the target's queue is full
+    pub fn send(&self, message: Arc<Vec<u8>>) -> Result<bool, io::Error> {
+        match self.tx.try_send(message) {
+            Ok(_) => Ok(false),
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                Ok(true)
+            }
+            Err(TrySendError::Closed(_)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                format!("Swarm target {} sender task is no longer running", self.addr),
+            )),
+        }
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    // NOTE: queued messages get one write timeout to drain, a stalled target is abandoned rather than holding up exit
+    pub async fn close(self) {
+        drop(self.tx);
+
+        let mut task = self.task;
+        match time::timeout(Duration::from_secs(DEFAULT_SWARM_WRITE_TIMEOUT_SECS), &mut task).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("Swarm target {} sender task did not exit cleanly: {}", self.addr, e.to_string()),
+            Err(_) => {
+                warn!("Swarm target {} did not drain its queue in time, dropping remaining messages", self.addr);
+                task.abort();
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::*;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Client, Url};
+use serde::Serialize;
+use tokio::io;
+use tokio::sync::mpsc::{self, error::TrySendError, Sender};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+pub const DEFAULT_WEBHOOK_QUEUE_SIZE: usize = 1024;
+pub const DEFAULT_WEBHOOK_BATCH_SIZE: usize = 1;
+
+const DEFAULT_WEBHOOK_MAX_RETRIES: u32 = 3;
+const DEFAULT_WEBHOOK_RETRY_BASE_SECS: u64 = 1;
+
+pub fn parse_webhook_headers(raw_headers: &Vec<String>) -> Result<HeaderMap, io::Error> {
+    let mut headers = HeaderMap::new();
+
+    for raw_header in raw_headers.iter() {
+        let Some((name, value)) = raw_header.split_once(':') else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Webhook header is not in the format of \"Name: value\": {}", raw_header),
+            ));
+        };
+
+        let name = match HeaderName::from_bytes(name.trim().as_bytes()) {
+            Ok(x) => x,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid webhook header name {}: {}", name.trim(), e.to_string()),
+                ))
+            }
+        };
+        let value = match HeaderValue::from_str(value.trim()) {
+            Ok(x) => x,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid webhook header value for {}: {}", name, e.to_string()),
+                ))
+            }
+        };
+        headers.append(name, value);
+    }
+
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    Ok(headers)
+}
+
+// NOTE: frames are serialized up front so a slow webhook never holds on to the processor's frames
+pub struct WebhookSink {
+    tx: Sender<String>,
+    dropped: Arc<AtomicU64>,
+    task: JoinHandle<()>,
+}
+
+impl WebhookSink {
+    pub fn new(
+        client: Client,
+        url: Url,
+        headers: HeaderMap,
+        batch_size: usize,
+        queue_size: usize,
+    ) -> WebhookSink {
+        let (tx, mut rx) = mpsc::channel::<String>(queue_size.max(1));
+        let batch_size = batch_size.max(1);
+
+        let task = tokio::spawn(async move {
+            while let Some(payload) = rx.recv().await {
+                let mut batch = vec![payload];
+                while batch.len() < batch_size {
+                    match rx.try_recv() {
+                        Ok(x) => batch.push(x),
+                        Err(_) => break,
+                    }
+                }
+
+                let body = if batch_size == 1 {
+                    batch.pop().unwrap_or_default()
+                } else {
+                    format!("[{}]", batch.join(","))
+                };
+
+                post_with_retries(&client, &url, &headers, body).await;
+            }
+
+            debug!("Webhook queue closed, sender task exiting");
+        });
+
+        WebhookSink {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+            task,
+        }
+    }
+
+    // NOTE: returns true if the payload was dropped because the webhook queue is full
+    pub fn send<T: Serialize>(&self, payload: &T) -> Result<bool, io::Error> {
+        let payload = serde_json::to_string(payload)?;
+
+        match self.tx.try_send(payload) {
+            Ok(_) => Ok(false),
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                Ok(true)
+            }
+            Err(TrySendError::Closed(_)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "Webhook sender task is no longer running",
+            )),
+        }
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub async fn close(self) {
+        drop(self.tx);
+        if let Err(e) = self.task.await {
+            warn!("Webhook sender task did not exit cleanly: {}", e.to_string());
+        }
+    }
+}
+
+async fn post_with_retries(client: &Client, url: &Url, headers: &HeaderMap, body: String) {
+    let mut attempt = 0;
+
+    loop {
+        let result = client
+            .post(url.clone())
+            .headers(headers.clone())
+            .body(body.clone())
+            .send()
+            .await;
+
+        let err = match result {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) if response.status().is_client_error() => {
+                warn!("Webhook {} rejected frame with status {}, not retrying", url, response.status());
+                return;
+            }
+            Ok(response) => format!("status {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= DEFAULT_WEBHOOK_MAX_RETRIES {
+            warn!("Giving up on webhook {} after {} attempts: {}", url, attempt + 1, err);
+            return;
+        }
+
+        let wait_secs = DEFAULT_WEBHOOK_RETRY_BASE_SECS << attempt;
+        debug!("Webhook {} failed ({}), retrying in {} seconds", url, err, wait_secs);
+        sleep(Duration::from_secs(wait_secs)).await;
+
+        attempt += 1;
+    }
+}
@@ -90,7 +90,7 @@ impl<'de> Deserialize<'de> for WKTPoint {
 
 struct WKTPolylineVisitor;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct WKTPolyline {
     pub points: Vec<(f64, f64, f64)>,
 }
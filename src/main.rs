@@ -1,7 +1,8 @@
-use clap::command;
+use clap::{arg, command};
+use common::arguments::parse_log_settings;
 use modules::elasticsearch;
 use std::process::exit;
-use tokio::runtime::Runtime;
+use tokio::runtime::Builder;
 
 mod common;
 mod modules;
@@ -16,6 +17,10 @@ fn main() {
             .propagate_version(true)
             .subcommand_required(true)
             .arg_required_else_help(true)
+            .arg(
+                arg!(--"worker-threads" <N> "Number of async runtime worker threads (default: one per CPU core)")
+                    .global(true),
+            )
             .subcommand(server::get_server_arguments()),
     )
         .subcommands([
@@ -24,7 +29,22 @@ fn main() {
         ]);
 
     let args = cmd.get_matches();
-    let rt = match Runtime::new() {
+
+    let mut rt_builder = Builder::new_multi_thread();
+    rt_builder.enable_all();
+    if let Some(raw_worker_threads) = args.get_one::<String>("worker-threads") {
+        match raw_worker_threads.trim().parse::<usize>() {
+            Ok(x) if x > 0 => {
+                rt_builder.worker_threads(x);
+            }
+            _ => {
+                eprintln!("Invalid --worker-threads, expected a positive number: {}", raw_worker_threads);
+                exit(exitcode::USAGE)
+            }
+        }
+    }
+
+    let rt = match rt_builder.build() {
         Ok(v) => v,
         Err(e) => {
             eprintln!("Failed to start tokio: {}", e.to_string());
@@ -36,21 +56,10 @@ fn main() {
     rt.block_on(async {
         match args.subcommand() {
             Some((subcmd, matches)) => {
-                let verbose_level = *matches.get_one::<u8>("verbose").unwrap_or(&0);
-                
-                stderrlog::new()
-                    .module(module_path!())
-                    .quiet(matches.get_flag("quiet"))
-                    .verbosity((verbose_level as usize) + 1)
-                    .timestamp(
-                        if verbose_level > 1 { 
-                            stderrlog::Timestamp::Second 
-                        } else { 
-                            stderrlog::Timestamp::Off 
-                        }
-                    )
-                    .init()
-                    .unwrap();
+                if let Err(e) = utils::logging::init_logging(parse_log_settings(matches)) {
+                    eprintln!("Failed to initialize logging: {}", e.to_string());
+                    exit(exitcode::CANTCREAT)
+                }
 
                 match subcmd {
                     server::SERVER_COMMAND => server::start(matches).await,
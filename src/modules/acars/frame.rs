@@ -0,0 +1,112 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::common::frame;
+use crate::utils::normalize_tail;
+
+#[derive(Debug, Deserialize)]
+pub struct App {
+    pub name: String,
+    pub ver: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Frame {
+    pub timestamp: f64,
+
+    pub freq: f64,
+
+    #[serde(default)]
+    pub level: f64,
+
+    #[serde(default)]
+    pub error: u32,
+
+    pub mode: String,
+    pub label: String,
+    pub block_id: Option<String>,
+
+    // NOTE: acarsdec reports a NAK as false and an acknowledgement as the acknowledged block ID
+    pub ack: Option<Value>,
+
+    pub tail: Option<String>,
+    pub flight: Option<String>,
+    pub msgno: Option<String>,
+    pub text: Option<String>,
+
+    #[serde(default)]
+    pub end: bool,
+
+    pub sublabel: Option<String>,
+    pub mfi: Option<String>,
+
+    pub depa: Option<String>,
+    pub dsta: Option<String>,
+
+    pub icao: Option<u32>,
+
+    pub app: Option<App>,
+}
+
+impl Frame {
+    // NOTE: downlink block IDs are digits while uplink block IDs are letters
+    pub fn from_ground_station(&self) -> bool {
+        self.block_id
+            .as_ref()
+            .and_then(|x| x.chars().next())
+            .map_or(false, |x| x.is_ascii_alphabetic())
+    }
+
+    pub fn aircraft_entity(&self) -> frame::Entity {
+        frame::Entity {
+            kind: String::from("Aircraft"),
+            icao: self.icao.map(|x| format!("{:06X}", x)),
+            gs: None,
+            id: None,
+            callsign: self.flight.as_ref().map(|x| x.trim().to_string()).filter(|x| !x.is_empty()),
+            tail: self.tail.as_ref().map(|x| normalize_tail(x)).filter(|x| !x.is_empty()),
+            coords: None,
+        }
+    }
+
+    // NOTE: VHF ACARS does not identify the ground station, so it is left anonymous unless one is configured
+    pub fn ground_station_entity(&self, configured: Option<&frame::Entity>) -> frame::Entity {
+        if let Some(station) = configured {
+            return station.clone();
+        }
+
+        frame::Entity {
+            kind: String::from("Ground station"),
+            icao: None,
+            gs: None,
+            id: None,
+            callsign: None,
+            tail: None,
+            coords: None,
+        }
+    }
+
+    pub fn to_acars(&self) -> frame::ACARS {
+        let msgno = self.msgno.clone().unwrap_or_default();
+
+        frame::ACARS {
+            mode: self.mode.clone(),
+            more: !self.end,
+            label: self.label.clone(),
+            ack: match self.ack {
+                Some(Value::String(ref x)) => Some(x.clone()),
+                Some(Value::Bool(false)) => Some(String::from("!")),
+                _ => None,
+            },
+            blk_id: self.block_id.clone(),
+            msg_num: msgno.get(0..3).map(|x| x.to_string()),
+            msg_num_seq: msgno.get(3..4).map(|x| x.to_string()),
+            tail: self.tail.clone(),
+            flight: self.flight.clone(),
+            sublabel: self.sublabel.clone(),
+            mfi: self.mfi.clone(),
+            cfi: None,
+            text: self.text.clone(),
+        }
+    }
+}
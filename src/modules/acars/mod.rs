@@ -0,0 +1,290 @@
+use chrono::SecondsFormat;
+use log::*;
+use tokio::io::BufReader;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use actix_web::web::Data;
+use async_trait::async_trait;
+use clap::{arg, Arg, ArgAction, ArgMatches, Command};
+use serde_json::json;
+use tokio::sync::RwLock;
+use tokio::{io, process};
+
+use self::frame::Frame;
+use self::session::AcarsdecSession;
+
+use super::session::{DryRunSession, EndSessionReason};
+use super::settings::ModuleSettings;
+use super::XngModule;
+use crate::common::arguments::parse_bin_path;
+use crate::common::{AIRFRAMESIO_ACARSDEC_UDP_PORT, AIRFRAMESIO_HOST};
+use crate::common::frame as cff;
+use crate::modules::ground_station_db::{upsert_ground_station, GroundStationDB};
+use crate::modules::PROP_LISTENING_BAND;
+use crate::server::db::StateDB;
+use crate::utils::version::check_decoder_version;
+use crate::utils::timestamp::unix_time_to_utc_datetime;
+
+mod frame;
+mod module;
+mod session;
+
+const ACARS_COMMAND: &'static str = "acars";
+
+const DEFAULT_BIN_PATH: &'static str = "/usr/local/bin/acarsdec";
+const MIN_DECODER_VERSION: &'static str = "3.7.0";
+const DEFAULT_SESSION_TIMEOUT_SECS: u64 = 900;
+const DEFAULT_ACARS_FREQ: u64 = 131550;
+
+const ACARS_MIN_KHZ: u64 = 118000;
+const ACARS_MAX_KHZ: u64 = 137000;
+
+#[derive(Default)]
+pub struct AcarsModule {
+    name: &'static str,
+    settings: Option<Data<RwLock<ModuleSettings>>>,
+
+    bin: PathBuf,
+    decoder_version: Option<String>,
+    args: Vec<String>,
+
+    feed_airframes: bool,
+    feed_airframes_manual: bool,
+    dry_run: bool,
+
+    bands: Vec<u64>,
+    ground_station: Option<cff::Entity>,
+}
+
+#[async_trait]
+impl XngModule for AcarsModule {
+    fn id(&self) -> &'static str {
+        self.name
+    }
+
+    fn default_session_timeout_secs(&self) -> u64 {
+        DEFAULT_SESSION_TIMEOUT_SECS
+    }
+
+    fn get_arguments(&self) -> Command {
+        Command::new(ACARS_COMMAND)
+            .about("Listen to VHF ACARS messages using acarsdec")
+            .args(&[
+                arg!(--bin <FILE> "Path to acarsdec binary"),
+                Arg::new("bands").long("bands").value_delimiter(',').help("VHF ACARS frequencies in kHz to listen to (default: 131550)"),
+                arg!(--"ground-stations" <FILE> "Path to Ground Stations CSV file from Airframes data repository, used to look up --ground-station"),
+                arg!(--"ground-station" <ADDR> "Hexadecimal address of the ground station (from --ground-stations) these frequencies are heard from, since VHF ACARS does not identify it"),
+            ])
+            .arg(Arg::new("acars-args").action(ArgAction::Append))
+    }
+
+    async fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error> {
+        self.feed_airframes_manual = args.get_flag("feed-airframes-manual");
+        self.feed_airframes = args.get_flag("feed-airframes") || self.feed_airframes_manual;
+        self.dry_run = args.get_flag("dry-run");
+
+        let bin_path = parse_bin_path(args, DEFAULT_BIN_PATH);
+        if !bin_path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Provided acarsdec path is not a file: {}",
+                    bin_path.to_string_lossy()
+                ),
+            ));
+        }
+        self.bin = bin_path;
+        self.decoder_version = check_decoder_version(
+            &self.bin,
+            MIN_DECODER_VERSION,
+            args.get_flag("require-min-decoder-version"),
+        )
+        .await?;
+
+        let Some(acars_args) = args.get_many("acars-args") else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required acarsdec positional arguments"));
+        };
+        self.args = acars_args
+            .clone()
+            .map(|x: &String| x.to_string())
+            .collect::<Vec<String>>();
+
+        if self.feed_airframes && !self.args.iter().any(|x| x == "-i") {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required -i <station id> argument when feed airframes.io option is enabled"));
+        }
+
+        match (args.get_one::<String>("ground-stations"), args.get_one::<String>("ground-station")) {
+            (Some(path), Some(addr)) => {
+                let stations = GroundStationDB::from_csv(path)?;
+                self.ground_station = Some(Self::resolve_ground_station(&stations, addr)?);
+            }
+            (None, Some(_)) => return Err(io::Error::new(io::ErrorKind::InvalidInput, "--ground-station requires --ground-stations")),
+            (Some(_), None) => warn!("--ground-stations is set without --ground-station, frames will carry an anonymous ground station"),
+            (None, None) => {}
+        }
+
+        if self.feed_airframes_manual && !self.args.iter().any(|x| x == "-j") {
+            warn!("--feed-airframes-manual is set but no -j was passed to the decoder, frames will not reach airframes.io");
+        }
+
+        let bands: Vec<u64> = match args.get_many::<String>("bands") {
+            Some(bands) => {
+                let mut freqs: Vec<u64> = bands
+                    .map(|x| x.as_str().parse::<u64>().unwrap_or(0))
+                    .collect();
+                freqs.sort_unstable();
+                freqs.dedup();
+                freqs
+            }
+            None => vec![DEFAULT_ACARS_FREQ],
+        };
+        if bands.iter().any(|&x| x < ACARS_MIN_KHZ || x > ACARS_MAX_KHZ) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Bands contains invalid VHF ACARS kHz frequency: {:?}",
+                    bands
+                ),
+            ));
+        }
+        self.bands = bands;
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        settings: Data<RwLock<ModuleSettings>>,
+        state_db: Data<RwLock<StateDB>>,
+    ) {
+        self.settings = Some(settings.clone());
+
+        {
+            let mut settings = settings.write().await;
+            settings.status.decoder_version = self.decoder_version.clone();
+        }
+
+        if let Some(ref station) = self.ground_station {
+            if let Err(e) = upsert_ground_station(&state_db, station).await {
+                warn!("Failed to populate configured ground station: {}", e.to_string());
+            }
+        }
+    }
+
+    async fn start_session(
+        &mut self,
+        _last_end_reason: EndSessionReason,
+    ) -> Result<Box<dyn super::session::Session>, io::Error> {
+        let settings = self.get_settings()?;
+
+        let mut feed_args: Vec<String> = Vec::new();
+        let feed_addr = format!("{}:{}", AIRFRAMESIO_HOST, AIRFRAMESIO_ACARSDEC_UDP_PORT);
+
+        // NOTE: with --feed-airframes-manual the user's own -j arguments are trusted as is
+        if self.feed_airframes && !self.feed_airframes_manual && !self.args.iter().any(|x| x.eq_ignore_ascii_case(&feed_addr)) {
+            feed_args.extend_from_slice(&[String::from("-j"), feed_addr]);
+        }
+
+        // NOTE: -o 4 prints one JSON object per message to STDOUT. Options go ahead of the user's arguments,
+        //       which may end with device options that acarsdec expects right before the frequencies
+        let mut cmd = process::Command::new(self.bin.clone());
+        cmd.stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .arg("-o")
+            .arg("4")
+            .args(feed_args)
+            .args(self.args.clone())
+            .args(self.bands.iter().map(|x| format!("{:.3}", *x as f64 / 1000.0)).collect::<Vec<String>>());
+
+        if self.dry_run {
+            return Ok(Box::new(DryRunSession::from_command(&cmd, self.bands.clone())));
+        }
+
+        let mut proc = match cmd.spawn() {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to spawn process: {}", e.to_string()),
+                ))
+            }
+        };
+
+        settings.write().await.set(PROP_LISTENING_BAND, json!(self.bands))?;
+        debug!("New ACARS session started, listening: {:?}", self.bands);
+
+        let Some(stdout) = proc.stdout.take() else {
+            return Err(io::Error::new(io::ErrorKind::Other, "Unable to take stdout from child process"));
+        };
+        let Some(stderr) = proc.stderr.take() else {
+            return Err(io::Error::new(io::ErrorKind::Other, "Unable to take stderr from child process"));
+        };
+
+        Ok(Box::new(AcarsdecSession::new(proc, BufReader::new(stdout), stderr, self.bands.clone())))
+    }
+
+    async fn process_message(
+        &mut self,
+        _current_band: &Vec<u64>,
+        msg: &str,
+    ) -> Result<crate::common::frame::CommonFrame, io::Error> {
+        let raw_frame = serde_json::from_str::<Frame>(msg)?;
+
+        let Some(arrival_time) = unix_time_to_utc_datetime(raw_frame.timestamp) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid arrival time",
+            ));
+        };
+
+        let (frame_src, frame_dst) = if raw_frame.from_ground_station() {
+            (raw_frame.ground_station_entity(self.ground_station.as_ref()), raw_frame.aircraft_entity())
+        } else {
+            (raw_frame.aircraft_entity(), raw_frame.ground_station_entity(self.ground_station.as_ref()))
+        };
+
+        let indexed = cff::Indexed {
+            timestamp: arrival_time.to_rfc3339_opts(SecondsFormat::Micros, true),
+            src_airport: raw_frame.depa.clone(),
+            dst_airport: raw_frame.dsta.clone(),
+
+            ..Default::default()
+        };
+
+        let (app_name, app_version) = match raw_frame.app {
+            Some(ref app) => (app.name.clone(), app.ver.clone()),
+            None => (String::from("acarsdec"), self.decoder_version.clone().unwrap_or_default()),
+        };
+
+        Ok(cff::CommonFrame {
+            schema_version: cff::CFF_SCHEMA_VERSION,
+
+            timestamp: arrival_time.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            freq: raw_frame.freq,
+            signal: raw_frame.level as f32,
+
+            err: raw_frame.error > 0,
+
+            paths: Vec::new(),
+
+            app: cff::AppInfo {
+                name: app_name,
+                version: app_version,
+            },
+
+            indexed,
+            metadata: cff::Metadata { hfdl: None, vdl2: None },
+
+            src: frame_src,
+            dst: Some(frame_dst),
+            acars: Some(raw_frame.to_acars()),
+            from_ground_station: None,
+            raw: None,
+        })
+    }
+
+    async fn reload(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use actix_web::web::Data;
+use tokio::io;
+use tokio::sync::RwLock;
+
+use crate::common::frame as cff;
+use crate::modules::ground_station_db::GroundStationDB;
+use crate::modules::{settings::ModuleSettings, XngModule};
+
+use super::{AcarsModule, ACARS_COMMAND};
+
+impl AcarsModule {
+    pub fn new() -> Box<dyn XngModule> {
+        Box::new(AcarsModule {
+            name: ACARS_COMMAND,
+
+            ..Default::default()
+        })
+    }
+
+    pub fn get_settings(&self) -> Result<Data<RwLock<ModuleSettings>>, io::Error> {
+        let Some(ref settings) = self.settings else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "ModuleSettings is None"));
+        };
+        Ok(settings.clone())
+    }
+
+    pub fn resolve_ground_station(stations: &GroundStationDB, addr: &String) -> Result<cff::Entity, io::Error> {
+        let norm_addr = addr.trim().to_uppercase();
+        let Ok(gs_id) = u32::from_str_radix(&norm_addr, 16) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a valid hexadecimal ground station address", addr)));
+        };
+        let Some(station) = stations.get(&norm_addr) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("Ground station {} not found in --ground-stations", norm_addr)));
+        };
+
+        Ok(cff::Entity {
+            kind: String::from("Ground station"),
+            icao: Some(norm_addr),
+            gs: Some(station.display_name()),
+            id: Some(gs_id),
+            callsign: None,
+            tail: None,
+            coords: Some(station.coords.clone()),
+        })
+    }
+}
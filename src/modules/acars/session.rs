@@ -0,0 +1,85 @@
+use log::*;
+
+use async_trait::async_trait;
+use std::process::ExitStatus;
+use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdout};
+use tokio::time::{timeout, Duration};
+
+use crate::modules::session::{EndSessionReason, Session, DEFAULT_EXIT_STATUS_WAIT_SECS};
+
+pub struct AcarsdecSession {
+    process: Child,
+
+    reader: BufReader<ChildStdout>,
+    stderr: ChildStderr,
+
+    bands: Vec<u64>,
+}
+
+#[async_trait]
+impl Session for AcarsdecSession {
+    async fn read_message(&mut self, msg: &mut String) -> Result<usize, io::Error> {
+        self.reader.read_line(msg).await
+    }
+
+    async fn on_timeout(&mut self) -> bool {
+        false
+    }
+
+    async fn get_errors(&mut self) -> String {
+        let mut errors = String::new();
+        if let Err(e) = self.stderr.read_to_string(&mut errors).await {
+            return format!("Failed to read STDERR: {}", e.to_string());
+        }
+
+        errors
+    }
+
+    fn get_listening_band(&self) -> &Vec<u64> {
+        &self.bands
+    }
+
+    async fn exit_status(&mut self) -> Option<ExitStatus> {
+        match timeout(
+            Duration::from_secs(DEFAULT_EXIT_STATUS_WAIT_SECS),
+            self.process.wait(),
+        )
+        .await
+        {
+            Ok(Ok(status)) => Some(status),
+            Ok(Err(e)) => {
+                warn!("Failed to get acarsdec exit status: {}", e.to_string());
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    async fn end(&mut self, reason: EndSessionReason) {
+        debug!("Terminating launched acarsdec process...");
+
+        #[allow(unused_must_use)]
+        {
+            self.process.kill().await;
+        }
+
+        debug!("ACARS session terminated: reason={:?}", reason);
+    }
+}
+
+impl AcarsdecSession {
+    pub fn new(
+        process: Child,
+        reader: BufReader<ChildStdout>,
+        stderr: ChildStderr,
+        bands: Vec<u64>,
+    ) -> AcarsdecSession {
+        AcarsdecSession {
+            process,
+            reader,
+            stderr,
+            bands,
+        }
+    }
+}
@@ -2,8 +2,8 @@ use serde::Deserialize;
 use serde_json::Value;
 use serde_valid::Validate;
 
-use super::ground_station_db::GroundStationDB;
-use crate::common::formats::{validate_entity_type, Application, EntityType, Timestamp};
+use crate::modules::ground_station_db::GroundStationDB;
+use crate::common::formats::{validate_entity_type, Application, EntityType, Timestamp, RESERVED_ENTITY_KIND};
 use crate::common::frame;
 use crate::common::wkt::WKTPoint;
 
@@ -41,10 +41,7 @@ impl Entity {
             EntityType::GroundStation => {
                 if let Some(stations) = stations {
                     if let Some(station) = stations.get(&norm_addr) {
-                        gs = Some(format!(
-                            "{} ({}/{})",
-                            station.airport_name, station.airport_iata, station.airport_icao
-                        ));
+                        gs = Some(station.display_name());
                         coords = Some(station.coords.clone());
                     }
                 }
@@ -53,7 +50,10 @@ impl Entity {
         }
 
         frame::Entity {
-            kind: self.entity_type.clone(),
+            kind: match self.kind() {
+                EntityType::Reserved => String::from(RESERVED_ENTITY_KIND),
+                _ => self.entity_type.clone(),
+            },
             icao: Some(norm_addr),
             gs,
             coords,
@@ -143,6 +143,21 @@ impl ParamACLocation {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ParamGSLocation {
+    loc: ParamACLocationCoord,
+}
+
+impl ParamGSLocation {
+    pub fn wkt(&self) -> WKTPoint {
+        WKTPoint {
+            x: self.loc.lon,
+            y: self.loc.lat,
+            z: 0.0,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct VDLParam {
     pub name: String,
@@ -12,33 +12,41 @@ use serde_json::json;
 use tokio::sync::RwLock;
 use tokio::{io, process};
 
-use self::frame::{Frame, ParamACLocation};
-use self::ground_station_db::GroundStationDB;
+use self::frame::{Frame, ParamACLocation, ParamGSLocation};
 use self::session::DumpVDL2Session;
-use self::validators::validate_next_session_band;
+use self::validators::{
+    is_vdl2_channel, nearest_vdl2_channel, validate_next_session_band, validate_off_raster_mode,
+    OFF_RASTER_REJECT, OFF_RASTER_SNAP, VDL2_MAX_KHZ, VDL2_MIN_KHZ,
+};
 
-use super::session::EndSessionReason;
+use super::session::{DryRunSession, EndSessionReason};
 use super::settings::ModuleSettings;
 use super::XngModule;
 use crate::common::arguments::{extract_soapysdr_driver, parse_bin_path};
 use crate::common::wkt::WKTPolyline;
 use crate::common::{AIRFRAMESIO_DUMPVDL2_UDP_PORT, AIRFRAMESIO_HOST};
 use crate::common::frame::{self as cff, Indexed};
+use crate::modules::ground_station_db::GroundStationDB;
 use crate::modules::PROP_LISTENING_BAND;
 use crate::server::db::StateDB;
 use crate::utils::normalize_tail;
+use crate::utils::version::check_decoder_version;
 use crate::utils::timestamp::{split_unix_time_to_utc_datetime, unix_time_to_utc_datetime};
 
 mod frame;
-mod ground_station_db;
 mod module;
 mod session;
 mod validators;
 
 const AOA_COMMAND: &'static str = "aoa";
-const BROADCAST_ADDR: &'static str = "FFFFFF";
+
+const VDL2_KIND_AVLC: &'static str = "AVLC";
+const VDL2_KIND_ACARS: &'static str = "ACARS";
+const VDL2_KIND_XID: &'static str = "XID";
+const VDL2_KIND_X25: &'static str = "X25";
 
 const DEFAULT_BIN_PATH: &'static str = "/usr/bin/dumpvdl2";
+const MIN_DECODER_VERSION: &'static str = "2.2.0";
 const DEFAULT_SESSION_TIMEOUT_SECS: u64 = 900;
 const DEFAULT_VDL2_FREQ: u64 = 136975;
 
@@ -51,11 +59,14 @@ pub struct AoaModule {
     state_db: Option<Data<RwLock<StateDB>>>,
     
     bin: PathBuf,
+    decoder_version: Option<String>,
     stations: Option<GroundStationDB>,
     args: Vec<String>,
     driver: String,
 
     feed_airframes: bool,
+    feed_airframes_manual: bool,
+    dry_run: bool,
 
     next_session_band: Vec<u64>,
 }
@@ -76,13 +87,16 @@ impl XngModule for AoaModule {
             .args(&[
                 arg!(--bin <FILE> "Path to dumpvdl2 binary"),
                 arg!(--"ground-stations" <FILE> "Path to VDL2 Ground Stations CSV file from Airframes data repository (geo-region specific)"),
-                Arg::new("start-bands").long("start-bands").value_delimiter(',').help("Starting VDL2 frequencies in kHz to listen to (default: 136975)")
+                Arg::new("start-bands").long("start-bands").value_delimiter(',').help("Starting VDL2 frequencies in kHz to listen to (default: 136975)"),
+                arg!(--"off-raster" <MODE> "How to handle start bands not on the 25/8.33 kHz VDL2 channel raster. Valid modes: snap, reject (default: reject)")
             ])
             .arg(Arg::new("aoa-args").action(ArgAction::Append))
     }
 
-    fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error> {
-        self.feed_airframes = args.get_flag("feed-airframes");
+    async fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error> {
+        self.feed_airframes_manual = args.get_flag("feed-airframes-manual");
+        self.feed_airframes = args.get_flag("feed-airframes") || self.feed_airframes_manual;
+        self.dry_run = args.get_flag("dry-run");
 
         let bin_path = parse_bin_path(args, DEFAULT_BIN_PATH);
         if !bin_path.is_file() {
@@ -95,6 +109,12 @@ impl XngModule for AoaModule {
             ));
         }
         self.bin = bin_path;
+        self.decoder_version = check_decoder_version(
+            &self.bin,
+            MIN_DECODER_VERSION,
+            args.get_flag("require-min-decoder-version"),
+        )
+        .await?;
 
         if let Some(ground_station_path) = args.get_one::<String>("ground-stations") {
             self.stations = Some(GroundStationDB::from_csv(ground_station_path)?);
@@ -122,6 +142,10 @@ impl XngModule for AoaModule {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required --station-id <name> argument when feed airframes.io option is enabled"));
         }
 
+        if self.feed_airframes_manual && !self.args.iter().any(|x| x.eq_ignore_ascii_case("--output")) {
+            warn!("--feed-airframes-manual is set but no --output was passed to the decoder, frames will not reach airframes.io");
+        }
+
         let start_bands: Vec<u64> = match args.get_many::<String>("start-bands") {
             Some(bands) => {
                 let mut freqs: Vec<u64> = bands
@@ -132,7 +156,7 @@ impl XngModule for AoaModule {
             }
             None => vec![DEFAULT_VDL2_FREQ],
         };
-        if start_bands.iter().any(|&x| x < 118000 || x > 137000) {
+        if start_bands.iter().any(|&x| x < VDL2_MIN_KHZ || x > VDL2_MAX_KHZ) {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 format!(
@@ -141,7 +165,35 @@ impl XngModule for AoaModule {
                 ),
             ));
         }
-        self.next_session_band = start_bands;
+
+        let off_raster = args.get_one::<String>("off-raster").unwrap_or(&String::from(OFF_RASTER_REJECT)).to_lowercase();
+        if let Err(e) = validate_off_raster_mode(&off_raster) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, e));
+        }
+
+        let mut snapped_bands: Vec<u64> = Vec::with_capacity(start_bands.len());
+        for band in start_bands.into_iter() {
+            if is_vdl2_channel(band) {
+                snapped_bands.push(band);
+                continue;
+            }
+
+            let nearest = nearest_vdl2_channel(band);
+            if off_raster == OFF_RASTER_SNAP {
+                warn!("Start band {} kHz is not on the VDL2 channel raster, snapping to {} kHz", band, nearest);
+                snapped_bands.push(nearest);
+            } else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Start band {} kHz is not on the VDL2 channel raster (nearest channel is {} kHz), use --off-raster {} to snap automatically",
+                        band, nearest, OFF_RASTER_SNAP
+                    ),
+                ));
+            }
+        }
+        snapped_bands.dedup();
+        self.next_session_band = snapped_bands;
 
         Ok(())
     }
@@ -155,6 +207,7 @@ impl XngModule for AoaModule {
         self.settings = Some(settings.clone());
 
         let mut settings = settings.write().await;
+        settings.status.decoder_version = self.decoder_version.clone();
 
         settings.add_prop_with_validator(
             PROP_NEXT_SESSION_BAND.to_string(),
@@ -175,7 +228,8 @@ impl XngModule for AoaModule {
             AIRFRAMESIO_HOST, AIRFRAMESIO_DUMPVDL2_UDP_PORT
         );
 
-        if self.feed_airframes {
+        // NOTE: with --feed-airframes-manual the user's own --output arguments are trusted as is
+        if self.feed_airframes && !self.feed_airframes_manual {
             if let Some(idx) = extra_args
                 .iter()
                 .position(|x| x.eq_ignore_ascii_case(&output_arg))
@@ -200,32 +254,22 @@ impl XngModule for AoaModule {
         {
             let mut settings = settings.write().await;
 
-            let next_session_band: Vec<u64>;
-            {
-                let Some(value) = settings.props.get(&PROP_NEXT_SESSION_BAND.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_NEXT_SESSION_BAND prop")
-                    );    
-                };
-
-                // NOTE: next_session_band's validator should prevent malformed entries from sneaking in
-                match value.as_array() {
-                    Some(x) => next_session_band = x.into_iter().map(|y| y.as_u64().unwrap_or(0)).collect(),
-                    None => {
-                        return Err(io::Error::new(io::ErrorKind::InvalidData, "PROP_NEXT_SESSION_BAND expects an array of numbers"))
-                    }
-                }
-            }
+            // NOTE: next_session_band's validator should prevent malformed entries from sneaking in
+            let next_session_band = settings.get_u64_array(PROP_NEXT_SESSION_BAND)?;
         
-            proc = match process::Command::new(self.bin.clone())
-                .stdout(Stdio::piped())
+            let mut cmd = process::Command::new(self.bin.clone());
+            cmd.stdout(Stdio::piped())
                 .stderr(Stdio::piped())
                 .arg("--output")
                 .arg("decoded:json:file:path=-")
                 .args(extra_args)
-                .args(next_session_band.iter().map(|x| (x * 1000).to_string()).collect::<Vec<String>>())
-                .spawn()
-            {
+                .args(next_session_band.iter().map(|x| (x * 1000).to_string()).collect::<Vec<String>>());
+
+            if self.dry_run {
+                return Ok(Box::new(DryRunSession::from_command(&cmd, next_session_band)));
+            }
+
+            proc = match cmd.spawn() {
                 Ok(v) => v,
                 Err(e) => {
                     return Err(io::Error::new(
@@ -235,25 +279,9 @@ impl XngModule for AoaModule {
                 }
             };
 
-            {
-                let Some(value) = settings.props.get_mut(&PROP_LISTENING_BAND.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_LISTENING_BAND prop")
-                    );    
-                };
-                *value = json!(next_session_band);
-            }
-
+            settings.set(PROP_LISTENING_BAND, json!(next_session_band))?;
             listening_bands = next_session_band.clone();
-            
-            {
-                let Some(value) = settings.props.get_mut(&PROP_NEXT_SESSION_BAND.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_NEXT_SESSION_BAND prop")
-                    );    
-                };
-                *value = json!([]);
-            }
+            settings.set(PROP_NEXT_SESSION_BAND, json!([]))?;
 
             debug!("New AoA session started, listening: {:?}", next_session_band);            
         }
@@ -268,6 +296,19 @@ impl XngModule for AoaModule {
         Ok(Box::new(DumpVDL2Session::new(proc, BufReader::new(stdout), stderr, listening_bands)))
     }
 
+    fn example_invocation(&self) -> Option<String> {
+        Some(
+            include_str!("example.txt")
+                .replace("{command}", AOA_COMMAND)
+                .replace("{bin}", DEFAULT_BIN_PATH)
+                .replace("{default_freq}", DEFAULT_VDL2_FREQ.to_string().as_str())
+                .replace(
+                    "{feed_output}",
+                    format!("decoded:json:udp:address={},port={}", AIRFRAMESIO_HOST, AIRFRAMESIO_DUMPVDL2_UDP_PORT).as_str(),
+                ),
+        )
+    }
+
     async fn process_message(
         &mut self,
         current_band: &Vec<u64>,
@@ -290,6 +331,10 @@ impl XngModule for AoaModule {
         };
 
         let mut paths: Vec<cff::PropagationPath> = Vec::new();
+        let mut metadata = cff::VDL2Metadata {
+            kind: String::from(VDL2_KIND_AVLC),
+            x25: None,
+        };
 
         let mut indexed = cff::Indexed {
             timestamp: arrival_time.to_rfc3339_opts(SecondsFormat::Micros, true),
@@ -302,31 +347,8 @@ impl XngModule for AoaModule {
             frame_src = avlc.src.to_common_frame_entity(self.stations.as_ref());
             frame_dst = Some(avlc.dst.to_common_frame_entity(self.stations.as_ref()));
 
-            if let Some(state_db) = self.state_db.clone() {
-                let db = state_db.write().await;
-                let station: &cff::Entity = if avlc.from_ground_station() {
-                    &frame_src
-                } else {
-                    frame_dst.as_ref().unwrap()
-                };
-
-                if station.gs.is_some() && station.coords.is_some() {
-                    let gs_name = station.gs.as_ref().unwrap();
-                    let coords = station.coords.as_ref().unwrap();
-                    let Some(ref addr) = station.icao else {
-                        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Ground station missing ICAO: {:?}", station)));    
-                    };
-                
-                    match u32::from_str_radix(addr.as_str(), 16) {
-                        Ok(x) => if let Err(e) = db.create_ground_station(x, gs_name, coords.y, coords.x).await {
-                            return Err(io::Error::new(io::ErrorKind::Other, format!("Failed to create ground station in state DB: {}", e.to_string())));
-                        },
-                        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a valid hexadecimal ICAO addr: {}", addr, e.to_string())))
-                    }
-                } 
-            }
-            
             if let Some(ref acars) = avlc.acars {
+                metadata.kind = String::from(VDL2_KIND_ACARS);
                 has_err = acars.err;
                 acars_content = Some(cff::ACARS {
                     mode: acars.mode.clone(),
@@ -356,6 +378,17 @@ impl XngModule for AoaModule {
                     frame_src.callsign = acars.flight.clone();
                 }
             } else if let Some(ref xid) = avlc.xid {
+                metadata.kind = String::from(VDL2_KIND_XID);
+                if avlc.from_ground_station() && frame_src.coords.is_none() {
+                    if let Some(param) = xid.vdl_params.iter().find(|x| x.name == "gs_location") {
+                        match serde_json::from_value::<ParamGSLocation>(param.value.clone()) {
+                            Ok(x) => frame_src.coords = Some(x.wkt()),
+                            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{:?} is not a valid gs_location VDL2 param: {}", param.value, e.to_string())))
+                        }
+                    }
+                }
+                
+
                 if let Some(param) = xid.vdl_params.iter().find(|x| x.name == "ac_location") {
                     match serde_json::from_value::<ParamACLocation>(param.value.clone()) {
                         Ok(x) => {
@@ -385,15 +418,30 @@ impl XngModule for AoaModule {
                     }
                 }
             } else if let Some(ref x25) = avlc.x25 {
+                metadata.kind = String::from(VDL2_KIND_X25);
+                metadata.x25 = Some(cff::VDL2X25Packet {
+                    pkt_type: x25.pkt_type,
+                    pkt_type_name: x25.pkt_type_name.clone(),
+                    chan_group: x25.chan_group,
+                    chan_num: x25.chan_num,
+                    more: x25.more,
+                });
+
                 if let Some(ref clnp) = x25.clnp {
                     // TODO: ADS-C v2
                 }
             }
+
+            // NOTE: upserted after the branches above, XID frames fill in the ground station's coords
+            let ground_station = if avlc.from_ground_station() { &frame_src } else { frame_dst.as_ref().unwrap() };
+            self.upsert_ground_station(ground_station).await?;
         } else {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "VDL2 frame missing AVLC block"));
         }
 
         Ok(cff::CommonFrame {
+            schema_version: cff::CFF_SCHEMA_VERSION,
+
             timestamp: unix_time_to_utc_datetime(raw_frame.vdl2.ts.to_f64()).unwrap_or(Utc::now().with_timezone(&UTC)).to_rfc3339_opts(SecondsFormat::Nanos, true),
             freq: raw_frame.vdl2.freq_as_mhz(),
             signal: raw_frame.vdl2.sig_level as f32,
@@ -408,11 +456,16 @@ impl XngModule for AoaModule {
             },
 
             indexed,
-            metadata: cff::Metadata { hfdl: None },
+            metadata: cff::Metadata {
+                hfdl: None,
+                vdl2: Some(metadata),
+            },
             
             src: frame_src,
             dst: frame_dst,
             acars: acars_content,
+            from_ground_station: None,
+            raw: None,
         })   
     }
 
@@ -2,6 +2,8 @@ use actix_web::web::Data;
 use tokio::io;
 use tokio::sync::RwLock;
 
+use crate::common::frame as cff;
+use crate::modules::ground_station_db::upsert_ground_station;
 use crate::modules::{settings::ModuleSettings, XngModule};
 
 use super::{AoaModule, AOA_COMMAND};
@@ -21,4 +23,12 @@ impl AoaModule {
         };
         Ok(settings.clone())
     }
+
+    pub async fn upsert_ground_station(&self, station: &cff::Entity) -> Result<(), io::Error> {
+        let Some(ref state_db) = self.state_db else {
+            return Ok(());
+        };
+
+        upsert_ground_station(state_db, station).await
+    }
 }
@@ -1,10 +1,12 @@
 use log::*;
 
 use async_trait::async_trait;
+use std::process::ExitStatus;
 use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::process::{Child, ChildStderr, ChildStdout};
+use tokio::time::{timeout, Duration};
 
-use crate::modules::session::{EndSessionReason, Session};
+use crate::modules::session::{EndSessionReason, Session, DEFAULT_EXIT_STATUS_WAIT_SECS};
 
 pub struct DumpVDL2Session {
     process: Child,
@@ -38,6 +40,22 @@ impl Session for DumpVDL2Session {
         &self.bands
     }
 
+    async fn exit_status(&mut self) -> Option<ExitStatus> {
+        match timeout(
+            Duration::from_secs(DEFAULT_EXIT_STATUS_WAIT_SECS),
+            self.process.wait(),
+        )
+        .await
+        {
+            Ok(Ok(status)) => Some(status),
+            Ok(Err(e)) => {
+                warn!("Failed to get dumpvdl2 exit status: {}", e.to_string());
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
     async fn end(&mut self, reason: EndSessionReason) {
         debug!("Terminating launched dumpvdl2 process...");
 
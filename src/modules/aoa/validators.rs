@@ -1,5 +1,62 @@
 use serde_json::Value;
 
+pub const VDL2_MIN_KHZ: u64 = 118000;
+pub const VDL2_MAX_KHZ: u64 = 137000;
+
+pub const OFF_RASTER_SNAP: &'static str = "snap";
+pub const OFF_RASTER_REJECT: &'static str = "reject";
+
+const VDL2_CHANNEL_SPACING_KHZ: u64 = 25;
+
+// NOTE: 8.33 kHz channels split each 25 kHz channel in three, offsets are rounded to whole kHz
+const VDL2_CHANNEL_OFFSETS_KHZ: [u64; 4] = [0, 8, 17, 25];
+
+pub fn nearest_vdl2_channel(khz: u64) -> u64 {
+    let base = khz - (khz % VDL2_CHANNEL_SPACING_KHZ);
+    let offset = khz - base;
+
+    let nearest = VDL2_CHANNEL_OFFSETS_KHZ
+        .iter()
+        .min_by_key(|&&x| x.abs_diff(offset))
+        .unwrap_or(&0);
+
+    (base + nearest).clamp(VDL2_MIN_KHZ, VDL2_MAX_KHZ)
+}
+
+pub fn is_vdl2_channel(khz: u64) -> bool {
+    (VDL2_MIN_KHZ..=VDL2_MAX_KHZ).contains(&khz) && nearest_vdl2_channel(khz) == khz
+}
+
+pub fn validate_off_raster_mode(value: &str) -> Result<(), String> {
+    match value.to_lowercase().as_str() {
+        OFF_RASTER_SNAP | OFF_RASTER_REJECT => Ok(()),
+        _ => Err(format!("Unknown off raster mode, expected one of: {}, {}", OFF_RASTER_SNAP, OFF_RASTER_REJECT)),
+    }
+}
+
 pub fn validate_next_session_band(value: &Value) -> Result<(), String> {
-    todo!()
+    let Some(bands) = value.as_array() else {
+        return Err(format!("Expected array of kHz frequencies"));
+    };
+    if bands.is_empty() {
+        return Err(format!("Expected at least one kHz frequency"));
+    }
+
+    for band in bands.iter() {
+        let Some(khz) = band.as_u64() else {
+            return Err(format!("Expected positive kHz frequency, got {}", band));
+        };
+        if !(VDL2_MIN_KHZ..=VDL2_MAX_KHZ).contains(&khz) {
+            return Err(format!("Invalid kHz range: {}", khz));
+        }
+        if !is_vdl2_channel(khz) {
+            return Err(format!(
+                "{} kHz is not on the VDL2 channel raster, nearest channel is {} kHz",
+                khz,
+                nearest_vdl2_channel(khz)
+            ));
+        }
+    }
+
+    Ok(())
 }
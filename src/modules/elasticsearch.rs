@@ -1,11 +1,12 @@
 use crate::common::{
-    arguments::{parse_elastic_index, parse_elastic_url},
-    es_utils::{create_es_client, get_xng_index_mapping},
+    arguments::{parse_elastic_index, parse_elastic_url, parse_http_timeout, parse_user_agent, DEFAULT_ELASTIC_INDEX},
+    es_utils::{create_es_client, create_es_index, delete_es_index, es_index_exists, load_xng_index_mapping, retry_es_call, DEFAULT_ES_ADMIN_RETRIES},
 };
 use clap::{arg, ArgMatches, Command};
-use elasticsearch::indices::{IndicesCreateParts, IndicesDeleteParts, IndicesExistsParts};
 use log::*;
-use reqwest::{StatusCode, Url};
+use reqwest::Url;
+use std::path::PathBuf;
+use tokio::io;
 
 pub const INIT_ES_COMMAND: &'static str = "init_es";
 pub const DELETE_ES_COMMAND: &'static str = "delete_es";
@@ -13,11 +14,19 @@ pub const DELETE_ES_COMMAND: &'static str = "delete_es";
 pub fn get_arguments(cmd: &'static str, desc: &'static str) -> Command {
     Command::new(cmd).about(desc).args(&[
         arg!(--elastic <URL> "Export processed common JSON frames to ElasticSearch"),
-        arg!(--"elastic-index" <INDEXNAME> "ElasticSearch Index name to use for storing common JSON frames"),
+        arg!(--"elastic-index" <INDEXNAME> "ElasticSearch Index name to create or delete. Default is xng_acars_db (deprecated, pass it explicitly), listening modules write to xng_<MODULE> (e.g. xng_hfdl)"),
         arg!(--apply "Apply changes to specified ElasticSearch server"),
         arg!(--validate "Validate SSL certificates"),
+        arg!(--"http-timeout-secs" <SECONDS> "Connect and request timeout for Elasticsearch calls (default: 30)"),
+        arg!(--"user-agent" <AGENT> "Sent to Elasticsearch as X-Opaque-Id to identify xng's requests (default: xng/<VERSION>)"),
+        arg!(--"mapping-file" <FILE> "JSON file deep merged over the default index mapping when creating the index"),
+        arg!(--retries <COUNT> "Times to retry a failed Elasticsearch request with backoff, requests Elasticsearch rejects are not retried (default: 2)"),
         arg!(-q --quiet "Silence all output"),
         arg!(-v --verbose ... "Verbose level"),
+        arg!(--"log-file" <PATH> "Also write log output to the given file, rotated by size"),
+        arg!(--"log-file-max-bytes" <BYTES> "Size in bytes at which the log file is rotated, 0 disables rotation (default: 10485760)"),
+        arg!(--"log-file-count" <COUNT> "Number of rotated log files to retain (default: 5)"),
+        arg!(--"log-file-only" "Only write log output to the log file, not stderr"),
     ])
 }
 
@@ -37,11 +46,49 @@ async fn perform_es_index_action(args: &ArgMatches, delete: bool) {
         error!("Required Elasticsearch URL argument not found");
         return;
     };
-    let elastic_index = parse_elastic_index(args);
+    // NOTE: the server (xng_acars_db) and each listening module (xng_<MODULE>) default to different indices,
+    //       so relying on the default is easy to get wrong
+    if args.get_one::<String>("elastic-index").is_none() {
+        warn!(
+            "No --elastic-index given, using {} which is only the server's default (listening modules write to xng_<MODULE>). Relying on this default is deprecated, pass --elastic-index explicitly",
+            DEFAULT_ELASTIC_INDEX
+        );
+    }
+    let elastic_index = parse_elastic_index(args, DEFAULT_ELASTIC_INDEX);
+    info!("Using Elasticsearch index {}", elastic_index);
     let validate = args.get_flag("validate");
     let apply = args.get_flag("apply");
+    let retries = args
+        .get_one::<String>("retries")
+        .unwrap_or(&String::from("default"))
+        .parse::<u32>()
+        .unwrap_or(DEFAULT_ES_ADMIN_RETRIES);
+
+    let mapping_file = args.get_one::<String>("mapping-file").map(PathBuf::from);
+    if delete && mapping_file.is_some() {
+        warn!("--mapping-file is ignored when deleting an index");
+    }
+    let mapping = if delete {
+        None
+    } else {
+        match load_xng_index_mapping(mapping_file.as_ref()) {
+            Ok(x) => Some(x),
+            Err(e) => {
+                error!("Failed to load index mapping: {}", e.to_string());
+                return;
+            }
+        }
+    };
+
+    let http_timeout = match parse_http_timeout(args) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}", e.to_string());
+            return;
+        }
+    };
 
-    let client = match create_es_client(&mut elastic_url, validate) {
+    let client = match create_es_client(&mut elastic_url, validate, http_timeout, parse_user_agent(args).as_str()) {
         Ok(x) => x,
         Err(e) => {
             error!(
@@ -52,25 +99,15 @@ async fn perform_es_index_action(args: &ArgMatches, delete: bool) {
         }
     };
 
-    let exists = match client
-        .indices()
-        .exists(IndicesExistsParts::Index(&[elastic_index.as_str()]))
-        .send()
-        .await
-    {
+    let exists = match retry_es_call("Index existence check", retries, None, || es_index_exists(&client, elastic_index.as_str())).await {
         Ok(x) => x,
         Err(e) => {
-            error!(
-                "Failed to determine existence of index {} on {}: {}",
-                elastic_index,
-                elastic_url,
-                e.to_string()
-            );
+            error!("{} on {}", e.to_string(), elastic_url);
             return;
         }
     };
 
-    if exists.status_code().is_success() {
+    if exists {
         if !delete {
             error!("Index {} already exists, use the delete_es subcommand to delete the index first before rerunning.", elastic_index);
             return;
@@ -84,38 +121,14 @@ async fn perform_es_index_action(args: &ArgMatches, delete: bool) {
             return;
         }
 
-        let delete = match client
-            .indices()
-            .delete(IndicesDeleteParts::Index(&[elastic_index.as_str()]))
-            .send()
-            .await
-        {
-            Ok(x) => x,
-            Err(e) => {
-                error!(
-                    "Failed to send index {} deletion request to {}: {}",
-                    elastic_index,
-                    elastic_url,
-                    e.to_string()
-                );
-                return;
-            }
-        };
-
-        if delete.status_code().is_success() {
-            println!(
+        match retry_es_call("Index deletion", retries, Some(io::ErrorKind::NotFound), || delete_es_index(&client, elastic_index.as_str())).await {
+            Ok(_) => println!(
                 "Deleted index {} on Elasticsearch server at {}",
                 elastic_index, elastic_url
-            );
-        } else {
-            error!("Deletion failed: error code = {:?}", delete.status_code());
+            ),
+            Err(e) => error!("{} on {}", e.to_string(), elastic_url),
         }
-
-        trace!(
-            "Response:\n{}",
-            delete.text().await.unwrap_or(String::from("None"))
-        )
-    } else if exists.status_code() == StatusCode::NOT_FOUND {
+    } else {
         if delete {
             error!(
                 "Index {} does not exist. We cannot delete an index that doesn't exist!",
@@ -129,35 +142,21 @@ async fn perform_es_index_action(args: &ArgMatches, delete: bool) {
                 "Actions to be performed: create and define index {} on Elasticsearch server at {}; rerun with --apply to apply operations", 
                 elastic_index, elastic_url
             );
+            trace!(
+                "Index mapping:\n{}",
+                serde_json::to_string_pretty(&mapping).unwrap_or(String::from("None"))
+            );
             return;
         }
 
-        let response = match client
-            .indices()
-            .create(IndicesCreateParts::Index(elastic_index.as_str()))
-            .body(get_xng_index_mapping())
-            .send()
-            .await
-        {
-            Ok(x) => x,
-            Err(e) => {
-                error!("Failed to create index with mapping: {}", e.to_string());
-                return;
-            }
-        };
-        if response.status_code().is_success() {
-            println!(
+        let mapping = mapping.unwrap_or_default();
+        match retry_es_call("Index creation", retries, Some(io::ErrorKind::AlreadyExists), || create_es_index(&client, elastic_index.as_str(), mapping.clone())).await {
+            Ok(_) => println!(
                 "Created index {} on Elasticsearch server at {}",
                 elastic_index, elastic_url
-            );
-        } else {
-            error!("Creation failed: error code = {:?}", response.status_code());
+            ),
+            Err(e) => error!("{} on {}", e.to_string(), elastic_url),
         }
-
-        trace!(
-            "Response:\n{}",
-            response.text().await.unwrap_or(String::from("None"))
-        )
     }
 }
 
@@ -0,0 +1,229 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::io;
+
+use crate::common::frame as cff;
+use crate::common::wkt::WKTPoint;
+use crate::utils::timestamp::unix_time_to_utc_datetime;
+
+// NOTE: every mapping value is either a JSON pointer into the decoder's message (a string starting with "/")
+//       or a constant that is used as is
+#[derive(Debug, Deserialize)]
+pub struct EntityMapping {
+    pub kind: Value,
+
+    pub icao: Option<Value>,
+    pub gs: Option<Value>,
+    pub id: Option<Value>,
+    pub callsign: Option<Value>,
+    pub tail: Option<Value>,
+
+    pub latitude: Option<Value>,
+    pub longitude: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcarsMapping {
+    pub mode: Option<Value>,
+    pub label: Value,
+    pub more: Option<Value>,
+
+    pub ack: Option<Value>,
+    pub blk_id: Option<Value>,
+    pub msg_num: Option<Value>,
+    pub msg_num_seq: Option<Value>,
+    pub tail: Option<Value>,
+    pub flight: Option<Value>,
+    pub sublabel: Option<Value>,
+    pub mfi: Option<Value>,
+    pub cfi: Option<Value>,
+    pub text: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FrameMapping {
+    pub app_name: Option<Value>,
+    pub app_version: Option<Value>,
+
+    pub timestamp: Value,
+
+    pub freq: Value,
+    pub freq_scale: Option<f64>,
+
+    pub signal: Option<Value>,
+    pub err: Option<Value>,
+
+    pub src: EntityMapping,
+    pub dst: Option<EntityMapping>,
+
+    pub acars: Option<AcarsMapping>,
+}
+
+fn resolve<'a>(spec: &'a Value, msg: &'a Value) -> Option<&'a Value> {
+    let value = match spec.as_str() {
+        Some(pointer) if pointer.starts_with('/') => msg.pointer(pointer)?,
+        _ => spec,
+    };
+
+    if value.is_null() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn resolve_string(spec: &Option<Value>, msg: &Value) -> Option<String> {
+    match resolve(spec.as_ref()?, msg)? {
+        Value::String(x) => Some(x.clone()),
+        Value::Number(x) => Some(x.to_string()),
+        Value::Bool(x) => Some(x.to_string()),
+        _ => None,
+    }
+}
+
+fn resolve_f64(spec: &Option<Value>, msg: &Value) -> Option<f64> {
+    match resolve(spec.as_ref()?, msg)? {
+        Value::Number(x) => x.as_f64(),
+        Value::String(x) => x.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+fn resolve_bool(spec: &Option<Value>, msg: &Value) -> Option<bool> {
+    match resolve(spec.as_ref()?, msg)? {
+        Value::Bool(x) => Some(*x),
+        Value::Number(x) => x.as_u64().map(|y| y != 0),
+        Value::String(x) => x.trim().parse::<bool>().ok(),
+        _ => None,
+    }
+}
+
+// NOTE: numbers are treated as unix time in seconds, strings as RFC 3339
+fn resolve_timestamp(spec: &Value, msg: &Value) -> Option<DateTime<Utc>> {
+    match resolve(spec, msg)? {
+        Value::Number(x) => unix_time_to_utc_datetime(x.as_f64()?).map(|y| y.with_timezone(&Utc)),
+        Value::String(x) => DateTime::parse_from_rfc3339(x.trim())
+            .ok()
+            .map(|y| y.with_timezone(&Utc)),
+        _ => None,
+    }
+}
+
+impl EntityMapping {
+    fn to_entity(&self, msg: &Value) -> Result<cff::Entity, io::Error> {
+        let Some(kind) = resolve_string(&Some(self.kind.clone()), msg) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Entity type could not be resolved from {}", self.kind),
+            ));
+        };
+
+        let coords = match (
+            resolve_f64(&self.latitude, msg),
+            resolve_f64(&self.longitude, msg),
+        ) {
+            (Some(y), Some(x)) => Some(WKTPoint { x, y, z: 0.0 }),
+            _ => None,
+        };
+
+        Ok(cff::Entity {
+            kind,
+            icao: resolve_string(&self.icao, msg).map(|x| x.to_uppercase()),
+            gs: resolve_string(&self.gs, msg),
+            id: resolve_f64(&self.id, msg).map(|x| x as u32),
+            callsign: resolve_string(&self.callsign, msg).map(|x| x.trim().to_string()),
+            tail: resolve_string(&self.tail, msg).map(|x| x.trim().to_string()),
+            coords,
+        })
+    }
+}
+
+impl AcarsMapping {
+    fn to_acars(&self, msg: &Value) -> Option<cff::ACARS> {
+        Some(cff::ACARS {
+            mode: resolve_string(&self.mode, msg).unwrap_or(String::from("2")),
+            more: resolve_bool(&self.more, msg).unwrap_or(false),
+            label: resolve_string(&Some(self.label.clone()), msg)?,
+            ack: resolve_string(&self.ack, msg),
+            blk_id: resolve_string(&self.blk_id, msg),
+            msg_num: resolve_string(&self.msg_num, msg),
+            msg_num_seq: resolve_string(&self.msg_num_seq, msg),
+            tail: resolve_string(&self.tail, msg),
+            flight: resolve_string(&self.flight, msg),
+            sublabel: resolve_string(&self.sublabel, msg),
+            mfi: resolve_string(&self.mfi, msg),
+            cfi: resolve_string(&self.cfi, msg),
+            text: resolve_string(&self.text, msg),
+        })
+    }
+}
+
+impl FrameMapping {
+    pub fn load(path: &PathBuf) -> Result<FrameMapping, io::Error> {
+        let contents = fs::read_to_string(path)?;
+        match serde_json::from_str::<FrameMapping>(&contents) {
+            Ok(x) => Ok(x),
+            Err(e) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Invalid field mapping in {}: {}",
+                    path.to_string_lossy(),
+                    e.to_string()
+                ),
+            )),
+        }
+    }
+
+    pub fn to_common_frame(&self, msg: &Value, default_app_name: &str) -> Result<cff::CommonFrame, io::Error> {
+        let Some(timestamp) = resolve_timestamp(&self.timestamp, msg) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Timestamp could not be resolved from {}", self.timestamp),
+            ));
+        };
+        let Some(freq) = resolve_f64(&Some(self.freq.clone()), msg) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Frequency could not be resolved from {}", self.freq),
+            ));
+        };
+
+        let dst = match self.dst {
+            Some(ref mapping) => Some(mapping.to_entity(msg)?),
+            None => None,
+        };
+
+        Ok(cff::CommonFrame {
+            schema_version: cff::CFF_SCHEMA_VERSION,
+
+            timestamp: timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            freq: freq * self.freq_scale.unwrap_or(1.0),
+            signal: resolve_f64(&self.signal, msg).unwrap_or(0.0) as f32,
+            err: resolve_bool(&self.err, msg).unwrap_or(false),
+
+            paths: Vec::new(),
+
+            app: cff::AppInfo {
+                name: resolve_string(&self.app_name, msg).unwrap_or(default_app_name.to_string()),
+                version: resolve_string(&self.app_version, msg).unwrap_or_default(),
+            },
+
+            indexed: cff::Indexed {
+                timestamp: timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+
+                ..Default::default()
+            },
+            metadata: cff::Metadata { hfdl: None, vdl2: None },
+
+            src: self.src.to_entity(msg)?,
+            dst,
+            acars: self.acars.as_ref().and_then(|x| x.to_acars(msg)),
+            from_ground_station: None,
+            raw: None,
+        })
+    }
+}
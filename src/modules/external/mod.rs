@@ -0,0 +1,186 @@
+use log::*;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::BufReader;
+
+use actix_web::web::Data;
+use async_trait::async_trait;
+use clap::{arg, Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use tokio::{io, process};
+
+use self::mapping::FrameMapping;
+use self::session::ExternalSession;
+
+use super::session::{DryRunSession, EndSessionReason};
+use super::settings::ModuleSettings;
+use super::XngModule;
+use crate::common::frame::CommonFrame;
+use crate::modules::PROP_LISTENING_BAND;
+use crate::server::db::StateDB;
+
+mod mapping;
+mod module;
+mod session;
+
+const EXTERNAL_COMMAND: &'static str = "external";
+
+const DEFAULT_SESSION_TIMEOUT_SECS: u64 = 900;
+
+#[derive(Default)]
+pub struct ExternalModule {
+    name: &'static str,
+    settings: Option<Data<RwLock<ModuleSettings>>>,
+
+    bin: PathBuf,
+    app_name: String,
+    mapping: Option<FrameMapping>,
+    args: Vec<String>,
+    bands: Vec<u64>,
+
+    dry_run: bool,
+}
+
+#[async_trait]
+impl XngModule for ExternalModule {
+    fn id(&self) -> &'static str {
+        self.name
+    }
+
+    fn default_session_timeout_secs(&self) -> u64 {
+        DEFAULT_SESSION_TIMEOUT_SECS
+    }
+
+    fn get_arguments(&self) -> Command {
+        Command::new(EXTERNAL_COMMAND)
+            .about("Listen to any decoder emitting line delimited JSON, mapped to common JSON frames by a field mapping file")
+            .args(&[
+                arg!(--bin <FILE> "Path to decoder binary"),
+                arg!(--mapping <FILE> "Path to JSON field mapping file describing how decoder messages map to common JSON frames"),
+                Arg::new("bands").long("bands").value_delimiter(',').help("Frequencies in kHz the decoder listens on, only used for reporting the listening band"),
+            ])
+            .arg(Arg::new("external-args").action(ArgAction::Append))
+    }
+
+    async fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error> {
+        self.dry_run = args.get_flag("dry-run");
+
+        if args.get_flag("feed-airframes") || args.get_flag("feed-airframes-manual") {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Feeding airframes.io is not supported for external decoders, configure the decoder's own output instead"));
+        }
+
+        let Some(bin_path) = args.get_one::<String>("bin").map(PathBuf::from) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required --bin <FILE> argument"));
+        };
+        if !bin_path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Provided decoder path is not a file: {}",
+                    bin_path.to_string_lossy()
+                ),
+            ));
+        }
+        self.app_name = bin_path
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or(String::from(EXTERNAL_COMMAND));
+        self.bin = bin_path;
+
+        let Some(mapping_path) = args.get_one::<String>("mapping").map(PathBuf::from) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required --mapping <FILE> argument"));
+        };
+        self.mapping = Some(FrameMapping::load(&mapping_path)?);
+
+        self.args = args
+            .get_many::<String>("external-args")
+            .map(|x| x.map(|y| y.to_string()).collect::<Vec<String>>())
+            .unwrap_or_default();
+
+        self.bands = match args.get_many::<String>("bands") {
+            Some(bands) => {
+                let mut freqs: Vec<u64> = Vec::new();
+                for band in bands {
+                    match band.trim().parse::<u64>() {
+                        Ok(x) => freqs.push(x),
+                        Err(e) => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                format!("Invalid kHz frequency in --bands, {}: {}", band, e.to_string()),
+                            ))
+                        }
+                    }
+                }
+                freqs.sort_unstable();
+                freqs
+            }
+            None => Vec::new(),
+        };
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        settings: Data<RwLock<ModuleSettings>>,
+        _state_db: Data<RwLock<StateDB>>,
+    ) {
+        self.settings = Some(settings.clone());
+    }
+
+    async fn start_session(
+        &mut self,
+        _last_end_reason: EndSessionReason,
+    ) -> Result<Box<dyn super::session::Session>, io::Error> {
+        let settings = self.get_settings()?;
+
+        let mut cmd = process::Command::new(self.bin.clone());
+        cmd.stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(self.args.clone());
+
+        if self.dry_run {
+            return Ok(Box::new(DryRunSession::from_command(&cmd, self.bands.clone())));
+        }
+
+        let mut proc = match cmd.spawn() {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to spawn process: {}", e.to_string()),
+                ))
+            }
+        };
+
+        settings.write().await.set(PROP_LISTENING_BAND, json!(self.bands))?;
+        debug!("New external session started for {}, listening: {:?}", self.app_name, self.bands);
+
+        let Some(stdout) = proc.stdout.take() else {
+            return Err(io::Error::new(io::ErrorKind::Other, "Unable to take stdout from child process"));
+        };
+        let Some(stderr) = proc.stderr.take() else {
+            return Err(io::Error::new(io::ErrorKind::Other, "Unable to take stderr from child process"));
+        };
+
+        Ok(Box::new(ExternalSession::new(proc, BufReader::new(stdout), stderr, self.bands.clone())))
+    }
+
+    async fn process_message(
+        &mut self,
+        _current_band: &Vec<u64>,
+        msg: &str,
+    ) -> Result<CommonFrame, io::Error> {
+        let Some(ref mapping) = self.mapping else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Field mapping is not loaded"));
+        };
+
+        let raw_msg = serde_json::from_str::<Value>(msg)?;
+        mapping.to_common_frame(&raw_msg, &self.app_name)
+    }
+
+    async fn reload(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
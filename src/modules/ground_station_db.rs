@@ -1,10 +1,14 @@
 use std::collections::HashMap;
 
+use actix_web::web::Data;
 use csv::ReaderBuilder;
 use serde::Deserialize;
 use tokio::io;
+use tokio::sync::RwLock;
 
+use crate::common::frame::Entity;
 use crate::common::wkt::WKTPoint;
+use crate::server::db::StateDB;
 
 #[derive(Debug, Deserialize)]
 struct GSRawRecord {
@@ -36,6 +40,12 @@ pub struct GSRecord {
     pub coords: WKTPoint,
 }
 
+impl GSRecord {
+    pub fn display_name(&self) -> String {
+        format!("{} ({}/{})", self.airport_name, self.airport_iata, self.airport_icao)
+    }
+}
+
 impl GSRawRecord {
     pub fn coords_as_wkt(&self) -> Option<WKTPoint> {
         let mut val = self.lat.clone().trim().to_string();
@@ -85,3 +95,23 @@ impl GroundStationDB {
         self.db.get(addr)
     }
 }
+
+// NOTE: stations without coordinates are left alone, they would be created at 0,0
+pub async fn upsert_ground_station(state_db: &Data<RwLock<StateDB>>, station: &Entity) -> Result<(), io::Error> {
+    let Some(ref coords) = station.coords else {
+        return Ok(());
+    };
+    let Some(ref addr) = station.icao else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Ground station missing ICAO: {:?}", station)));
+    };
+    let gs_name = station.gs.as_ref().unwrap_or(addr);
+
+    match u32::from_str_radix(addr.as_str(), 16) {
+        Ok(x) => if let Err(e) = state_db.read().await.create_ground_station(x, gs_name, None, coords.y, coords.x).await {
+            return Err(io::Error::new(io::ErrorKind::Other, format!("Failed to create ground station in state DB: {}", e.to_string())));
+        },
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a valid hexadecimal ICAO addr: {}", addr, e.to_string())))
+    }
+
+    Ok(())
+}
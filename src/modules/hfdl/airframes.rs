@@ -1,9 +1,12 @@
 use std::io;
 
-use reqwest::StatusCode;
+use log::*;
+use reqwest::{Client, StatusCode};
 use serde::Deserialize;
 use serde_json;
 
+use crate::common::http::{generate_request_id, REQUEST_ID_HEADER};
+
 #[derive(Debug, Deserialize)]
 pub struct GroundStationFreqInfo {
     pub active: Vec<u16>,
@@ -31,9 +34,20 @@ impl HFDLGroundStationStatus {
     }
 }
 
-pub async fn get_airframes_gs_status() -> io::Result<HFDLGroundStationStatus> {
-    let response = match reqwest::get("https://api.airframes.io/hfdl/ground-stations").await {
+pub async fn get_airframes_gs_status(client: &Client) -> io::Result<HFDLGroundStationStatus> {
+    let request_id = generate_request_id();
+    debug!("Fetching airframes.io HFDL ground station map, {} = {}", REQUEST_ID_HEADER, request_id);
+
+    let response = match client
+        .get("https://api.airframes.io/hfdl/ground-stations")
+        .header(REQUEST_ID_HEADER, request_id.as_str())
+        .send()
+        .await
+    {
         Ok(r) => r,
+        Err(e) if e.is_timeout() => {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, e.to_string()))
+        }
         Err(e) => {
             return Err(io::Error::new(
                 io::ErrorKind::ConnectionAborted,
@@ -63,6 +77,9 @@ pub async fn get_airframes_gs_status() -> io::Result<HFDLGroundStationStatus> {
 
     let body = match response.text().await {
         Ok(v) => v,
+        Err(e) if e.is_timeout() => {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, e.to_string()))
+        }
         Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
     };
 
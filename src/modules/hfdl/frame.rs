@@ -1,7 +1,7 @@
 use serde::Deserialize;
 use serde_valid::Validate;
 
-use crate::common::formats::{validate_entity_type, Application, EntityType, Timestamp};
+use crate::common::formats::{validate_entity_type, Application, EntityType, Timestamp, RESERVED_ENTITY_KIND};
 use crate::common::frame;
 use crate::common::wkt::WKTPoint;
 
@@ -36,12 +36,15 @@ impl Entity {
             return EntityType::GroundStation;
         }
 
-        unreachable!("Validation failed: encountered {}", normalized_type)
+        EntityType::Reserved
     }
 
     pub fn to_common_frame_entity(&self, systable: &SystemTable) -> frame::Entity {
         frame::Entity {
-            kind: self.entity_type.clone(),
+            kind: match self.kind() {
+                EntityType::Reserved => String::from(RESERVED_ENTITY_KIND),
+                _ => self.entity_type.clone(),
+            },
             icao: if let Some(ref ac_info) = self.ac_info {
                 Some(ac_info.icao.clone())
             } else {
@@ -306,6 +309,10 @@ pub struct HFDL {
 }
 
 impl HFDL {
+    pub fn slot_type(&self) -> frame::HFDLSlot {
+        frame::HFDLSlot::from(self.slot.clone())
+    }
+
     pub fn freq_as_mhz(&self) -> f64 {
         self.freq as f64 / 1000000.0
     }
@@ -6,9 +6,10 @@ use crate::common::wkt::WKTPolyline;
 use crate::modules::PROP_LISTENING_BAND;
 use crate::modules::hfdl::airframes::get_airframes_gs_status;
 use crate::modules::hfdl::schedule::parse_session_schedule;
-use crate::modules::hfdl::utils::{freq_bands_by_sample_rate, first_freq_above_eq, get_max_dist_khz_by_sample_rate};
+use crate::modules::hfdl::utils::{freq_bands_by_sample_rate, dedup_paths_by_party, first_freq_above_eq, get_max_dist_khz_by_sample_rate, request_data_bits, select_session_band};
 use crate::server::db::StateDB;
 use crate::utils::normalize_tail;
+use crate::utils::version::check_decoder_version;
 use crate::utils::timestamp::{split_unix_time_to_utc_datetime, nearest_time_in_past, unix_time_to_utc_datetime};
 
 use self::frame::Frame;
@@ -16,7 +17,7 @@ use self::schedule::validate_session_schedule;
 use self::session::DumpHFDLSession;
 use self::systable::SystemTable;
 use self::validators::{validate_session_method, validate_next_session_band};
-use super::session::EndSessionReason;
+use super::session::{DryRunSession, EndSessionReason};
 use super::settings::{ModuleSettings, update_station_by_frequencies};
 use super::XngModule;
 use actix_web::web::Data;
@@ -25,67 +26,94 @@ use chrono::{Utc, SecondsFormat, DateTime, Local, Duration};
 use chrono_tz::UTC;
 use clap::{arg, Arg, ArgAction, ArgMatches, Command};
 use log::*;
-use rand::Rng;
-use serde_json::{json, Value};
-use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::path::PathBuf;
 use std::ops::DerefMut;
 use std::process::Stdio;
+use std::time::Duration as StdDuration;
 use tokio::io::{self, BufReader};
 use tokio::process;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 
 mod airframes;
 mod frame;
 mod module;
-mod schedule;
+pub mod schedule;
 mod session;
 mod systable;
 mod utils;
 mod validators;
 
 const DEFAULT_BIN_PATH: &'static str = "/usr/bin/dumphfdl";
+const MIN_DECODER_VERSION: &'static str = "1.4.0";
 const DEFAULT_SYSTABLE_PATH: &'static str = "/etc/systable.conf";
 
 const DEFAULT_STALE_TIMEOUT_SECS: u64 = 2700;
 const DEFAULT_SESSION_TIMEOUT_SECS: u64 = 600;
 const DEFAULT_SESSION_METHOD: &'static str = "random";
+const DEFAULT_SEED_ACTIVE_WINDOW_MINS: i64 = 60;
+const DEFAULT_FREQ_CHANGE_DEBOUNCE_COUNT: u32 = 1;
+const DEFAULT_SOAPY_ENUMERATE_TIMEOUT_SECS: u64 = 0;
+
+const TIMESTAMP_SOURCE_MESSAGE: &'static str = "message";
+const TIMESTAMP_SOURCE_RECEPTION: &'static str = "reception";
 
 const HFDL_COMMAND: &'static str = "hfdl";
 
+const ENV_XNG_START_BAND: &'static str = "XNG_START_BAND";
+
 const PROP_STALE_TIMEOUT_SEC: &'static str = "stale_timeout_sec";
-const PROP_USE_AIRFRAMES_GS: &'static str = "use_airframes_gs";
+pub const PROP_USE_AIRFRAMES_GS: &'static str = "use_airframes_gs";
 const PROP_SAMPLE_RATE: &'static str = "sample_rate";
 const PROP_NEXT_SESSION_BAND: &'static str = "next_session_band";
-const PROP_SESSION_SCHEDULE: &'static str = "session_schedule";
-const PROP_SESSION_METHOD: &'static str = "session_method";
+pub const PROP_SESSION_SCHEDULE: &'static str = "session_schedule";
+pub const PROP_SESSION_METHOD: &'static str = "session_method";
 const PROP_ONLY_USE_ACTIVE: &'static str = "only_use_active";
 
 #[derive(Default)]
 pub struct HfdlModule {
     name: &'static str,
     settings: Option<Data<RwLock<ModuleSettings>>>,
+    state_db: Option<Data<RwLock<StateDB>>>,
 
     sample_rates: Vec<u64>,
+    pending_enumeration: Option<JoinHandle<Result<Vec<u64>, io::Error>>>,
     
     bin: PathBuf,
+    decoder_version: Option<String>,
     systable: SystemTable,
+    airframes_gs_names: HashMap<u8, String>,
 
     args: Vec<String>,
     driver: String,
 
     feed_airframes: bool,
+    feed_airframes_manual: bool,
+    dry_run: bool,
+    no_soapy: bool,
+    sdr_stats: bool,
+    use_message_time: bool,
     
     sample_rate: u64,
     stale_timeout_secs: u64,
     use_airframes_gs: bool,
     only_use_active: bool,
+    follow_freq_changes: HashSet<String>,
+    freq_change_debounce_count: u32,
+    // NOTE: reported frequency set, consecutive SPDUs reporting it and the set the station had before the change
+    pending_freq_changes: HashMap<u8, (Vec<u64>, u32, Vec<u64>)>,
     next_session_band: u64,
     schedule: String,
     method: String,
 
     last_req_session_band: u64,
     last_random_freq_band: u64,
+    rng: Option<StdRng>,
 }
 
 #[async_trait]
@@ -103,20 +131,30 @@ impl XngModule for HfdlModule {
             .about("Listen to HFDL messages using dumphfdl")
             .args(&[
                 arg!(--bin <FILE> "Path to dumphfdl binary"),
-                arg!(--systable <FILE> "Path to dumphfdl system table configuration"),
+                arg!(--systable <FILE> ... "Path to dumphfdl system table configuration, repeat to merge several tables"),
                 arg!(--"stale-timeout" <SECONDS> "Elapsed time since last update before an aircraft and ground station frequency data is considered stale"),
                 arg!(--"sample-rate" <HERTZ> "Initial sample rate to use for splitting HFDL spectrum into bands of coverage"),
+                arg!(--"soapy-enumerate-timeout-secs" <SECONDS> "Time to wait for SoapySDR sample rate enumeration before falling back to --sample-rates, 0 waits forever (default: 0)"),
+                arg!(--"sample-rates" <HERTZ> "Comma separated sample rates the SDR supports, used when SoapySDR enumeration times out"),
+                arg!(--"no-soapy" "Skip SoapySDR device enumeration and use --sample-rate as is, for file or rtl_tcp sources (requires --sample-rate)"),
                 arg!(--"use-airframes-gs-map" "Use airframes.io's live HFDL ground station frequency map"),
                 arg!(--"only-listen-on-active" "Only listen on active HFDL frequencies (NOTE: use --use-airframes-gs-map to avoid rapid initial session ends on new SPDUs)"),
-                arg!(--"start-band-contains" <HERTZ> "Initial starting band to listen on. Overrides --schedule if both are configured"),
+                arg!(--"start-band-contains" <HERTZ> "Initial starting band to listen on. Overrides --schedule if both are configured. Falls back to XNG_START_BAND env var if not provided"),
                 arg!(--schedule <SCHEDULE_FMT> "Session switch schedule in the format of: time=<HOUR_0_TO_23>,band_contains=<FREQ_HZ>;..."),
-                arg!(--method <METHOD_TYPE> "Session switching methods to use. Default method is random. Valid methods: random, inc, dec, static")
+                arg!(--method <METHOD_TYPE> "Session switching methods to use. Default method is random. Valid methods: random, inc, dec, static"),
+                arg!(--"timestamp-source" <SOURCE> "Timestamp stored in the state DB and indexed to Elasticsearch. Valid sources: message (default, embedded HFNPDU time when present), reception"),
+                arg!(--"sdr-stats" "Parse key=value SDR statistics (sample_rate, gain, overloads) from the decoder's STDERR and report the latest values in /api/status; stock dumphfdl does not print these"),
+                arg!(--"session-seed" <SEED> "Seed the random session method so band selection is reproducible (default: unseeded)"),
+                arg!(--"freq-change-debounce-count" <COUNT> "Consecutive SPDUs a ground station frequency change near the current band must persist for before the session is reloaded (default: 1)"),
+                arg!(--"follow-freq-changes" <ICAOS> "Comma separated ICAO addresses of aircraft to follow, ending the session and retuning when they report a frequency change outside the current band"),
             ])
             .arg(Arg::new("hfdl-args").action(ArgAction::Append))
     }
 
-    fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error> {
-        self.feed_airframes = args.get_flag("feed-airframes");
+    async fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error> {
+        self.feed_airframes_manual = args.get_flag("feed-airframes-manual");
+        self.feed_airframes = args.get_flag("feed-airframes") || self.feed_airframes_manual;
+        self.dry_run = args.get_flag("dry-run");
 
         let bin_path = parse_bin_path(args, DEFAULT_BIN_PATH);
         if !bin_path.is_file() {
@@ -129,11 +167,36 @@ impl XngModule for HfdlModule {
             ));
         }
         self.bin = bin_path;
+        self.decoder_version = check_decoder_version(
+            &self.bin,
+            MIN_DECODER_VERSION,
+            args.get_flag("require-min-decoder-version"),
+        )
+        .await?;
+
+        let systable_paths: Vec<PathBuf> = match args.get_many::<String>("systable") {
+            Some(paths) => paths.map(PathBuf::from).collect(),
+            None => vec![PathBuf::from(DEFAULT_SYSTABLE_PATH)],
+        };
+        self.systable = SystemTable::load(&systable_paths[0])?;
+        if systable_paths.len() > 1 {
+            for path in systable_paths[1..].iter() {
+                self.systable.merge(SystemTable::load(path)?);
+            }
 
-        self.systable = SystemTable::load(&PathBuf::from(
-            args.get_one::<String>("systable")
-                .unwrap_or(&DEFAULT_SYSTABLE_PATH.to_string()),
-        ))?;
+            if let Err(e) = self.systable.write_merged() {
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("Failed to write merged systable: {}", e.to_string()),
+                ));
+            }
+            info!(
+                "Merged {} systables with {} ground stations into {}",
+                systable_paths.len(),
+                self.systable.stations.len(),
+                self.systable.path.to_string_lossy()
+            );
+        }
         
         let Some(hfdl_args) = args.get_many("hfdl-args") else {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required HFDL positional arguments"));
@@ -143,10 +206,17 @@ impl XngModule for HfdlModule {
             .map(|x: &String| x.to_string())
             .collect::<Vec<String>>();
 
-        let Some(driver) = extract_soapysdr_driver(&self.args) else {
-            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing --soapysdr argument with driver= specification"));              
-        };
-        self.driver = driver.clone();
+        self.no_soapy = args.get_flag("no-soapy");
+        if self.no_soapy {
+            if !args.contains_id("sample-rate") {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required --sample-rate <HERTZ> argument when --no-soapy is enabled"));
+            }
+        } else {
+            let Some(driver) = extract_soapysdr_driver(&self.args) else {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing --soapysdr argument with driver= specification"));              
+            };
+            self.driver = driver.clone();
+        }
 
         if self.feed_airframes
             && !self
@@ -157,7 +227,40 @@ impl XngModule for HfdlModule {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required --station-id <name> argument when feed airframes.io option is enabled"));
         }
 
-        if let Err(e) = self.load_sample_rates(&driver) {
+        if self.feed_airframes_manual && !self.args.iter().any(|x| x.eq_ignore_ascii_case("--output")) {
+            warn!("--feed-airframes-manual is set but no --output was passed to the decoder, frames will not reach airframes.io");
+        }
+
+        self.sample_rate = args
+            .get_one::<String>("sample-rate")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(512000);
+
+        let mut fallback_rates: Vec<u64> = Vec::new();
+        if let Some(raw_rates) = args.get_one::<String>("sample-rates") {
+            for raw_rate in raw_rates.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+                match raw_rate.parse::<u64>() {
+                    Ok(x) if x > 0 => fallback_rates.push(x),
+                    _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid sample rate in --sample-rates: {}", raw_rate))),
+                }
+            }
+            fallback_rates.sort_unstable();
+            fallback_rates.dedup();
+        }
+        let enumerate_timeout = match args
+            .get_one::<String>("soapy-enumerate-timeout-secs")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_SOAPY_ENUMERATE_TIMEOUT_SECS)
+        {
+            0 => None,
+            x => Some(StdDuration::from_secs(x)),
+        };
+
+        if self.no_soapy {
+            info!("SoapySDR device enumeration disabled, using sample rate of {} as is", self.sample_rate);
+        } else if let Err(e) = self.load_sample_rates(&self.driver.clone(), enumerate_timeout, &fallback_rates).await {
             return Err(
                 io::Error::new(
                     io::ErrorKind::InvalidInput, 
@@ -165,12 +268,6 @@ impl XngModule for HfdlModule {
                 )
             );    
         }
-        
-        self.sample_rate = args
-            .get_one::<String>("sample-rate")
-            .unwrap_or(&String::from("default"))
-            .parse::<u64>()
-            .unwrap_or(512000);
 
         self.stale_timeout_secs = args
             .get_one::<String>("stale-timeout")
@@ -179,7 +276,30 @@ impl XngModule for HfdlModule {
             .unwrap_or(DEFAULT_STALE_TIMEOUT_SECS);
 
         self.use_airframes_gs = args.get_flag("use-airframes-gs-map");
+        self.sdr_stats = args.get_flag("sdr-stats");
         self.only_use_active = args.get_flag("only-listen-on-active");
+        if let Some(raw_seed) = args.get_one::<String>("session-seed") {
+            match raw_seed.parse::<u64>() {
+                Ok(x) => self.rng = Some(StdRng::seed_from_u64(x)),
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid --session-seed, {}: {}", raw_seed, e.to_string()))),
+            }
+        }
+        self.freq_change_debounce_count = args
+            .get_one::<String>("freq-change-debounce-count")
+            .unwrap_or(&String::from("default"))
+            .parse::<u32>()
+            .unwrap_or(DEFAULT_FREQ_CHANGE_DEBOUNCE_COUNT)
+            .max(1);
+
+        if let Some(raw_icaos) = args.get_one::<String>("follow-freq-changes") {
+            for icao in raw_icaos.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+                if icao.len() != 6 || u32::from_str_radix(icao, 16).is_err() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid ICAO address in --follow-freq-changes: {}", icao)));
+                }
+                self.follow_freq_changes.insert(icao.to_uppercase());
+            }
+            info!("Following frequency changes of aircraft: {:?}", self.follow_freq_changes);
+        }
         
         let schedule = args.get_one::<String>("schedule").map(|x| x.clone()).unwrap_or(String::from(""));
         if !schedule.is_empty() {
@@ -190,25 +310,57 @@ impl XngModule for HfdlModule {
         }
         self.schedule = schedule;
         
-        self.next_session_band = args
-            .get_one::<String>("start-band-contains")
-            .unwrap_or(&String::from("default"))
-            .parse::<u16>()
-            .unwrap_or(0) as u64;
+        let start_band = match args.get_one::<String>("start-band-contains") {
+            Some(value) => Some(value.clone()),
+            None => match env::var(ENV_XNG_START_BAND) {
+                Ok(value) => {
+                    debug!("Found {} env var, using provided start band: {}", ENV_XNG_START_BAND, value);
+                    Some(value)
+                }
+                Err(_) => None,
+            },
+        };
+        self.next_session_band = match start_band.as_ref() {
+            Some(value) => match value.trim().parse::<u16>() {
+                Ok(x) => x as u64,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid start band, {}: {}", value, e.to_string()))),
+            },
+            None => 0,
+        };
+        if let Err(e) = validate_next_session_band(&json!(self.next_session_band)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid start band, {}: {}", self.next_session_band, e.to_string())));
+        }
+
+        self.warn_uncovered_freqs();
 
         let method = args.get_one::<String>("method").unwrap_or(&String::from(DEFAULT_SESSION_METHOD)).clone();
         if let Err(e) = validate_session_method(&json!(method)) {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid session method, {}: {}", method, e.to_string())));
         }
         self.method = method.to_lowercase();
+
+        let timestamp_source = args.get_one::<String>("timestamp-source").unwrap_or(&String::from(TIMESTAMP_SOURCE_MESSAGE)).to_lowercase();
+        self.use_message_time = match timestamp_source.as_str() {
+            TIMESTAMP_SOURCE_MESSAGE => true,
+            TIMESTAMP_SOURCE_RECEPTION => false,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid timestamp source {}, expected {} or {}", timestamp_source, TIMESTAMP_SOURCE_MESSAGE, TIMESTAMP_SOURCE_RECEPTION),
+                ))
+            }
+        };
         
         Ok(())
     }
 
     async fn init(&mut self, settings: Data<RwLock<ModuleSettings>>, state_db: Data<RwLock<StateDB>>) {
         self.settings = Some(settings.clone());
+        self.state_db = Some(state_db.clone());
         
         let mut settings = settings.write().await;
+        settings.status.decoder_version = self.decoder_version.clone();
+        settings.systable = Some(self.systable.as_json());
 
         settings.props.insert(
             PROP_STALE_TIMEOUT_SEC.to_string(),
@@ -245,7 +397,7 @@ impl XngModule for HfdlModule {
         {
             let state_db = state_db.write().await;
             for gs in self.systable.stations.iter() {
-                if let Err(e) = state_db.create_ground_station(gs.id as u32, &gs.name, gs.position.0, gs.position.1).await {
+                if let Err(e) = state_db.create_ground_station(gs.id as u32, &gs.name, gs.short_code(), gs.position.0, gs.position.1).await {
                     warn!("Failed to populate initial ground stations, id={} name={}: {}", gs.id, gs.name, e.to_string());
                 }
             }
@@ -259,13 +411,19 @@ impl XngModule for HfdlModule {
 
         let mut end_session_on_timeout = true;
         
+        // NOTE: pending changes were measured against the previous band
+        self.pending_freq_changes.clear();
+
+        self.wait_pending_enumeration().await;
+
         let mut extra_args = self.args.clone();
         let output_arg = format!(
             "decoded:json:tcp:address={},port={}",
             AIRFRAMESIO_HOST, AIRFRAMESIO_DUMPHFDL_TCP_PORT
         );
 
-        if self.feed_airframes {
+        // NOTE: with --feed-airframes-manual the user's own --output arguments are trusted as is
+        if self.feed_airframes && !self.feed_airframes_manual {
             if let Some(idx) = extra_args
                 .iter()
                 .position(|x| x.eq_ignore_ascii_case(&output_arg))
@@ -285,83 +443,32 @@ impl XngModule for HfdlModule {
         }
 
         let listening_bands: Vec<u16>;
+        let mut airframes_gs_names: Vec<(u32, String)> = Vec::new();
 
         let mut proc;
         {
             let mut settings = settings.write().await;
 
-            let use_airframes_gs;
-            {
-                let value = settings.props.get(&PROP_USE_AIRFRAMES_GS.to_string()).unwrap_or(&json!(false));
-                use_airframes_gs = value.as_bool().unwrap_or(false);
-            }
-
-            let only_use_active;
-            {
-                let value = settings.props.get(&PROP_ONLY_USE_ACTIVE.to_string()).unwrap_or(&json!(false));
-                only_use_active = value.as_bool().unwrap_or(false);
-            }
+            let use_airframes_gs = settings.get_bool(PROP_USE_AIRFRAMES_GS).unwrap_or(false);
+            let only_use_active = settings.get_bool(PROP_ONLY_USE_ACTIVE).unwrap_or(false);
             
-            let session_method;
-            {
-                let Some(value) = settings.props.get(&PROP_SESSION_METHOD.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_SESSION_METHOD prop")
-                    );
-                };
-                session_method = value.as_str().unwrap_or(DEFAULT_SESSION_METHOD).to_string();
-            }
+            let session_method = settings.get_str(PROP_SESSION_METHOD).unwrap_or(DEFAULT_SESSION_METHOD.to_string());
             if session_method == "static" {
                 end_session_on_timeout = false;
             }
             
-            let sample_rate;
-            {
-                let Some(suggested_sample_rate) = settings.props.get(&PROP_SAMPLE_RATE.to_string()) else {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_SAMPLE_RATE prop"));    
-                };
-                match self.nearest_sample_rate(suggested_sample_rate.as_u64().unwrap_or(0)) {
-                    Some(rate) => sample_rate = rate,
-                    None => {
-                        return Err(
-                            io::Error::new(
-                                io::ErrorKind::InvalidData, 
-                                format!("Failed to find nearest sample rate to {:?}", suggested_sample_rate)
-                            )
-                        );
-                    }
-                };
-            }
-            
-            let stale_timeout_sec;
-            {  
-                let Some(value) = settings.props.get(&PROP_STALE_TIMEOUT_SEC.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_STALE_TIMEOUT_SEC prop")  
-                    );
-                };
-                match value.as_u64() {
-                    Some(x) => stale_timeout_sec = x,
-                    None => {
-                        return Err(
-                            io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                "Stale timeout is not a number" 
-                            )
-                        );
-                    }
-                }
-            }
+            let suggested_sample_rate = settings.get_u64(PROP_SAMPLE_RATE)?;
+            let Some(sample_rate) = self.nearest_sample_rate(suggested_sample_rate) else {
+                return Err(
+                    io::Error::new(
+                        io::ErrorKind::InvalidData, 
+                        format!("Failed to find nearest sample rate to {:?}", suggested_sample_rate)
+                    )
+                );
+            };
             
-            let mut next_session_band;
-            {
-                let Some(value) = settings.props.get(&PROP_NEXT_SESSION_BAND.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_NEXT_SESSION_BAND prop")
-                    );    
-                };
-                next_session_band = value.as_u64().unwrap_or(0);
-            } 
+            let stale_timeout_sec = settings.get_u64(PROP_STALE_TIMEOUT_SEC)?;
+            let mut next_session_band = settings.get_u64(PROP_NEXT_SESSION_BAND).unwrap_or(0);
 
             // NOTE: always respect user requests to change frequency bands
             if next_session_band == 0 && matches!(last_end_reason, EndSessionReason::SessionUpdate) {
@@ -407,7 +514,7 @@ impl XngModule for HfdlModule {
 
             // NOTE: avoid doubly updating the stations since SessionUpdate would've just updated
             if use_airframes_gs && !matches!(last_end_reason, EndSessionReason::SessionUpdate) {
-                match get_airframes_gs_status().await {
+                match get_airframes_gs_status(&settings.http_client).await {
                     Ok(gs_status) => {
                         trace!("Populating stations with Airframes HFDL map data");
                         
@@ -437,7 +544,15 @@ impl XngModule for HfdlModule {
                                     warn!("Failed to send ground station change event: {}", e.to_string());                                    
                                 }
                             }
+
+                            self.airframes_gs_names.insert(station.id, station.name.clone());
                         }
+
+                        airframes_gs_names.extend(
+                            gs_status.ground_stations
+                                .iter()
+                                .map(|x| (x.id as u32, x.name.clone()))
+                        );
                             
                         all_freqs.extend(gs_status.all_freqs());
                     }
@@ -445,6 +560,19 @@ impl XngModule for HfdlModule {
                 }
             }
 
+            // NOTE: until the first SPDUs arrive, seed active-only sessions with the frequencies the state DB heard recently
+            if only_use_active && all_freqs.is_empty() {
+                if let Some(ref state_db) = self.state_db {
+                    match state_db.read().await.recent_active_frequencies(Duration::minutes(DEFAULT_SEED_ACTIVE_WINDOW_MINS)).await {
+                        Ok(freqs) => {
+                            debug!("Seeded active frequencies from state DB: {:?}", freqs);
+                            all_freqs.extend(freqs.iter().map(|&x| x as u16));
+                        }
+                        Err(e) => warn!("Failed to seed active frequencies from state DB: {}", e.to_string()),
+                    }
+                }
+            }
+
             if all_freqs.is_empty() {
                 all_freqs.extend_from_slice(&self.systable.all_freqs());
             }
@@ -456,64 +584,22 @@ impl XngModule for HfdlModule {
             debug!("Available Bands: {:?}", bands_for_rate);
             
             if next_session_band == 0 {
-                let mut candidates = bands_for_rate
-                    .iter()
-                    .map(|(_, v)| v.first())
-                    .filter(|x| x.is_some())
-                    .map(|x| x.unwrap())
-                    .collect::<Vec<&u16>>();
-                candidates.sort_unstable();
-                
-                let listening_band = settings.props.get(&PROP_LISTENING_BAND.to_string()).unwrap_or(&Value::Null);
-                let mut last_listening_freq: Option<u64> = None;
-                
-                if !candidates.is_empty() && listening_band.is_array() && session_method != "static" {
-                    let last_band = listening_band
-                        .as_array()
-                        .unwrap() 
-                        .iter()
-                        .map(|x| x.as_u64())
-                        .filter(|x| x.is_some())
-                        .map(|x| x.unwrap())
-                        .collect::<Vec<u64>>();
-                    if let Some(first_freq) = last_band.first() {
-                        last_listening_freq = Some(*first_freq as u64);
-
-                        if let Some(last_idx) = candidates.iter().position(|&x| *x as u64 >= *first_freq) {
-                            let max_idx = candidates.len() - 1;
-                            match session_method.as_str() {
-                                "inc" => if last_idx + 1 > max_idx {
-                                    next_session_band = *candidates[0] as u64;
-                                } else {
-                                    next_session_band = *candidates[last_idx + 1] as u64;
-                                }                              
-                                "dec" => if last_idx == 0 {
-                                    next_session_band = *candidates[max_idx] as u64;
-                                } else {
-                                    next_session_band = *candidates[last_idx - 1] as u64;
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-
+                let candidates = bands_for_rate
+                    .values()
+                    .filter_map(|x| x.first().copied())
+                    .collect::<Vec<u16>>();
+                let last_listening_freq = settings
+                    .get_u64_array(PROP_LISTENING_BAND)
+                    .unwrap_or_default()
+                    .first()
+                    .copied();
+
+                next_session_band = match self.rng {
+                    Some(ref mut rng) => select_session_band(&session_method, candidates, last_listening_freq, &mut self.last_random_freq_band, rng),
+                    None => select_session_band(&session_method, candidates, last_listening_freq, &mut self.last_random_freq_band, &mut rand::thread_rng()),
+                };
                 if next_session_band == 0 && session_method == "random" {
-                    let mut rng = rand::thread_rng();
-                    if let Some(first_freq) = last_listening_freq {
-                        candidates = candidates
-                            .into_iter()
-                            .filter(|&x| *x as u64 != first_freq && *x as u64 != self.last_random_freq_band)
-                            .collect();
-                        self.last_random_freq_band = first_freq;
-                    }
-
-                    if !candidates.is_empty() {
-                        let new_idx = rng.gen_range(0..(candidates.len() - 1));
-                        next_session_band = *candidates[new_idx] as u64;
-                    } else {
-                        warn!("Candidate bands pool is empty!");
-                    }
+                    warn!("Candidate bands pool is empty!");
                 }
             }
             
@@ -543,8 +629,8 @@ impl XngModule for HfdlModule {
             
             self.last_req_session_band = next_session_band;
             
-            proc = match process::Command::new(self.bin.clone())
-                .stdout(Stdio::piped())
+            let mut cmd = process::Command::new(self.bin.clone());
+            cmd.stdout(Stdio::piped())
                 .stderr(Stdio::piped())
                 .arg("--system-table")
                 .arg(self.systable.path.to_path_buf())
@@ -553,9 +639,13 @@ impl XngModule for HfdlModule {
                 .arg("--output")
                 .arg("decoded:json:file:path=-")
                 .args(extra_args)
-                .args(bands.iter().map(|x| x.to_string()).collect::<Vec<String>>())
-                .spawn()
-            {
+                .args(bands.iter().map(|x| x.to_string()).collect::<Vec<String>>());
+
+            if self.dry_run {
+                return Ok(Box::new(DryRunSession::from_command(&cmd, bands.iter().map(|&x| x as u64).collect())));
+            }
+
+            proc = match cmd.spawn() {
                 Ok(v) => v,
                 Err(e) => {
                     return Err(io::Error::new(
@@ -565,28 +655,23 @@ impl XngModule for HfdlModule {
                 }
             };
 
-            {
-                let Some(value) = settings.props.get_mut(&PROP_LISTENING_BAND.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_LISTENING_BAND prop")
-                    );    
-                };
-                *value = json!(bands);
-            }
-
+            settings.set(PROP_LISTENING_BAND, json!(bands))?;
             listening_bands = bands.clone();
-            
-            {
-                let Some(value) = settings.props.get_mut(&PROP_NEXT_SESSION_BAND.to_string()) else {
-                    return Err(
-                        io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_NEXT_SESSION_BAND prop")
-                    );    
-                };
-                *value = json!(0);
-            }
+            settings.set(PROP_NEXT_SESSION_BAND, json!(0))?;
 
             debug!("New HFDL session started, requested freq {}, listening: {:?}", next_session_band, bands);            
         }
+
+        // NOTE: stations missing from the local systable would otherwise stay nameless in the state DB. This runs
+        //       after the settings lock is released since the frame processor takes the state DB lock first
+        if let Some(ref state_db) = self.state_db {
+            let state_db = state_db.read().await;
+            for (id, name) in airframes_gs_names.iter() {
+                if let Err(e) = state_db.update_ground_station_name(*id, name).await {
+                    warn!("Failed to update ground station name, id={} name={}: {}", id, name, e.to_string());
+                }
+            }
+        }
         
         let Some(stdout) = proc.stdout.take() else {
             return Err(io::Error::new(io::ErrorKind::Other, "Unable to take stdout from child process"));
@@ -602,9 +687,22 @@ impl XngModule for HfdlModule {
             listening_bands,
             next_session_begin,
             end_session_on_timeout,
+            if self.sdr_stats { self.settings.clone() } else { None },
         )))
     }
 
+    fn example_invocation(&self) -> Option<String> {
+        Some(
+            include_str!("example.txt")
+                .replace("{command}", HFDL_COMMAND)
+                .replace("{bin}", DEFAULT_BIN_PATH)
+                .replace(
+                    "{feed_output}",
+                    format!("decoded:json:tcp:address={},port={}", AIRFRAMESIO_HOST, AIRFRAMESIO_DUMPHFDL_TCP_PORT).as_str(),
+                ),
+        )
+    }
+
     async fn process_message(&mut self, current_band: &Vec<u64>, msg: &str) -> Result<crate::common::frame::CommonFrame, io::Error> {
         let raw_frame = serde_json::from_str::<Frame>(msg)?;
         let mut frame_src: cff::Entity;
@@ -644,54 +742,21 @@ impl XngModule for HfdlModule {
                 let settings = self.get_settings()?;
                 let mut settings = settings.write().await;
 
-                let use_airframes_gs;
-                {
-                    let value = settings.props.get(&PROP_USE_AIRFRAMES_GS.to_string()).unwrap_or(&json!(false));
-                    use_airframes_gs = value.as_bool().unwrap_or(false);
-                }
+                let use_airframes_gs = settings.get_bool(PROP_USE_AIRFRAMES_GS).unwrap_or(false);
+                let only_use_active = settings.get_bool(PROP_ONLY_USE_ACTIVE).unwrap_or(false);
+                let sample_rate = settings.get_u64(PROP_SAMPLE_RATE).unwrap_or(0);
+                let stale_timeout_sec = settings.get_u64(PROP_STALE_TIMEOUT_SEC)?;
 
-                let only_use_active;
-                {
-                    let value = settings.props.get(&PROP_ONLY_USE_ACTIVE.to_string()).unwrap_or(&json!(false));
-                    only_use_active = value.as_bool().unwrap_or(false);
-                }
-
-                let sample_rate;
-                {
-                    let value = settings.props.get(&PROP_SAMPLE_RATE.to_string()).unwrap_or(&json!(false));
-                    sample_rate = value.as_u64().unwrap_or(0);    
-                }
-                
-                let stale_timeout_sec;
-                {  
-                    let Some(value) = settings.props.get(&PROP_STALE_TIMEOUT_SEC.to_string()) else {
-                        return Err(
-                            io::Error::new(io::ErrorKind::InvalidData, "Missing PROP_STALE_TIMEOUT_SEC prop")  
-                        );
-                    };
-                    match value.as_u64() {
-                        Some(x) => stale_timeout_sec = x,
-                        None => {
-                            return Err(
-                                io::Error::new(
-                                    io::ErrorKind::InvalidData,
-                                    "Stale timeout is not a number" 
-                                )
-                            );
-                        }
-                    }
-                }
-
-                let mut changed = false;
-                
                 for station in spdu.gs_status.iter() {
                     let freq_set: Vec<u64> = station.freqs.iter().map(|x| x.freq as u64).collect();
+                    let mut changed = false;
+                    let mut previous_set: Vec<u64> = Vec::new();
                     if let Some(change_event) = update_station_by_frequencies(
                         settings.deref_mut(),
                         Some(arrival_time.to_rfc3339_opts(SecondsFormat::Micros, true)),
                         stale_timeout_sec as i64,
                         json!(station.gs.id), 
-                        station.gs.name.clone(), 
+                        self.resolve_gs_name(station.gs.id, &station.gs.name), 
                         &freq_set
                     ) {
                         debug!(
@@ -708,6 +773,7 @@ impl XngModule for HfdlModule {
 
                         match (serde_json::from_str::<Vec<u16>>(change_event.old.as_str()), serde_json::from_str::<Vec<u16>>(change_event.new.as_str())) {
                             (Ok(old_band), Ok(new_band)) => {
+                                previous_set = old_band.iter().map(|&x| x as u64).collect();
                                 let old_band_set: HashSet<&u16> = HashSet::from_iter(old_band.iter());
                                 let new_band_set: HashSet<&u16> = HashSet::from_iter(new_band.iter());
 
@@ -747,14 +813,46 @@ impl XngModule for HfdlModule {
                             }
                         }
                     }
+
+                    // NOTE: a change only counts once later SPDUs keep reporting the same frequency set for the station
+                    if changed {
+                        if let Some(pending) = self.pending_freq_changes.get(&station.gs.id) {
+                            previous_set = pending.2.clone();
+                        }
+
+                        // NOTE: a station flipping back to the set it had before the pending change needs no reload
+                        let flipped_back = !previous_set.is_empty() && HashSet::<&u64>::from_iter(previous_set.iter()) == HashSet::from_iter(freq_set.iter());
+                        if flipped_back {
+                            self.pending_freq_changes.remove(&station.gs.id);
+                        } else {
+                            self.pending_freq_changes.insert(station.gs.id, (freq_set, 1, previous_set));
+                        }
+                    } else {
+                        match self.pending_freq_changes.get(&station.gs.id).map(|x| x.0 == freq_set) {
+                            Some(true) => {
+                                if let Some(pending) = self.pending_freq_changes.get_mut(&station.gs.id) {
+                                    pending.1 += 1;
+                                }
+                            }
+                            Some(false) => {
+                                self.pending_freq_changes.remove(&station.gs.id);
+                            }
+                            None => {}
+                        }
+                    }
                 }
 
-                if (only_use_active || use_airframes_gs) && changed {
+                if !(only_use_active || use_airframes_gs) {
+                    self.pending_freq_changes.clear();
+                } else if self.pending_freq_changes.values().any(|x| x.1 >= self.freq_change_debounce_count) {
                     if let Err(e) = settings.end_session_signaler.send(EndSessionReason::SessionUpdate) {
                         warn!("Failed to signal end session after : {}", e.to_string());
                     } else {
                         debug!("Latest SPDU changed frequencies, reloading session to make sure only active frequencies are listened to");
                     }
+                    self.pending_freq_changes.clear();
+                } else if !self.pending_freq_changes.is_empty() {
+                    trace!("Pending frequency changes not yet persisted for {} SPDUs: {:?}", self.freq_change_debounce_count, self.pending_freq_changes);
                 }
             }
             
@@ -763,10 +861,13 @@ impl XngModule for HfdlModule {
                 heard_on: spdu.gs_status.iter().map(|x| cff::HFDLGSEntry {
                     kind: x.gs.entity_type.clone(),
                     id: x.gs.id,
-                    gs: x.gs.name.clone().unwrap_or(String::from("")),
+                    gs: self.resolve_gs_name(x.gs.id, &x.gs.name).unwrap_or(String::from("")),
                     freqs: x.freqs.iter().map(|y| y.freq as f64 / 1000.0).collect(),
                 }).collect(),
                 reason: None,  
+                request_data: None,
+                bit_rate: Some(raw_frame.hfdl.bit_rate),
+                slot: Some(raw_frame.hfdl.slot_type()),
             });
         } else if let Some(ref lpdu) = raw_frame.hfdl.lpdu {
             frame_src = lpdu.src.to_common_frame_entity(&self.systable);
@@ -783,7 +884,9 @@ impl XngModule for HfdlModule {
             if let Some(ref hfnpdu) = lpdu.hfnpdu {
                 if let Some(ref flight_id) = hfnpdu.flight_id {
                     frame_src.callsign = Some(flight_id.trim().to_string());
+                    indexed.flight_id = Some(flight_id.trim().to_string());
                 }
+                indexed.flight_leg_num = hfnpdu.flight_leg_num;
 
                 if let Some(ref pos) = hfnpdu.pos {
                     let pt = pos.as_wkt();
@@ -802,7 +905,8 @@ impl XngModule for HfdlModule {
                     }
                 }
 
-                if let Some(ref msg_time) = hfnpdu.time {
+                // NOTE: frames without an embedded message time keep the reception time either way
+                if let Some(msg_time) = hfnpdu.time.as_ref().filter(|_| self.use_message_time) {
                     let Some(frame_time) = nearest_time_in_past(&arrival_time, msg_time.hour, msg_time.min, msg_time.sec) else {
                         return Err(io::Error::new(
                             io::ErrorKind::InvalidData,
@@ -852,7 +956,7 @@ impl XngModule for HfdlModule {
                     heard_on = freq_data.iter().map(|x| cff::HFDLGSEntry {
                         kind: x.gs.entity_type.clone(),
                         id: x.gs.id,
-                        gs: x.gs.name.clone().unwrap_or(String::from("")),
+                        gs: self.resolve_gs_name(x.gs.id, &x.gs.name).unwrap_or(String::from("")),
                         freqs: x.heard_on_freqs.iter().map(|y| y.freq as f64 / 1000.0).collect(),
                     }).collect();
 
@@ -882,10 +986,27 @@ impl XngModule for HfdlModule {
                     }
                 }
                 
+                if let (false, Some(_), Some(ref freq_data), Some(ref icao)) = (
+                    lpdu.from_ground_station(),
+                    &hfnpdu.last_freq_change_cause,
+                    &hfnpdu.freq_data,
+                    &frame_src.icao,
+                ) {
+                    if self.follow_freq_changes.contains(&icao.to_uppercase()) {
+                        self.follow_freq_change(current_band, icao, freq_data).await?;
+                    }
+                }
+
                 metadata = Some(cff::HFDLMetadata {
                     kind: hfnpdu.kind.name.clone(),
                     heard_on,
                     reason, 
+                    request_data: hfnpdu.request_data.map(|x| cff::HFDLRequestData {
+                        code: x,
+                        bits: request_data_bits(x),
+                    }),
+                    bit_rate: Some(raw_frame.hfdl.bit_rate),
+                    slot: Some(raw_frame.hfdl.slot_type()),
                 });
             } else {
                 if let Some(ref ac_id) = lpdu.assigned_ac_id {
@@ -901,7 +1022,14 @@ impl XngModule for HfdlModule {
                 if let Some(ref r) = lpdu.reason {
                     reason = Some(r.descr.clone());
                 }
-                metadata = Some(cff::HFDLMetadata { kind: lpdu.kind.name.clone(), heard_on: vec![], reason});
+                metadata = Some(cff::HFDLMetadata {
+                    kind: lpdu.kind.name.clone(),
+                    heard_on: vec![],
+                    reason,
+                    request_data: None,
+                    bit_rate: Some(raw_frame.hfdl.bit_rate),
+                    slot: Some(raw_frame.hfdl.slot_type()),
+                });
             }
         } else {
             return Err(io::Error::new(
@@ -911,6 +1039,8 @@ impl XngModule for HfdlModule {
         }
 
         Ok(cff::CommonFrame {
+            schema_version: cff::CFF_SCHEMA_VERSION,
+
             timestamp: unix_time_to_utc_datetime(
                 raw_frame.hfdl.ts.to_f64()
             ).unwrap_or(Utc::now().with_timezone(&UTC)).to_rfc3339_opts(SecondsFormat::Nanos, true),
@@ -919,7 +1049,7 @@ impl XngModule for HfdlModule {
 
             err: has_err,
 
-            paths,
+            paths: dedup_paths_by_party(paths),
 
             app: cff::AppInfo {
                 name: raw_frame.hfdl.app.name,
@@ -928,12 +1058,15 @@ impl XngModule for HfdlModule {
 
             indexed,
             metadata: cff::Metadata {
-                hfdl: metadata,  
+                hfdl: metadata,
+                vdl2: None,
             },
             
             src: frame_src,
             dst: frame_dst,
             acars: acars_content,
+            from_ground_station: None,
+            raw: None,
         })
     }
 
@@ -942,16 +1075,8 @@ impl XngModule for HfdlModule {
         {
             let settings = settings.read().await;
 
-            let Some(schedule) = settings.props.get(PROP_SESSION_SCHEDULE) else {
-                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Session schedule prop value does not exist")))
-            };
-
-            if let Some(schedule) = schedule.as_str() {
-                // NOTE: schedule is already validated on being set
-                self.schedule = schedule.to_string();
-            } else {
-                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Session schedule prop value is not a string")))
-            }
+            // NOTE: schedule is already validated on being set
+            self.schedule = settings.get_str(PROP_SESSION_SCHEDULE)?;
         }
 
         Ok(())
@@ -1,11 +1,19 @@
 use actix_web::web::Data;
 use log::*;
+use serde_json::json;
 use soapysdr::Device;
 use std::env;
+use std::time::Duration;
 use tokio::io;
 use tokio::sync::RwLock;
+use tokio::task::spawn_blocking;
+use tokio::time::timeout as tokio_timeout;
 
-use super::{HfdlModule, HFDL_COMMAND};
+use super::frame::FreqData;
+use super::schedule::parse_session_schedule;
+use super::utils::{first_freq_above_eq, uncovered_freqs};
+use super::{HfdlModule, HFDL_COMMAND, PROP_NEXT_SESSION_BAND};
+use crate::modules::session::EndSessionReason;
 use crate::modules::{settings::ModuleSettings, XngModule};
 
 const ENV_XNG_TEST_RATES: &'static str = "XNG_TEST_SAMPLERATES";
@@ -29,7 +37,42 @@ impl HfdlModule {
         Ok(settings.clone())
     }
 
-    pub fn load_sample_rates(&mut self, driver: &String) -> Result<(), io::Error> {
+    // NOTE: the aircraft's listening frequencies are its new assignment, nothing is done while any of them is still in the current band
+    pub async fn follow_freq_change(&self, current_band: &Vec<u64>, icao: &String, freq_data: &Vec<FreqData>) -> Result<(), io::Error> {
+        let listening_freqs: Vec<u64> = freq_data
+            .iter()
+            .flat_map(|x| x.listening_on_freqs.iter().map(|y| y.freq as u64))
+            .collect();
+        let (Some(first_freq), Some(last_freq)) = (current_band.first(), current_band.last()) else {
+            return Ok(());
+        };
+        if listening_freqs.iter().any(|x| x >= first_freq && x <= last_freq) {
+            return Ok(());
+        }
+        let Some(target_freq) = listening_freqs.first() else {
+            return Ok(());
+        };
+
+        let settings = self.get_settings()?;
+        let mut settings = settings.write().await;
+        if let Err(e) = settings.set(PROP_NEXT_SESSION_BAND, json!(target_freq)) {
+            warn!("Unable to follow {} to {} kHz: {}", icao, target_freq, e.to_string());
+            return Ok(());
+        }
+
+        if let Err(e) = settings.end_session_signaler.send(EndSessionReason::SessionUpdate) {
+            warn!("Failed to signal end session to follow {}: {}", icao, e.to_string());
+        } else {
+            info!("Aircraft {} changed frequency, retuning to band containing {} kHz", icao, target_freq);
+        }
+
+        Ok(())
+    }
+
+    // NOTE: some drivers block for seconds while enumerating, so it runs on the blocking pool and falls back
+    //       to the user's --sample-rates on timeout. A timed out enumeration is kept so the first session
+    //       waits for it to release the device before the decoder opens it
+    pub async fn load_sample_rates(&mut self, driver: &String, timeout: Option<Duration>, fallback_rates: &Vec<u64>) -> Result<(), io::Error> {
         if let Ok(value) = env::var(ENV_XNG_TEST_RATES) {
             let mut test_rates: Vec<u64> = value
                 .split(",")
@@ -45,90 +88,115 @@ impl HfdlModule {
             );
             self.sample_rates = test_rates;
 
-            Ok(())
-        } else {
-            let dev = match Device::new(driver.as_str()) {
-                Ok(x) => x,
-                Err(e) => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::NotFound,
-                        format!(
-                            "Failed to open SoapySDR device {} ({}): is it plugged in?",
-                            driver,
-                            e.to_string()
-                        ),
-                    ))
-                }
-            };
+            return Ok(());
+        }
+
+        let thread_driver = driver.clone();
+        let mut task = spawn_blocking(move || enumerate_sample_rates(&thread_driver));
 
-            let chan_count = match dev.num_channels(soapysdr::Direction::Rx) {
+        let result = match timeout {
+            Some(timeout) => match tokio_timeout(timeout, &mut task).await {
                 Ok(x) => x,
-                Err(e) => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        format!(
-                            "Failed to enumerate channels for Rx for SoapySDR device {}: {}",
-                            driver,
-                            e.to_string()
-                        ),
-                    ))
+                Err(_) => {
+                    self.pending_enumeration = Some(task);
+
+                    if fallback_rates.is_empty() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            format!(
+                                "SoapySDR device {} did not enumerate sample rates within {} seconds, pass --sample-rates to fall back on",
+                                driver,
+                                timeout.as_secs()
+                            ),
+                        ));
+                    }
+
+                    warn!(
+                        "SoapySDR device {} did not enumerate sample rates within {} seconds, using fallback sample rates: {:?}",
+                        driver,
+                        timeout.as_secs(),
+                        fallback_rates
+                    );
+                    self.sample_rates = fallback_rates.clone();
+                    return Ok(());
                 }
-            };
-            if chan_count == 0 {
-                return Err(io::Error::new(
-                    io::ErrorKind::Unsupported,
-                    format!("Unsupported SoapySDR device {} with no channels", driver),
-                ));
-            } else if chan_count > 1 {
-                warn!(
-                    "SoapySDR device {} has more than one receive channel!",
-                    driver
-                );
+            },
+            None => task.await,
+        };
+        match result {
+            Ok(Ok(sample_rates)) => {
+                info!("Using sample rates enumerated from SoapySDR device {}: {:?}", driver, sample_rates);
+                self.sample_rates = sample_rates;
+                Ok(())
             }
+            Ok(Err(e)) => Err(e),
+            Err(e) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("SoapySDR device {} enumeration task failed: {}", driver, e.to_string()),
+            )),
+        }
+    }
 
-            // TODO: support more channels but for now, only grab data for the first one
-            let mut sample_rates = match dev.get_sample_rate_range(soapysdr::Direction::Rx, 0) {
-                Ok(x) => x
-                    .iter()
-                    .map(|x| {
-                        if x.minimum == x.maximum {
-                            x.maximum as u64
-                        } else {
-                            0 as u64
-                        }
-                    })
-                    .collect::<Vec<u64>>(),
-                Err(e) => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!(
-                            "Failed to get sample rates for SoapySDR device {} on channel 0: {}",
-                            driver,
-                            e.to_string()
-                        ),
-                    ))
-                }
-            };
+    pub async fn wait_pending_enumeration(&mut self) {
+        let Some(task) = self.pending_enumeration.take() else {
+            return;
+        };
 
-            if sample_rates.iter().any(|&x| x == 0) {
-                warn!(
-                    "SoapySDR device {} has different minimum/maximum sample rate range entry!",
-                    driver
-                );
+        if !task.is_finished() {
+            info!("Waiting for SoapySDR device {} to finish enumerating before starting the decoder...", self.driver);
+        }
+        let _ = task.await;
+    }
 
-                sample_rates = sample_rates.into_iter().filter(|&x| x > 0).collect();
-            }
+    pub fn resolve_gs_name(&self, id: u8, name: &Option<String>) -> Option<String> {
+        match name {
+            Some(x) if !x.is_empty() => Some(x.clone()),
+            _ => self
+                .systable
+                .by_id(id)
+                .map(|x| x.name.clone())
+                .or_else(|| self.airframes_gs_names.get(&id).cloned()),
+        }
+    }
 
-            sample_rates.sort_unstable();
-            sample_rates.dedup();
+    pub fn warn_uncovered_freqs(&self) {
+        let mut configured: Vec<u16> = Vec::new();
+        if self.next_session_band > 0 {
+            configured.push(self.next_session_band as u16);
+        }
+        if !self.schedule.is_empty() {
+            if let Ok(schedule) = parse_session_schedule(&self.schedule) {
+                configured.extend(schedule.iter().map(|(_, freq)| *freq as u16));
+            }
+        }
+        configured.sort_unstable();
+        configured.dedup();
 
-            self.sample_rates = sample_rates;
+        let mut all_freqs = self.systable.all_freqs();
+        all_freqs.sort_unstable();
+        all_freqs.dedup();
 
-            Ok(())
+        let sample_rate = self.nearest_sample_rate(self.sample_rate).unwrap_or(self.sample_rate);
+        for freq in uncovered_freqs(&configured, &all_freqs, sample_rate as u32) {
+            match first_freq_above_eq(&all_freqs, freq) {
+                Some(target) => warn!(
+                    "{} kHz is not covered by any band at a sample rate of {}, sessions will listen on the band containing {} kHz instead",
+                    freq, sample_rate, target
+                ),
+                None => warn!(
+                    "{} kHz is above every system table frequency and will never be covered at a sample rate of {}",
+                    freq, sample_rate
+                ),
+            }
         }
     }
 
     pub fn nearest_sample_rate(&self, sample_rate: u64) -> Option<u64> {
+        // NOTE: without SoapySDR there is nothing to snap to, the user's rate has to match their source
+        if self.no_soapy {
+            return Some(sample_rate).filter(|&x| x > 0);
+        }
+
         if let Some(idx) = self.sample_rates.iter().position(|&x| x >= sample_rate) {
             Some(self.sample_rates[idx])
         } else {
@@ -137,6 +205,10 @@ impl HfdlModule {
     }
 
     pub fn calculate_actual_sample_rate(&self, bands: &Vec<u16>) -> Option<u64> {
+        if self.no_soapy {
+            return None;
+        }
+
         let mut bands = bands.clone();
         bands.sort_unstable();
 
@@ -148,3 +220,82 @@ impl HfdlModule {
         }
     }
 }
+
+fn enumerate_sample_rates(driver: &String) -> Result<Vec<u64>, io::Error> {
+    let dev = match Device::new(driver.as_str()) {
+        Ok(x) => x,
+        Err(e) => {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Failed to open SoapySDR device {} ({}): is it plugged in?",
+                    driver,
+                    e.to_string()
+                ),
+            ))
+        }
+    };
+
+    let chan_count = match dev.num_channels(soapysdr::Direction::Rx) {
+        Ok(x) => x,
+        Err(e) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Failed to enumerate channels for Rx for SoapySDR device {}: {}",
+                    driver,
+                    e.to_string()
+                ),
+            ))
+        }
+    };
+    if chan_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Unsupported SoapySDR device {} with no channels", driver),
+        ));
+    } else if chan_count > 1 {
+        warn!(
+            "SoapySDR device {} has more than one receive channel!",
+            driver
+        );
+    }
+
+    // TODO: support more channels but for now, only grab data for the first one
+    let mut sample_rates = match dev.get_sample_rate_range(soapysdr::Direction::Rx, 0) {
+        Ok(x) => x
+            .iter()
+            .map(|x| {
+                if x.minimum == x.maximum {
+                    x.maximum as u64
+                } else {
+                    0 as u64
+                }
+            })
+            .collect::<Vec<u64>>(),
+        Err(e) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "Failed to get sample rates for SoapySDR device {} on channel 0: {}",
+                    driver,
+                    e.to_string()
+                ),
+            ))
+        }
+    };
+
+    if sample_rates.iter().any(|&x| x == 0) {
+        warn!(
+            "SoapySDR device {} has different minimum/maximum sample rate range entry!",
+            driver
+        );
+
+        sample_rates = sample_rates.into_iter().filter(|&x| x > 0).collect();
+    }
+
+    sample_rates.sort_unstable();
+    sample_rates.dedup();
+
+    Ok(sample_rates)
+}
@@ -1,18 +1,105 @@
+use actix_web::web::Data;
 use async_trait::async_trait;
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, Utc};
 use log::*;
+use std::collections::VecDeque;
+use std::process::ExitStatus;
 use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::process::{Child, ChildStderr, ChildStdout};
 use tokio::select;
-use tokio::time::{sleep_until, Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, timeout, Duration, Instant};
 
-use crate::modules::session::{EndSessionReason, Session, SESSION_SCHEDULED_END};
+use crate::modules::session::{
+    EndSessionReason, Session, DEFAULT_EXIT_STATUS_WAIT_SECS, SESSION_SCHEDULED_END,
+};
+use crate::modules::settings::ModuleSettings;
+use crate::modules::status::SdrStats;
+
+const MAX_STDERR_LINES: usize = 100;
+
+// NOTE: recognizes key=value tokens such as "sample_rate=912000 gain=38.6 overloads=2", returns None if no known key is present.
+//       Stock dumphfdl does not print these, they are expected from a wrapper or driver build that does
+pub fn parse_stats_line(line: &str) -> Option<SdrStats> {
+    let mut stats = SdrStats {
+        updated: Utc::now(),
+        ..Default::default()
+    };
+    let mut found = false;
+
+    for token in line.split(|c: char| c.is_whitespace() || c == ',') {
+        let Some((key, value)) = token.split_once('=') else {
+            continue;
+        };
+
+        match key.trim().to_lowercase().replace('-', "_").as_str() {
+            "sample_rate" | "samplerate" => stats.sample_rate = value.trim().parse::<u64>().ok(),
+            "gain" => stats.gain = value.trim().trim_end_matches("dB").parse::<f64>().ok(),
+            "overloads" | "overload" => stats.overloads = value.trim().parse::<u64>().ok(),
+            _ => continue,
+        }
+        found = true;
+    }
+
+    if found {
+        Some(stats)
+    } else {
+        None
+    }
+}
+
+// NOTE: stats lines update the module status as they arrive, only the last MAX_STDERR_LINES of everything
+//       else are kept for get_errors so a chatty decoder can't grow the buffer for the whole session
+fn scan_stderr(stderr: ChildStderr, settings: Data<RwLock<ModuleSettings>>) -> JoinHandle<String> {
+    tokio::spawn(async move {
+        // NOTE: stats from the previous session describe a different band and possibly gain
+        settings.write().await.status.sdr_stats = None;
+
+        let mut reader = BufReader::new(stderr);
+        let mut errors: VecDeque<String> = VecDeque::with_capacity(MAX_STDERR_LINES);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            match reader.read_line(&mut line).await {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    push_error_line(&mut errors, format!("Failed to read STDERR: {}\n", e.to_string()));
+                    break;
+                }
+            }
+
+            match parse_stats_line(&line) {
+                Some(stats) => {
+                    let mut settings = settings.write().await;
+                    match settings.status.sdr_stats {
+                        Some(ref mut x) => x.merge(stats),
+                        None => settings.status.sdr_stats = Some(stats),
+                    }
+                }
+                None => push_error_line(&mut errors, line.clone()),
+            }
+        }
+
+        errors.into_iter().collect()
+    })
+}
+
+fn push_error_line(errors: &mut VecDeque<String>, line: String) {
+    if errors.len() >= MAX_STDERR_LINES {
+        errors.pop_front();
+    }
+    errors.push_back(line);
+}
 
 pub struct DumpHFDLSession {
     process: Child,
 
     reader: BufReader<ChildStdout>,
-    stderr: ChildStderr,
+    stderr: Option<ChildStderr>,
+    stderr_scanner: Option<JoinHandle<String>>,
 
     bands: Vec<u64>,
 
@@ -43,8 +130,20 @@ impl Session for DumpHFDLSession {
     }
 
     async fn get_errors(&mut self) -> String {
+        if let Some(scanner) = self.stderr_scanner.take() {
+            return match timeout(Duration::from_secs(DEFAULT_EXIT_STATUS_WAIT_SECS), scanner).await {
+                Ok(Ok(errors)) => errors,
+                Ok(Err(e)) => format!("STDERR scanner failed: {}", e.to_string()),
+                Err(_) => String::from("Timed out waiting for STDERR to close"),
+            };
+        }
+
+        let Some(ref mut stderr) = self.stderr else {
+            return String::new();
+        };
+
         let mut errors = String::new();
-        if let Err(e) = self.stderr.read_to_string(&mut errors).await {
+        if let Err(e) = stderr.read_to_string(&mut errors).await {
             return format!("Failed to read STDERR: {}", e.to_string());
         }
 
@@ -55,6 +154,22 @@ impl Session for DumpHFDLSession {
         &self.bands
     }
 
+    async fn exit_status(&mut self) -> Option<ExitStatus> {
+        match timeout(
+            Duration::from_secs(DEFAULT_EXIT_STATUS_WAIT_SECS),
+            self.process.wait(),
+        )
+        .await
+        {
+            Ok(Ok(status)) => Some(status),
+            Ok(Err(e)) => {
+                warn!("Failed to get dumphfdl exit status: {}", e.to_string());
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
     async fn end(&mut self, reason: EndSessionReason) {
         debug!("Terminating launched dumphfdl process...");
 
@@ -62,6 +177,9 @@ impl Session for DumpHFDLSession {
         {
             self.process.kill().await;
         }
+        if let Some(ref scanner) = self.stderr_scanner {
+            scanner.abort();
+        }
 
         debug!("HFDL session terminated: reason={:?}", reason);
     }
@@ -75,6 +193,7 @@ impl DumpHFDLSession {
         bands: Vec<u16>,
         session_end_datetime: Option<DateTime<Local>>,
         end_session_on_timeout: bool,
+        sdr_stats_settings: Option<Data<RwLock<ModuleSettings>>>,
     ) -> DumpHFDLSession {
         let mut session_end: Option<Duration> = None;
         if let Some(dt) = session_end_datetime {
@@ -90,10 +209,16 @@ impl DumpHFDLSession {
             }
         }
 
+        let (stderr, stderr_scanner) = match sdr_stats_settings {
+            Some(settings) => (None, Some(scan_stderr(stderr, settings))),
+            None => (Some(stderr), None),
+        };
+
         DumpHFDLSession {
             process,
             reader,
             stderr,
+            stderr_scanner,
             bands: bands.into_iter().map(|x| x as u64).collect(),
             end_session_on_timeout,
             session_start: Instant::now(),
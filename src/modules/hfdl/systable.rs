@@ -1,11 +1,14 @@
 use lazy_static::lazy_static;
 use log::*;
-use regex::Regex;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::path::PathBuf;
-use std::str::from_utf8;
+use tempfile::NamedTempFile;
+
+const UNKNOWN_SHORT_NAME: &'static str = "???";
+const MAX_CONFIG_NESTING: usize = 32;
 
 #[derive(Debug)]
 pub struct GroundStation {
@@ -23,7 +26,7 @@ impl GroundStation {
         lat: f64,
         lon: f64,
         frequencies: Vec<f64>,
-    ) -> Option<GroundStation> {
+    ) -> Result<GroundStation, String> {
         lazy_static! {
             static ref SHORT_NAMES: HashMap<u8, &'static str> = HashMap::from([
                 (1, "SFO"),
@@ -46,43 +49,41 @@ impl GroundStation {
         }
 
         if id == 0 {
-            debug!("Ground station ID is not valid");
-            return None;
+            return Err(String::from("id field should not be 0"));
         }
 
         if name.len() == 0 {
-            debug!("Ground station name is not valid");
-            return None;
+            return Err(format!("name field of station {} should not be empty", id));
         }
 
         if lat >= 90.0 || lat <= -90.0 {
-            debug!("Ground station latitude is not valid: {}", lat);
-            return None;
+            return Err(format!("lat field of station {} is out of range: {}", id, lat));
         }
 
         if lon >= 180.0 || lon <= -180.0 {
-            debug!("Ground station longitude is not valid: {}", lon);
-            return None;
+            return Err(format!("lon field of station {} is out of range: {}", id, lon));
         }
 
         if frequencies.is_empty() {
-            debug!("Ground station frequencies are not valid");
-            return None;
+            return Err(format!("frequencies field of station {} should not be empty", id));
         }
 
-        if frequencies.iter().any(|&x| x == 0.0) {
-            debug!("Ground station frequencies contain invalid data");
-            return None;
+        if let Some(freq) = frequencies.iter().find(|&&x| x <= 0.0 || x > u16::MAX as f64) {
+            return Err(format!("frequencies field of station {} contains an invalid kHz frequency: {}", id, freq));
         }
 
-        Some(GroundStation {
+        Ok(GroundStation {
             id,
             name: name.to_string(),
-            short: SHORT_NAMES.get(&id).unwrap_or(&"???").to_string(),
+            short: SHORT_NAMES.get(&id).unwrap_or(&UNKNOWN_SHORT_NAME).to_string(),
             position: (lat, lon),
             frequencies: frequencies.into_iter().map(|x| x as u16).collect(),
         })
     }
+
+    pub fn short_code(&self) -> Option<&str> {
+        Some(self.short.as_str()).filter(|&x| x != UNKNOWN_SHORT_NAME)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -91,6 +92,9 @@ pub struct SystemTable {
 
     pub version: u8,
     pub stations: Vec<GroundStation>,
+
+    // NOTE: the temporary file written by write_merged that path points at, removed when dropped
+    merged: Option<NamedTempFile>,
 }
 
 impl SystemTable {
@@ -108,6 +112,19 @@ impl SystemTable {
             .find(|x| x.name.eq_ignore_ascii_case(name))
     }
 
+    pub fn as_json(&self) -> Value {
+        json!({
+            "version": self.version,
+            "stations": self.stations.iter().map(|x| json!({
+                "id": x.id,
+                "name": x.name,
+                "short": x.short,
+                "coords": (x.position.1, x.position.0),
+                "frequencies": x.frequencies,
+            })).collect::<Vec<Value>>(),
+        })
+    }
+
     pub fn all_freqs(&self) -> Vec<u16> {
         self.stations
             .iter()
@@ -115,35 +132,77 @@ impl SystemTable {
             .collect()
     }
 
-    pub fn load(path: &PathBuf) -> io::Result<Self> {
-        lazy_static! {
-            static ref NEWLINES_FMT: Regex = Regex::new(r"[\r\n]").unwrap();
-            static ref SYSTABLE_FMT: Regex = Regex::new(
-                r"(?x)
-                \s*version\s*=\s*([0-9]+)\s*;
-                \s*stations\s*=\s*\(
-                    (.+)
-                \)\s*;
-                "
-            )
-            .unwrap();
-            static ref STATIONS_FMT: Regex = Regex::new(
-                r#"(?x)
-                \{
-                    \s*(.+?)\s*
-                    \s*
-                \}
-                "#
-            )
-            .unwrap();
-            static ref STATION_FIELD_FMT: Regex = Regex::new(
-                r#"(?x)
-                    \s*(\w+?)\s*=\s*(.+?)\s*;
-                "#
-            )
-            .unwrap();
+    // NOTE: stations are matched by ID, the first table loaded wins on conflicting coordinates while frequencies are joined
+    pub fn merge(&mut self, mut other: SystemTable) {
+        self.version = self.version.max(other.version);
+
+        for station in std::mem::take(&mut other.stations).into_iter() {
+            let Some(existing) = self.stations.iter_mut().find(|x| x.id == station.id) else {
+                self.stations.push(station);
+                continue;
+            };
+
+            if existing.position != station.position {
+                warn!(
+                    "Ground station {} ({}) has conflicting coordinates in {}: keeping {:?}, ignoring {:?}",
+                    existing.id,
+                    existing.name,
+                    other.path.to_string_lossy(),
+                    existing.position,
+                    station.position
+                );
+            }
+
+            for freq in station.frequencies.into_iter() {
+                if !existing.frequencies.contains(&freq) {
+                    existing.frequencies.push(freq);
+                }
+            }
         }
 
+        self.stations.sort_by_key(|x| x.id);
+    }
+
+    pub fn to_config_string(&self) -> String {
+        let stations = self
+            .stations
+            .iter()
+            .map(|x| {
+                format!(
+                    "  {{\n    id = {};\n    name = \"{}\";\n    lat = {:?};\n    lon = {:?};\n    frequencies = ( {} );\n  }}",
+                    x.id,
+                    x.name.replace('\\', "\\\\").replace('"', "\\\""),
+                    x.position.0,
+                    x.position.1,
+                    x.frequencies
+                        .iter()
+                        .map(|y| y.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",\n");
+
+        format!("version = {};\nstations = (\n{}\n);\n", self.version, stations)
+    }
+
+    // NOTE: dumphfdl only takes a single --system-table, so merged tables are written out and path points at the result
+    pub fn write_merged(&mut self) -> io::Result<()> {
+        let mut file = tempfile::Builder::new()
+            .prefix("xng-systable-")
+            .suffix(".conf")
+            .tempfile()?;
+        file.write_all(self.to_config_string().as_bytes())?;
+        file.flush()?;
+
+        self.path = file.path().to_path_buf();
+        self.merged = Some(file);
+
+        Ok(())
+    }
+
+    pub fn load(path: &PathBuf) -> io::Result<Self> {
         let mut raw_content = String::new();
         {
             let Ok(mut fd) = File::open(path) else {
@@ -166,90 +225,65 @@ impl SystemTable {
             };
         }
 
-        let content = NEWLINES_FMT.replace_all(&raw_content, "");
-        let Some(m) = SYSTABLE_FMT.captures(&content) else {
+        match Self::parse(&raw_content) {
+            Ok(mut systable) => {
+                systable.path = path.clone();
+                Ok(systable)
+            }
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("{}: {}", path.to_string_lossy(), e.to_string()),
+            )),
+        }
+    }
+
+    // NOTE: errors name the byte offset, line and column (or the station and field) where parsing failed
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let settings = ConfigParser::new(content).parse()?;
+
+        let Some((version_offset, version_value)) = find_setting(&settings, "version") else {
+            return Err(invalid_data(String::from("Malformed systable configuration: missing version setting")));
+        };
+        let version = match version_value {
+            ConfigValue::Number(x) => x.parse::<u8>().ok(),
+            _ => None,
+        };
+        let Some(version) = version.filter(|&x| x >= 51) else {
             debug!(
-                "SYSTABLE_FMT regex has no captures when processing {}",
-                path.to_string_lossy()
+                "System table version number too old or invalid: expected >=51, got {:?}",
+                version_value
             );
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Malformed systable configuration: {}",
-                    path.to_string_lossy()
-                ),
-            ));
+            return Err(invalid_data(format!(
+                "Invalid version number at {}: expected a number >= 51",
+                describe_offset(content, *version_offset)
+            )));
         };
 
-        let version: u8 = m.get(1).map_or("", |x| x.as_str()).parse().unwrap_or(0);
-        if version < 51 {
-            debug!(
-                "System table version number too old: expected >=51, got {}",
-                version
-            );
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Invalid version number: {}", path.to_string_lossy()),
-            ));
-        }
+        let Some((stations_offset, stations_value)) = find_setting(&settings, "stations") else {
+            return Err(invalid_data(String::from("Malformed systable configuration: missing stations setting")));
+        };
+        let ConfigValue::List(raw_stations) = stations_value else {
+            return Err(invalid_data(format!(
+                "Malformed systable configuration at {}: stations should be a list",
+                describe_offset(content, *stations_offset)
+            )));
+        };
 
         debug!("Processing ground stations from systable.conf");
 
         let mut stations: Vec<GroundStation> = Vec::new();
-        let station_content = m.get(2).map_or("", |x| x.as_str());
-        
-        for c in STATIONS_FMT.captures_iter(&station_content) {
-            let mut station_id: Option<u8> = None;
-            let mut station_name: Option<String> = None;
-            let mut station_lat: Option<f64> = None;
-            let mut station_lon: Option<f64> = None;
-            let mut station_freqs: Vec<f64> = vec![];
-            
-            let raw_fields = c.get(1).map_or("", |x| x.as_str());
-            for raw_field in STATION_FIELD_FMT.captures_iter(&raw_fields) {
-                let key = raw_field.get(1).map_or("", |x| x.as_str()).to_lowercase();
-                match key.trim() {
-                    "id" => station_id = raw_field.get(2).map_or("", |x| x.as_str()).parse::<u8>().ok(),
-                    "name" => {
-                        let raw_value = raw_field.get(2).map_or("", |x| x.as_str());
-                        let bytes = raw_value.as_bytes();
-
-                        if bytes.len() > 1 && bytes[0] == b'"' && bytes[bytes.len()-1] == b'"' {
-                            station_name = from_utf8(&bytes[1..(bytes.len()-1)]).map(|x| x.to_string()).ok();
-                        }
-                    },
-                    "lat" => station_lat = raw_field.get(2).map_or("", |x| x.as_str()).trim().parse::<f64>().ok(),
-                    "lon" =>  station_lon = raw_field.get(2).map_or("", |x| x.as_str()).trim().parse::<f64>().ok(),
-                    "frequencies" => {
-                        let raw_value = raw_field.get(2).map_or("", |x| x.as_str()).replace(" ", "");
-                        let bytes = raw_value.as_bytes();
-
-                        if bytes.len() > 1 && bytes[0] == b'(' && bytes[bytes.len()-1] == b')' {
-                            if let Ok(value) = from_utf8(&bytes[1..(bytes.len()-1)]) {
-                                station_freqs = value.split(",").into_iter().map(|x| x.parse::<f64>().unwrap_or(0.0)).collect();
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        for (idx, (offset, raw_station)) in raw_stations.iter().enumerate() {
+            let station = parse_station(raw_station).map_err(|e| {
+                invalid_data(format!(
+                    "Malformed ground station #{} at {}: {}",
+                    idx + 1,
+                    describe_offset(content, *offset),
+                    e
+                ))
+            })?;
 
-            if let Some(station) = GroundStation::new(
-                station_id.unwrap_or(0),
-                station_name.unwrap_or("".to_string()),
-                station_lat.unwrap_or(180.0),
-                station_lon.unwrap_or(180.0),
-                station_freqs,
-            ) {
-                trace!("  Station = {:#?}", station);
-                stations.push(station);
-            } else {
-                trace!("Invalid station");
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Malformed ground station: {}", path.to_string_lossy()),
-                ));
-            }
+            trace!("  Station = {:#?}", station);
+            stations.push(station);
         }
 
         debug!(
@@ -259,9 +293,367 @@ impl SystemTable {
         );
 
         Ok(SystemTable {
-            path: path.clone(),
+            path: PathBuf::new(),
             version,
             stations,
+            merged: None,
         })
     }
 }
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn describe_offset(content: &str, offset: usize) -> String {
+    let before = content.get(..offset).unwrap_or(content);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |x| x.chars().count()) + 1;
+
+    format!("line {}, column {} (byte {})", line, column, offset)
+}
+
+#[derive(Debug)]
+enum ConfigValue {
+    Number(String),
+    Str(String),
+    List(Vec<(usize, ConfigValue)>),
+    Group(Vec<(String, usize, ConfigValue)>),
+}
+
+fn find_setting<'a>(
+    settings: &'a Vec<(String, usize, ConfigValue)>,
+    name: &str,
+) -> Option<(&'a usize, &'a ConfigValue)> {
+    settings
+        .iter()
+        .find(|(key, _, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, offset, value)| (offset, value))
+}
+
+fn parse_number<T: std::str::FromStr>(field: &str, value: &ConfigValue) -> Result<T, String> {
+    match value {
+        ConfigValue::Number(x) => x
+            .parse::<T>()
+            .map_err(|_| format!("{} field is not a valid number: {}", field, x)),
+        _ => Err(format!("{} field should be a number, got {:?}", field, value)),
+    }
+}
+
+fn parse_station(value: &ConfigValue) -> Result<GroundStation, String> {
+    let ConfigValue::Group(fields) = value else {
+        return Err(String::from("expected a { ... } group"));
+    };
+
+    let mut station_id: Option<u8> = None;
+    let mut station_name: Option<String> = None;
+    let mut station_lat: Option<f64> = None;
+    let mut station_lon: Option<f64> = None;
+    let mut station_freqs: Option<Vec<f64>> = None;
+
+    for (key, _, field) in fields.iter() {
+        match key.to_lowercase().as_str() {
+            "id" => station_id = Some(parse_number::<u8>("id", field)?),
+            "name" => match field {
+                ConfigValue::Str(x) => station_name = Some(x.clone()),
+                _ => return Err(format!("name field should be a string, got {:?}", field)),
+            },
+            "lat" => station_lat = Some(parse_number::<f64>("lat", field)?),
+            "lon" => station_lon = Some(parse_number::<f64>("lon", field)?),
+            "frequencies" => match field {
+                ConfigValue::List(items) => {
+                    station_freqs = Some(
+                        items
+                            .iter()
+                            .map(|(_, x)| parse_number::<f64>("frequencies", x))
+                            .collect::<Result<Vec<f64>, String>>()?,
+                    )
+                }
+                _ => return Err(format!("frequencies field should be a list, got {:?}", field)),
+            },
+            _ => {}
+        }
+    }
+
+    let Some(station_id) = station_id else {
+        return Err(String::from("missing id field"));
+    };
+    let (Some(station_name), Some(station_lat), Some(station_lon), Some(station_freqs)) =
+        (station_name, station_lat, station_lon, station_freqs)
+    else {
+        return Err(format!(
+            "station {} is missing one of the name, lat, lon or frequencies fields",
+            station_id
+        ));
+    };
+
+    GroundStation::new(station_id, station_name, station_lat, station_lon, station_freqs)
+}
+
+// NOTE: a small recursive descent parser for the libconfig subset systable.conf is written in, so
+//       settings may come in any order and comments (#, //, /* */) are skipped
+struct ConfigParser<'a> {
+    content: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+
+    // NOTE: setting names and list item numbers leading to the value being parsed, only used for errors
+    path: Vec<String>,
+}
+
+impl<'a> ConfigParser<'a> {
+    fn new(content: &'a str) -> ConfigParser<'a> {
+        ConfigParser {
+            content,
+            bytes: content.as_bytes(),
+            pos: 0,
+            path: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> io::Result<Vec<(String, usize, ConfigValue)>> {
+        self.parse_settings(None)
+    }
+
+    fn error_at(&self, offset: usize, message: String) -> io::Error {
+        let location = if self.path.is_empty() {
+            describe_offset(self.content, offset)
+        } else {
+            format!("{} in {}", describe_offset(self.content, offset), self.path.join(" "))
+        };
+        invalid_data(format!("Malformed systable configuration at {}: {}", location, message))
+    }
+
+    fn expected(&self, what: &str) -> io::Error {
+        let found = match self.content.get(self.pos..).and_then(|x| x.chars().next()) {
+            Some(x) => format!("'{}'", x),
+            None => String::from("end of file"),
+        };
+        self.error_at(self.pos, format!("expected {}, found {}", what, found))
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = &self.bytes[self.pos..];
+            if rest.first().map_or(false, |x| x.is_ascii_whitespace()) {
+                self.pos += 1;
+            } else if rest.starts_with(b"#") || rest.starts_with(b"//") {
+                self.pos += rest.iter().position(|&x| x == b'\n').unwrap_or(rest.len());
+            } else if rest.starts_with(b"/*") {
+                self.pos += rest
+                    .windows(2)
+                    .position(|x| x == b"*/")
+                    .map_or(rest.len(), |x| x + 2);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn take_while(&mut self, pred: impl Fn(u8) -> bool) -> String {
+        let start = self.pos;
+        while self.bytes.get(self.pos).map_or(false, |&x| pred(x)) {
+            self.pos += 1;
+        }
+        self.content[start..self.pos].to_string()
+    }
+
+    fn parse_settings(&mut self, close: Option<u8>) -> io::Result<Vec<(String, usize, ConfigValue)>> {
+        let mut settings: Vec<(String, usize, ConfigValue)> = Vec::new();
+
+        loop {
+            match self.peek() {
+                None if close.is_none() => return Ok(settings),
+                Some(x) if Some(x) == close => {
+                    self.pos += 1;
+                    return Ok(settings);
+                }
+                Some(x) if x.is_ascii_alphabetic() || x == b'_' || x == b'*' => {}
+                _ => {
+                    return Err(self.expected(if close.is_some() {
+                        "a setting name or '}'"
+                    } else {
+                        "a setting name"
+                    }))
+                }
+            }
+
+            let offset = self.pos;
+            let name = self.take_while(|x| x.is_ascii_alphanumeric() || x == b'_' || x == b'-' || x == b'*');
+
+            match self.peek() {
+                Some(b'=') | Some(b':') => self.pos += 1,
+                _ => return Err(self.expected(&format!("'=' or ':' after {}", name))),
+            }
+
+            self.path.push(name.clone());
+            let value = self.parse_value()?;
+            self.path.pop();
+
+            if matches!(self.peek(), Some(b';') | Some(b',')) {
+                self.pos += 1;
+            }
+
+            settings.push((name, offset, value));
+        }
+    }
+
+    // NOTE: every nested value adds to the path, so capping it keeps a run of '(' from overflowing the stack
+    fn parse_value(&mut self) -> io::Result<ConfigValue> {
+        if self.path.len() > MAX_CONFIG_NESTING {
+            return Err(self.error_at(self.pos, format!("values nested deeper than {} levels", MAX_CONFIG_NESTING)));
+        }
+
+        match self.peek() {
+            Some(b'"') => self.parse_string(),
+            Some(b'(') => self.parse_list(b')'),
+            Some(b'[') => self.parse_list(b']'),
+            Some(b'{') => {
+                self.pos += 1;
+                Ok(ConfigValue::Group(self.parse_settings(Some(b'}'))?))
+            }
+            Some(x) if x.is_ascii_digit() || x == b'-' || x == b'+' || x == b'.' => Ok(ConfigValue::Number(
+                self.take_while(|x| x.is_ascii_alphanumeric() || x == b'.' || x == b'-' || x == b'+'),
+            )),
+            _ => Err(self.expected("a value")),
+        }
+    }
+
+    fn parse_string(&mut self) -> io::Result<ConfigValue> {
+        let start = self.pos;
+        let mut value = String::new();
+        let mut escaped = false;
+
+        for (idx, x) in self.content[start + 1..].char_indices() {
+            if escaped {
+                value.push(x);
+                escaped = false;
+            } else if x == '\\' {
+                escaped = true;
+            } else if x == '"' {
+                self.pos = start + 1 + idx + 1;
+                return Ok(ConfigValue::Str(value));
+            } else {
+                value.push(x);
+            }
+        }
+
+        Err(self.error_at(start, String::from("string is missing its closing '\"'")))
+    }
+
+    fn parse_list(&mut self, close: u8) -> io::Result<ConfigValue> {
+        self.pos += 1;
+
+        let mut items: Vec<(usize, ConfigValue)> = Vec::new();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(ConfigValue::List(items));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let offset = self.pos;
+            self.path.push(format!("#{}", items.len() + 1));
+            let value = self.parse_value()?;
+            self.path.pop();
+            items.push((offset, value));
+
+            match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                    if self.peek() == Some(close) {
+                        self.pos += 1;
+                        break;
+                    }
+                }
+                Some(x) if x == close => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(self.expected(&format!("',' or '{}'", close as char))),
+            }
+        }
+
+        Ok(ConfigValue::List(items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_STATION: &'static str = "{ id = 1; name = \"San Francisco, California\"; lat = 37.4; lon = -122.2; frequencies = ( 5451, 8927 ); }";
+
+    fn systable_with_station(station: &str) -> String {
+        format!("version = 51;\nstations = (\n  {},\n  {}\n);\n", VALID_STATION, station)
+    }
+
+    fn parse_err(content: &str) -> String {
+        SystemTable::parse(content).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_valid_systable() {
+        let systable = SystemTable::parse(&systable_with_station(
+            "{ id = 2; name = \"Molokai, Hawaii\"; lat = 21.2; lon = -157.2; frequencies = ( 8927 ); }",
+        ))
+        .unwrap();
+
+        assert_eq!(systable.version, 51);
+        assert_eq!(systable.stations.len(), 2);
+        assert_eq!(systable.by_id(2).unwrap().frequencies, vec![8927]);
+    }
+
+    #[test]
+    fn unclosed_string_names_station_and_field() {
+        let err = parse_err(&systable_with_station(
+            "{ id = 2; name = \"Molokai; lat = 21.2; lon = -157.2; frequencies = ( 8927 ); }",
+        ));
+
+        assert!(err.contains("line 4, column 20"), "{}", err);
+        assert!(err.contains("stations #2 name"), "{}", err);
+        assert!(err.contains("missing its closing"), "{}", err);
+    }
+
+    #[test]
+    fn station_without_id_names_station_and_field() {
+        let err = parse_err(&systable_with_station(
+            "{ name = \"Molokai, Hawaii\"; lat = 21.2; lon = -157.2; frequencies = ( 8927 ); }",
+        ));
+
+        assert!(err.contains("ground station #2"), "{}", err);
+        assert!(err.contains("missing id field"), "{}", err);
+    }
+
+    #[test]
+    fn non_numeric_frequency_names_station_and_field() {
+        for frequencies in ["8927, \"abc\"", "8927, 10081x"] {
+            let err = parse_err(&systable_with_station(&format!(
+                "{{ id = 2; name = \"Molokai, Hawaii\"; lat = 21.2; lon = -157.2; frequencies = ( {} ); }}",
+                frequencies
+            )));
+
+            assert!(err.contains("ground station #2"), "{}", err);
+            assert!(err.contains("frequencies field"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn missing_stations_setting() {
+        let err = parse_err("version = 51;\n");
+
+        assert!(err.contains("missing stations setting"), "{}", err);
+    }
+
+    #[test]
+    fn deeply_nested_lists_are_rejected() {
+        let err = parse_err(&format!("stations = {};\n", "(".repeat(100_000)));
+
+        assert!(err.contains("nested deeper than"), "{}", err);
+    }
+}
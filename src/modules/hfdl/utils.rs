@@ -1,23 +1,15 @@
 use std::collections::HashMap;
 
+use rand::Rng;
+
+use crate::common::frame::PropagationPath;
+use crate::utils::band_name;
+
 pub fn get_max_dist_khz_by_sample_rate(sample_rate: u32) -> u32 {
     (((sample_rate as f64) * 0.9) / 1000.0) as u32
 }
 
 pub fn freq_bands_by_sample_rate(freqs: &Vec<u16>, sample_rate: u32) -> HashMap<String, Vec<u16>> {
-    let band_name = |band: &Vec<u16>| -> String {
-        let first = *band.first().unwrap_or(&0);
-        let last = *band.last().unwrap_or(&0);
-
-        if first == 0 {
-            return format!("{}", last);
-        }
-        if last == 0 || first == last {
-            return format!("{}", first);
-        }
-
-        format!("{}-{}", first, last)
-    };
     let mut bands: HashMap<String, Vec<u16>> = HashMap::new();
 
     let max_dist_khz = get_max_dist_khz_by_sample_rate(sample_rate) as u16;
@@ -50,3 +42,209 @@ pub fn first_freq_above_eq(freqs: &Vec<u16>, target_freq: u16) -> Option<u16> {
         .position(|&x| x >= target_freq)
         .map(|i| freqs[i])
 }
+
+// NOTE: dumphfdl doesn't decode request_data any further, so only the positions of the set bits are
+//       exposed rather than guessing at what each one asks the ground station for
+pub fn request_data_bits(code: u16) -> Vec<u8> {
+    (0..16u8)
+        .filter(|x| code & (1 << x) != 0)
+        .collect()
+}
+
+// NOTE: paths without a party id cannot be matched and are kept as is
+pub fn dedup_paths_by_party(paths: Vec<PropagationPath>) -> Vec<PropagationPath> {
+    let mut deduped: Vec<PropagationPath> = Vec::with_capacity(paths.len());
+
+    for path in paths.into_iter() {
+        let existing = path.party.id.and_then(|id| {
+            deduped
+                .iter_mut()
+                .find(|x| x.party.id == Some(id) && x.party.kind == path.party.kind)
+        });
+
+        match existing {
+            Some(entry) => {
+                for freq in path.freqs.into_iter() {
+                    if !entry.freqs.contains(&freq) {
+                        entry.freqs.push(freq);
+                    }
+                }
+                entry.freqs.sort_by(|a, b| a.total_cmp(b));
+            }
+            None => deduped.push(path),
+        }
+    }
+
+    deduped
+}
+
+// NOTE: a band can be listened to from anywhere its span still fits in the sample rate's usable bandwidth
+pub fn uncovered_freqs(freqs: &Vec<u16>, all_freqs: &Vec<u16>, sample_rate: u32) -> Vec<u16> {
+    let max_dist_khz = get_max_dist_khz_by_sample_rate(sample_rate) as u16;
+    let bands = freq_bands_by_sample_rate(all_freqs, sample_rate);
+
+    freqs
+        .iter()
+        .filter(|&&freq| {
+            !bands.values().any(|band| match (band.first(), band.last()) {
+                (Some(&first), Some(&last)) => {
+                    last.saturating_sub(max_dist_khz) <= freq && freq <= first.saturating_add(max_dist_khz)
+                }
+                _ => false,
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+// NOTE: candidates are the first frequency of each band, returns 0 when no band could be chosen. The RNG is
+//       passed in so band selection can be reproduced with a seeded generator
+pub fn select_session_band<R: Rng + ?Sized>(
+    method: &str,
+    mut candidates: Vec<u16>,
+    last_listening_freq: Option<u64>,
+    last_random_freq_band: &mut u64,
+    rng: &mut R,
+) -> u64 {
+    candidates.sort_unstable();
+    if candidates.is_empty() || method == "static" {
+        return 0;
+    }
+
+    if let Some(first_freq) = last_listening_freq {
+        if let Some(last_idx) = candidates.iter().position(|&x| x as u64 >= first_freq) {
+            let max_idx = candidates.len() - 1;
+            match method {
+                "inc" => return candidates[if last_idx + 1 > max_idx { 0 } else { last_idx + 1 }] as u64,
+                "dec" => return candidates[if last_idx == 0 { max_idx } else { last_idx - 1 }] as u64,
+                _ => {}
+            }
+        }
+    }
+
+    if method != "random" {
+        return 0;
+    }
+
+    if let Some(first_freq) = last_listening_freq {
+        candidates.retain(|&x| x as u64 != first_freq && x as u64 != *last_random_freq_band);
+        *last_random_freq_band = first_freq;
+    }
+
+    if candidates.is_empty() {
+        return 0;
+    }
+
+    candidates[rng.gen_range(0..candidates.len())] as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::common::frame::Entity;
+    use crate::common::wkt::WKTPolyline;
+
+    use super::*;
+
+    const CANDIDATES: [u16; 4] = [2941, 5451, 8825, 13276];
+
+    fn select(method: &str, last_listening_freq: Option<u64>, last_random_freq_band: &mut u64, seed: u64) -> u64 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        select_session_band(method, CANDIDATES.to_vec(), last_listening_freq, last_random_freq_band, &mut rng)
+    }
+
+    #[test]
+    fn inc_moves_to_next_band_and_wraps() {
+        let mut last_random = 0;
+        assert_eq!(select("inc", Some(5451), &mut last_random, 0), 8825);
+        assert_eq!(select("inc", Some(13276), &mut last_random, 0), 2941);
+    }
+
+    #[test]
+    fn dec_moves_to_previous_band_and_wraps() {
+        let mut last_random = 0;
+        assert_eq!(select("dec", Some(5451), &mut last_random, 0), 2941);
+        assert_eq!(select("dec", Some(2941), &mut last_random, 0), 13276);
+    }
+
+    #[test]
+    fn static_and_empty_candidates_select_nothing() {
+        let mut last_random = 0;
+        assert_eq!(select("static", Some(5451), &mut last_random, 0), 0);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(select_session_band("random", Vec::new(), None, &mut last_random, &mut rng), 0);
+    }
+
+    #[test]
+    fn random_is_reproducible_with_a_seed() {
+        for seed in 0..16 {
+            let (mut a, mut b) = (0, 0);
+            assert_eq!(select("random", None, &mut a, seed), select("random", None, &mut b, seed));
+        }
+    }
+
+    #[test]
+    fn random_skips_current_and_previous_bands() {
+        for seed in 0..64 {
+            let mut last_random = 2941;
+            let freq = select("random", Some(5451), &mut last_random, seed);
+            assert!(freq == 8825 || freq == 13276, "seed {} picked {}", seed, freq);
+            assert_eq!(last_random, 5451);
+        }
+    }
+
+    // NOTE: the old gen_range(0..len - 1) never picked the last candidate and panicked with a single one left
+    #[test]
+    fn random_can_pick_the_last_and_only_remaining_band() {
+        let mut last_random = 5451;
+        assert_eq!(
+            select_session_band("random", vec![5451, 8825], Some(5451), &mut last_random, &mut StdRng::seed_from_u64(0)),
+            8825
+        );
+
+        let picked: Vec<u64> = (0..64)
+            .map(|seed| select("random", None, &mut 0, seed))
+            .collect();
+        for freq in CANDIDATES.iter() {
+            assert!(picked.contains(&(*freq as u64)), "{} was never picked", freq);
+        }
+    }
+
+    fn path(id: Option<u32>, freqs: Vec<f64>) -> PropagationPath {
+        PropagationPath {
+            freqs,
+            path: WKTPolyline { points: vec![(0.0, 0.0, 0.0), (1.0, 1.0, 0.0)] },
+            party: Entity {
+                kind: String::from("Ground station"),
+                icao: None,
+                gs: None,
+                id,
+                callsign: None,
+                tail: None,
+                coords: None,
+            },
+        }
+    }
+
+    #[test]
+    fn dedup_paths_merges_overlapping_heard_on_entries() {
+        let deduped = dedup_paths_by_party(vec![
+            path(Some(2), vec![8.927, 6.559]),
+            path(Some(4), vec![10.081]),
+            path(Some(2), vec![6.559, 11.184]),
+            path(None, vec![8.927]),
+            path(None, vec![8.927]),
+        ]);
+
+        assert_eq!(deduped.len(), 4);
+        assert_eq!(deduped.iter().filter(|x| x.party.id == Some(2)).count(), 1);
+        assert_eq!(deduped[0].party.id, Some(2));
+        assert_eq!(deduped[0].freqs, vec![6.559, 8.927, 11.184]);
+        assert_eq!(deduped[1].freqs, vec![10.081]);
+        assert_eq!(deduped[2].party.id, None);
+        assert_eq!(deduped[3].party.id, None);
+    }
+}
@@ -5,39 +5,56 @@ use clap::{ArgMatches, Command, arg};
 use ::elasticsearch::Elasticsearch;
 use log::*;
 use reqwest::Url;
+use serde::Serialize;
 use serde_json::json;
-use tokio::io::AsyncWriteExt;
-use tokio::net::TcpStream;
 use tokio::signal::unix::{SignalKind, signal};
 use tokio::select;
 use tokio::sync::{RwLock, Mutex};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio::task::JoinHandle;
 use tokio::time::{self, sleep, Instant};
 use tokio::io;
 use tokio_util::sync::CancellationToken;
+use rand::Rng;
+use rdkafka::producer::Producer;
 use std::collections::HashMap;
 use std::process::exit;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::common;
-use crate::common::arguments::{parse_api_token, parse_disable_cross_site, parse_listen_host, parse_listen_port, parse_elastic_url, parse_state_db_url, parse_disable_state_db, parse_elastic_index};
+use crate::common::arguments::{parse_api_token, parse_swarm_token, parse_disable_cross_site, parse_listen_host, parse_listen_port, parse_elastic_url, parse_state_db_url, parse_disable_state_db, parse_elastic_index, parse_es_exclude_fields, parse_coord_precision, parse_kafka_brokers, parse_kafka_topic, parse_http_timeout, parse_user_agent, parse_summary_cache_ttl, parse_frame_buffer, parse_backpressure_policy, parse_state_db_read_pool, parse_state_db_auto_recover, parse_keep_broadcast, parse_min_event_interval, parse_max_future_skew, parse_auto_vacuum_interval, parse_shutdown_timeout};
+use crate::common::acars_labels::describe_label;
+use crate::common::acars_reassembly::{AcarsReassembler, DEFAULT_REASSEMBLY_TIMEOUT_SECS};
 use crate::common::batcher::create_es_batch_task;
-use crate::common::es_utils::create_es_client;
-use crate::common::events::GroundStationChangeEvent;
+use crate::common::es_utils::{create_es_client, EsIndexTarget};
+use crate::common::http::create_http_client;
+use crate::common::print_sampler::PrintSampler;
+use crate::common::swarm_target::{SwarmSink, SwarmTarget, DEFAULT_SWARM_QUEUE_SIZE};
+use crate::common::kafka_utils::{create_kafka_producer, produce_frame, KafkaProducer};
+use crate::common::webhook::{parse_webhook_headers, WebhookSink, DEFAULT_WEBHOOK_BATCH_SIZE, DEFAULT_WEBHOOK_QUEUE_SIZE};
+use crate::common::events::{GroundStationChangeEvent, Heartbeat, HeartbeatMessage};
 use crate::common::frame::CommonFrame;
-use crate::modules::session::{EndSessionReason, SESSION_SCHEDULED_END};
-use crate::modules::validators::validate_listening_bands;
-use crate::server::db::StateDB;
+use crate::common::frame_queue::frame_channel;
+use crate::modules::session::{EndSessionReason, SESSION_DRY_RUN_END, SESSION_INPUT_CLOSED_END, SESSION_SCHEDULED_END};
+use crate::modules::validators::{validate_active_freq_ttl_sec, validate_listening_bands};
+use crate::server::db::{create_summary_cache_task, create_vacuum_task, StateDB};
 use crate::server::services as server_services;
+use crate::server::DEFAULT_FUTURE_WARN_INTERVAL_SECS;
 
 use self::session::Session;
 use self::settings::ModuleSettings;
 
+mod acars;
 mod aoa;
+mod external;
+mod ground_station_db;
 mod hfdl;
+mod satcom;
 mod services;
 mod session;
+mod status;
 mod validators;
 
 pub mod elasticsearch;
@@ -45,17 +62,32 @@ pub mod settings;
 
 const DEFAULT_INITIAL_SWARM_CONNECT_TIMEOUT_SECS: u64 = 60;
 const DEFAULT_SESSION_INTERMISSION_SECS: u64 = 0;
+const DEFAULT_SESSION_INTERMISSION_JITTER: f64 = 0.0;
+const MAX_SESSION_INTERMISSION_JITTER: f64 = 10.0;
+const DEFAULT_ACTIVE_FREQ_TTL_SECS: u64 = 0;
+const MAX_ACTIVE_FREQ_TTL_SECS: u64 = 7 * 24 * 3600;
+const DEFAULT_HEARTBEAT_SECS: u64 = 0;
+const DEFAULT_NO_FRAME_WATCHDOG_SECS: u64 = 0;
+const MAX_NO_FRAME_WATCHDOG_SECS: u64 = 7 * 24 * 3600;
+const DEFAULT_PRINT_STATS_INTERVAL_SECS: u64 = 0;
 const DEFAULT_FAILED_SESSION_START_WAIT_SECS: u64 = 60;
+const DEFAULT_CRASH_BACKOFF_BASE_SECS: u64 = 2;
+const DEFAULT_CRASH_BACKOFF_MAX_SECS: u64 = 300;
 const DEFAULT_BATCH_WAIT_MS: u64 = 200;
+const DEFAULT_GS_CHANGE_BATCH_SIZE: usize = 64;
+const DEFAULT_GS_CHANGE_BATCH_MS: u64 = 500;
+const DEFAULT_KAFKA_FLUSH_TIMEOUT_SECS: u64 = 10;
 const DEFAULT_STATE_DB_URL: &'static str = "sqlite://state.sqlite3";
 const DEFAULT_LISTEN_HOST: &'static str = "127.0.0.1";
 const DEFAULT_LISTEN_PORT: u16 = 7871;
 
 const DEFAULT_CHANNEL_BUFFER: usize = 2048;
+const DEFAULT_CHANGE_EVENT_STREAM_BUFFER: usize = 64;
 
 const PROP_SESSION_TIMEOUT_SEC: &'static str = "session_timeout_sec";
 const PROP_SESSION_INTERMISSION_SEC: &'static str = "session_intermission_sec";
 const PROP_LISTENING_BAND: &'static str = "listening_band";
+const PROP_ACTIVE_FREQ_TTL_SEC: &'static str = "active_freq_ttl_sec";
 
 #[async_trait]
 pub trait XngModule {
@@ -64,7 +96,7 @@ pub trait XngModule {
     fn default_session_timeout_secs(&self) -> u64;
     
     fn get_arguments(&self) -> Command;
-    fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error>;
+    async fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error>;
 
     async fn init(&mut self, settings: Data<RwLock<ModuleSettings>>, state_db: Data<RwLock<StateDB>>);
 
@@ -72,6 +104,11 @@ pub trait XngModule {
     async fn start_session(&mut self, last_end_reason: EndSessionReason) -> Result<Box<dyn Session>, io::Error>;
 
     async fn reload(&mut self) -> Result<(), io::Error>;
+
+    // NOTE: a commented, known-good invocation rendered from the module's embedded template
+    fn example_invocation(&self) -> Option<String> {
+        None
+    }
 }
 
 pub struct ModuleManager {
@@ -82,7 +119,7 @@ impl ModuleManager {
     pub fn init() -> ModuleManager {
         ModuleManager {
             modules: HashMap::from_iter(
-                [aoa::AoaModule::new(), hfdl::HfdlModule::new()]
+                [acars::AcarsModule::new(), aoa::AoaModule::new(), external::ExternalModule::new(), hfdl::HfdlModule::new(), satcom::SatcomModule::new()]
                     .map(|m| (m.id(), m))
                     .into_iter()
                     .collect::<Vec<(&'static str, Box<dyn XngModule>)>>(),
@@ -98,11 +135,39 @@ impl ModuleManager {
                     common::arguments::register_common_arguments(m.get_arguments())
                         .args(&[
                             arg!(--"disable-api-control" "Disable controlling of session from API server"),
-                            arg!(--swarm <URL> "xng server instance to connect to (local API server will be disabled)"),
+                            arg!(--"disable-db-export" "Disable downloading the state DB from /api/export/db/"),
+                            arg!(--swarm <URL> ... "xng server instance to connect to, repeat to forward frames to several servers (local API server will be disabled)"),
+                            arg!(--"swarm-msgpack" "Send frames to swarm servers as length-prefixed MessagePack instead of newline delimited JSON. The switch is announced one-way and never acknowledged, so every --swarm server must support it"),
                             arg!(--"feed-airframes" "Feed JSON frames to airframes.io"),
+                            arg!(--"feed-airframes-manual" "Feed airframes.io using a decoder --output configured by hand; only validates feeding requirements").conflicts_with("feed-airframes"),
+                            arg!(--"kafka-brokers" <BROKERS> "Comma separated Kafka brokers to produce common JSON frames to"),
+                            arg!(--"kafka-topic" <TOPIC> "Kafka topic to produce common JSON frames to (default: xng_frames)"),
+                            arg!(--"webhook-url" <URL> "POST each common JSON frame to the given HTTP endpoint"),
+                            arg!(--"webhook-header" <HEADER> ... "Extra \"Name: value\" header sent with webhook requests, e.g. for auth"),
+                            arg!(--"webhook-batch" <COUNT> "Maximum number of frames POSTed together as a JSON array (default: 1, a single frame object)"),
+                            arg!(--"webhook-queue" <COUNT> "Frames buffered for the webhook before new frames are dropped (default: 1024)"),
+                            arg!(--"gs-change-batch-size" <COUNT> "Maximum number of ground station change events written to the state DB in one transaction (default: 64)"),
+                            arg!(--"gs-change-batch-ms" <MILLIS> "Time to collect ground station change events before writing them to the state DB, 0 writes each event right away (default: 500)"),
                             arg!(--"session-timeout" <SECONDS> "Elapsed time since last frame before a session is considered stale and requires switching"),
+                            arg!(--"no-frame-watchdog-secs" <SECONDS> "Restart the session if the decoder produces no valid frame for this long, even while it keeps writing output (default: 0, disabled, max: 604800)"),
                             arg!(--"session-intermission" <SECONDS> "Time to wait between sessions"),
+                            arg!(--"session-intermission-jitter" <FRACTION> "Randomly extend each intermission by up to this fraction of it, e.g. 0.5 waits 1x-1.5x (default: 0, max: 10)"),
+                            arg!(--"active-freq-ttl-secs" <SECONDS> "Hard age after which a ground station frequency is no longer reported as active, independent of --stale-timeout (default: 0, disabled, max: 604800)"),
+                            arg!(--"heartbeat-secs" <SECONDS> "Log a heartbeat with the current band and uptime every N seconds, also sent to the swarm server and webhook if configured (default: 0, disabled)"),
+                            arg!(--"print-stats-interval" <SECONDS> "Log a one line throughput summary every N seconds (default: 0, disabled)"),
                             arg!(--"disable-print-frame" "Disable printing JSON frames to STDOUT"), 
+                            arg!(--"print-frame-sample" <N> "Only print every Nth JSON frame to STDOUT (default: 1, every frame)"),
+                            arg!(--"print-frame-rate" <PER_SEC> "Print at most this many JSON frames per second to STDOUT (default: 0, unlimited)"),
+                            arg!(--"skip-empty-frames" "Do not record frames without ACARS content or coordinates in the state DB (frames are still printed and forwarded)"),
+                            arg!(--"redact-acars-text" "Irreversibly replace ACARS message text with its SHA-256 hash before frames are stored or forwarded"),
+                            arg!(--"reassemble-acars" "Join multi-block ACARS messages into an additional reassembled frame once the final block arrives"),
+                            arg!(--"reassemble-acars-timeout" <SECONDS> "Time to wait for the remaining blocks of a multi-block ACARS message before dropping it (default: 120)"),
+                            arg!(--"drop-reserved-entities" "Drop frames whose source or destination is a reserved-type entity instead of storing and forwarding them"),
+                            arg!(--"normalize-direction" "Always place the aircraft in src and the ground station in dst, setting from_ground_station on the frame instead"),
+                            arg!(--"embed-raw" "Attach the original decoder JSON to each common frame as the raw field"),
+                            arg!(--"dry-run" "Print the decoder command line the first session would spawn, then exit"),
+                            arg!(--"print-example" "Print a commented example invocation for this module, then exit"),
+                            arg!(--"require-min-decoder-version" "Refuse to start if the decoder binary is older than the minimum supported version"),
                         ])
                 )
                 .collect::<Vec<Command>>(),
@@ -115,39 +180,115 @@ impl ModuleManager {
             exit(exitcode::CONFIG);   
         };
 
-        let api_token = parse_api_token(args);
+        if args.get_flag("print-example") {
+            match module.example_invocation() {
+                Some(example) => println!("{}", example),
+                None => error!("No example invocation is available for module '{}'", cmd),
+            }
+            return;
+        }
+
+        let api_token = match parse_api_token(args) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("{}", e.to_string());
+                return;
+            }
+        };
+        let swarm_token = parse_swarm_token(args).cloned();
+        let swarm_msgpack = args.get_flag("swarm-msgpack");
         let disable_cross_site = parse_disable_cross_site(args);
         let listen_host = parse_listen_host(args, DEFAULT_LISTEN_HOST);
         let listen_port = parse_listen_port(args, DEFAULT_LISTEN_PORT);
         
         let disable_api_control = args.get_flag("disable-api-control");
+        let disable_db_export = args.get_flag("disable-db-export");
+        let shutdown_timeout = parse_shutdown_timeout(args);
+        let heartbeat_secs = args
+            .get_one::<String>("heartbeat-secs")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_HEARTBEAT_SECS);
+        let print_stats_interval_secs = args
+            .get_one::<String>("print-stats-interval")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_PRINT_STATS_INTERVAL_SECS);
+        let started = Instant::now();
+        let module_id = module.id();
         let disable_print_frame = args.get_flag("disable-print-frame");
+        let mut print_sampler = PrintSampler::new(
+            args.get_one::<String>("print-frame-sample")
+                .unwrap_or(&String::from("default"))
+                .parse::<u64>()
+                .unwrap_or(1),
+            args.get_one::<String>("print-frame-rate")
+                .unwrap_or(&String::from("default"))
+                .parse::<u64>()
+                .unwrap_or(0),
+        );
+        let embed_raw = args.get_flag("embed-raw");
+        let normalize_direction = args.get_flag("normalize-direction");
+        let drop_reserved_entities = args.get_flag("drop-reserved-entities");
+        let max_future_skew_secs = parse_max_future_skew(args);
+        let skip_empty_frames = args.get_flag("skip-empty-frames");
+        let redact_acars_text = args.get_flag("redact-acars-text");
+        let reassemble_acars = args.get_flag("reassemble-acars");
+        let reassemble_acars_timeout_secs = args
+            .get_one::<String>("reassemble-acars-timeout")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_REASSEMBLY_TIMEOUT_SECS);
         
         let mut session_intermission_secs = args
             .get_one::<String>("session-intermission")
             .unwrap_or(&String::from("default"))
             .parse::<u64>()
             .unwrap_or(DEFAULT_SESSION_INTERMISSION_SECS);
+        let session_intermission_jitter = match args.get_one::<String>("session-intermission-jitter") {
+            Some(x) => match x.parse::<f64>() {
+                Ok(v) if v.is_finite() && (0.0..=MAX_SESSION_INTERMISSION_JITTER).contains(&v) => v,
+                _ => {
+                    error!(
+                        "Invalid session intermission jitter '{}', expected a fraction between 0 and {}",
+                        x, MAX_SESSION_INTERMISSION_JITTER
+                    );
+                    return;
+                }
+            },
+            None => DEFAULT_SESSION_INTERMISSION_JITTER,
+        };
+        let active_freq_ttl_secs = args
+            .get_one::<String>("active-freq-ttl-secs")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_ACTIVE_FREQ_TTL_SECS)
+            .min(MAX_ACTIVE_FREQ_TTL_SECS);
         let mut session_timeout_secs = args
             .get_one::<String>("session-timeout")
             .unwrap_or(&String::from("default"))
             .parse::<u64>()
             .unwrap_or(module.default_session_timeout_secs());
+        let no_frame_watchdog_secs = args
+            .get_one::<String>("no-frame-watchdog-secs")
+            .unwrap_or(&String::from("default"))
+            .parse::<u64>()
+            .unwrap_or(DEFAULT_NO_FRAME_WATCHDOG_SECS)
+            .min(MAX_NO_FRAME_WATCHDOG_SECS);
 
-        let swarm_url: Option<Url> = if let Some(raw_url) = args.get_one::<String>("swarm") {
+        let mut swarm_urls: Vec<Url> = Vec::new();
+        for raw_url in args.get_many::<String>("swarm").unwrap_or_default() {
             match Url::parse(raw_url) {
                 Ok(v) => {
                     info!("Swarm enabled: aggregator = {}", raw_url);
-                    Some(v)
+                    swarm_urls.push(v);
                 },
                 Err(e) => {
-                    error!("Swarm URL is invalid: {}", e.to_string());
+                    error!("Swarm URL is invalid, {}: {}", raw_url, e.to_string());
                     return;
                 }
             }
-        } else {
-            None
-        };
+        }
         let mut elastic_url = if let Some(raw_url) = parse_elastic_url(args) {
             match Url::parse(raw_url) {
                 Ok(v) => {
@@ -162,8 +303,92 @@ impl ModuleManager {
         } else {
             None
         };
-        let elastic_index = parse_elastic_index(args);
+        let elastic_index = parse_elastic_index(args, format!("xng_{}", module.id()).as_str());
         let validate_es_cert = args.get_flag("validate-es-cert");
+        let es_exclude_fields = match parse_es_exclude_fields(args) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("{}", e.to_string());
+                return;
+            }
+        };
+        let coord_precision = match parse_coord_precision(args) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("{}", e.to_string());
+                return;
+            }
+        };
+
+        let http_timeout = match parse_http_timeout(args) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("{}", e.to_string());
+                return;
+            }
+        };
+        let user_agent = parse_user_agent(args);
+        let http_client = match create_http_client(http_timeout, user_agent.as_str()) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to create HTTP client: {}", e.to_string());
+                return;
+            }
+        };
+
+        let kafka_brokers = parse_kafka_brokers(args).map(|x| x.clone());
+        let kafka_topic = parse_kafka_topic(args);
+        if let Some(ref brokers) = kafka_brokers {
+            info!("Kafka producing enabled: brokers = {}, topic = {}", brokers, kafka_topic);
+        }
+        
+        let webhook_url: Option<Url> = if let Some(raw_url) = args.get_one::<String>("webhook-url") {
+            match Url::parse(raw_url) {
+                Ok(v) => {
+                    info!("Webhook enabled: url = {}", raw_url);
+                    Some(v)
+                }
+                Err(e) => {
+                    error!("Webhook URL is invalid: {}", e.to_string());
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let webhook_headers = match parse_webhook_headers(
+            &args.get_many::<String>("webhook-header")
+                .map(|x| x.cloned().collect())
+                .unwrap_or_default()
+        ) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("{}", e.to_string());
+                return;
+            }
+        };
+        let gs_change_batch_size = args
+            .get_one::<String>("gs-change-batch-size")
+            .unwrap_or(&String::from("default"))
+            .parse::<usize>()
+            .unwrap_or(DEFAULT_GS_CHANGE_BATCH_SIZE)
+            .max(1);
+        let gs_change_batch_wait = Duration::from_millis(
+            args.get_one::<String>("gs-change-batch-ms")
+                .unwrap_or(&String::from("default"))
+                .parse::<u64>()
+                .unwrap_or(DEFAULT_GS_CHANGE_BATCH_MS),
+        );
+        let webhook_batch = args
+            .get_one::<String>("webhook-batch")
+            .unwrap_or(&String::from("default"))
+            .parse::<usize>()
+            .unwrap_or(DEFAULT_WEBHOOK_BATCH_SIZE);
+        let webhook_queue = args
+            .get_one::<String>("webhook-queue")
+            .unwrap_or(&String::from("default"))
+            .parse::<usize>()
+            .unwrap_or(DEFAULT_WEBHOOK_QUEUE_SIZE);
         
         let state_db_url = match Url::parse(parse_state_db_url(args, DEFAULT_STATE_DB_URL).as_str()) {
             Ok(v) => {
@@ -176,24 +401,41 @@ impl ModuleManager {
             }   
         };
                 
-        if swarm_url.is_some() && elastic_url.is_some() {
+        if !swarm_urls.is_empty() && elastic_url.is_some() {
             error!("Swarm mode and importing to Elasticsearch are mutually exclusive options");
             error!("Please choose either swarm mode or importing to Elasticsearch.");
             return;    
         }
 
+        if !swarm_urls.is_empty() && kafka_brokers.is_some() {
+            error!("Swarm mode and producing to Kafka are mutually exclusive options");
+            error!("Please choose either swarm mode or producing to Kafka.");
+            return;
+        }
+
         let disable_state_db = parse_disable_state_db(args);
+
+        let frame_buffer = parse_frame_buffer(args, DEFAULT_CHANNEL_BUFFER);
+        let backpressure_policy = match parse_backpressure_policy(args) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Invalid backpressure policy: {}", e.to_string());
+                return;
+            }
+        };
         
         let (reload_signaler, mut reload_signal) = mpsc::unbounded_channel::<()>();
         let (end_session_signaler, mut end_session_signal) = mpsc::unbounded_channel::<EndSessionReason>();
         let (change_event_tx, mut change_event_rx) = mpsc::channel::<GroundStationChangeEvent>(DEFAULT_CHANNEL_BUFFER);
+        let (change_event_stream_tx, _) = broadcast::channel::<GroundStationChangeEvent>(DEFAULT_CHANGE_EVENT_STREAM_BUFFER);
+        let change_event_stream_tx = Data::new(change_event_stream_tx);
         
         let Ok(mut interrupt_signal) = signal(SignalKind::interrupt()) else {
             error!("Failed to register interrupt signal");
             return;
         };
 
-        if let Err(e) = module.parse_arguments(args) {
+        if let Err(e) = module.parse_arguments(args).await {
             error!("Failed to parse arguments: {}", e.to_string());
             return;    
         }
@@ -207,7 +449,13 @@ impl ModuleManager {
                 None 
             } else { 
                 Some(state_db_url.to_string()) 
-            }
+            },
+            parse_summary_cache_ttl(args),
+            parse_state_db_read_pool(args),
+            parse_state_db_auto_recover(args),
+            parse_min_event_interval(args),
+            coord_precision,
+            parse_keep_broadcast(args)
         ).await {
             Ok(v) => Data::new(RwLock::new(v)),
             Err(e) => {
@@ -222,12 +470,14 @@ impl ModuleManager {
                     reload_signaler,
                     end_session_signaler,
                     change_event_tx,
-                    swarm_url.is_some(),
+                    http_client.clone(),
+                    !swarm_urls.is_empty(),
                     disable_api_control,
-                    api_token,
+                    api_token.as_ref(),
                     vec![
                         (PROP_SESSION_TIMEOUT_SEC, json!(session_timeout_secs)),
-                        (PROP_SESSION_INTERMISSION_SEC, json!(session_intermission_secs))
+                        (PROP_SESSION_INTERMISSION_SEC, json!(session_intermission_secs)),
+                        (PROP_ACTIVE_FREQ_TTL_SEC, json!(active_freq_ttl_secs))
                     ]    
                 )
             )
@@ -241,20 +491,52 @@ impl ModuleManager {
                 json!(Vec::new() as Vec<u64>), 
                 validate_listening_bands
             );
+            settings.add_prop_with_validator(
+                PROP_ACTIVE_FREQ_TTL_SEC.to_string(),
+                json!(active_freq_ttl_secs),
+                validate_active_freq_ttl_sec
+            );
         }
         
         let cancel_token = CancellationToken::new();
+        if let Some(interval) = parse_auto_vacuum_interval(args) {
+            if !disable_state_db {
+                info!("State DB auto vacuum enabled, every {} hours", interval.as_secs() / 3600);
+                create_vacuum_task(state_db.clone(), interval, cancel_token.clone());
+            }
+        }
+        if let Some(interval) = state_db.read().await.summary_cache().read().await.refresh_interval() {
+            if !disable_state_db {
+                create_summary_cache_task(state_db.clone(), interval, cancel_token.clone());
+            }
+        }
         let http_cancel_token = cancel_token.clone();
         let http_state_db = state_db.clone();
         let http_module_settings = module_settings.clone();
+        let http_change_event_stream_tx = change_event_stream_tx.clone();
+        let http_es_index_target = elastic_url.as_ref().and_then(|es_url| {
+            match EsIndexTarget::new(es_url, validate_es_cert, http_timeout, user_agent.as_str(), &elastic_index) {
+                Ok(x) => Some(Data::new(x)),
+                Err(e) => {
+                    warn!("Failed to create ES client for the API to {}: {}", es_url, e.to_string());
+                    None
+                }
+            }
+        });
         
-        let http_thread = tokio::spawn(async move {
+        let mut http_thread = tokio::spawn(async move {
             let restricted_origin = format!("http://{}:{}", listen_host, listen_port);
             
             let server = HttpServer::new(move || {
                 App::new()
                     .app_data(http_state_db.clone())
                     .app_data(http_module_settings.clone())
+                    .app_data(http_change_event_stream_tx.clone())
+                    .configure(|cfg| {
+                        if let Some(ref target) = http_es_index_target {
+                            cfg.app_data(target.clone());
+                        }
+                    })
                     .wrap(middleware::DefaultHeaders::new().add(
                         (
                             "Access-Control-Allow-Origin", 
@@ -266,6 +548,7 @@ impl ModuleManager {
                         )
                     ))
                     .configure(services::config)
+                    .configure(|cfg| if !disable_db_export { services::config_db_export(cfg) })
                     .configure(server_services::config)
             })
                 .bind((listen_host.clone(), listen_port))
@@ -283,142 +566,304 @@ impl ModuleManager {
             }
         });
 
-        let (tx, mut rx) = mpsc::channel::<CommonFrame>(DEFAULT_CHANNEL_BUFFER);
+        let (tx, mut rx) = frame_channel(frame_buffer, backpressure_policy);
         
         let processor_cancel_token = cancel_token.clone();
+        let processor_module_settings = module_settings.clone();
+        let frame_counters = module_settings.read().await.status.frames.clone();
+        let processor_frame_counters = frame_counters.clone();
 
-        let processor_thread = tokio::spawn(async move {
+        let mut processor_thread = tokio::spawn(async move {
             let frames_batch: Data<Mutex<Vec<CommonFrame>>> = Data::new(Mutex::new(Vec::new()));
             let mut batcher: Option<JoinHandle<()>> = None;
 
-            let mut swarm_target: Option<String> = None;
-            let mut swarm_stream: Option<TcpStream> = None;
-            
-            if let Some(ref url) = swarm_url {
-                swarm_target = Some(format!(
-                    "{}:{}",
-                    url.host_str().unwrap_or("0.0.0.0"),
-                    url.port().unwrap_or(0)
-                ));
-            }
+            let mut swarm_targets: Vec<SwarmTarget> = swarm_urls
+                .iter()
+                .map(|x| SwarmTarget::new(x, swarm_token.as_ref(), swarm_msgpack))
+                .collect();
 
-            if let Some(ref target) = swarm_target {
+            // NOTE: waits for every target up front, targets still unreachable afterwards keep retrying while frames flow
+            if !swarm_targets.is_empty() {
                 let start = Instant::now();
-                let mut wait_secs = 1;
-                
-                while start.elapsed() < Duration::from_secs(DEFAULT_INITIAL_SWARM_CONNECT_TIMEOUT_SECS) {
-                    debug!("Attempting to connect to Swarm target at {}", target);
 
-                    select! {
-                        result = TcpStream::connect(target) => {
-                            match result {
-                                Ok(stream) => {
-                                    swarm_stream = Some(stream);
-                                    debug!("Swarm target successfully connected");
-                                    break;
-                                }
-                                Err(e) => {
-                                    error!("Failed to connect to swarm target, trying again in {} seconds: {}", wait_secs, e.to_string());
-                                }
-                            }
-                        }
-                        _ = processor_cancel_token.cancelled() => {
-                            info!("Processor thread got cancel request during initial connect");
-                            return;
-                        }
-                    }
+                while start.elapsed() < Duration::from_secs(DEFAULT_INITIAL_SWARM_CONNECT_TIMEOUT_SECS) {
+                    for target in swarm_targets.iter_mut().filter(|x| !x.is_connected()) {
+                        debug!("Attempting to connect to Swarm target at {}", target.addr());
 
-                    if swarm_stream.is_none() {
                         select! {
-                            _ = time::sleep(Duration::from_secs(wait_secs)) => {
-                                wait_secs *= 2;
+                            result = target.connect() => {
+                                match result {
+                                    Ok(_) => debug!("Swarm target {} successfully connected", target.addr()),
+                                    Err(e) => error!("Failed to connect to swarm target {}, trying again in {} seconds: {}", target.addr(), target.retry_wait_secs(), e.to_string()),
+                                }
                             }
                             _ = processor_cancel_token.cancelled() => {
-                                info!("Processor thread got cancel request while waiting for next retry");
+                                info!("Processor thread got cancel request during initial connect");
                                 return;
                             }
                         }
                     }
+                    processor_module_settings.write().await.status.swarm_connected = swarm_targets.iter().any(|x| x.is_connected());
+
+                    if swarm_targets.iter().all(|x| x.is_connected()) {
+                        break;
+                    }
+
+                    let wait_secs = swarm_targets
+                        .iter()
+                        .filter(|x| !x.is_connected())
+                        .map(|x| x.retry_wait_secs())
+                        .min()
+                        .unwrap_or(1);
+                    select! {
+                        _ = time::sleep(Duration::from_secs(wait_secs)) => {}
+                        _ = processor_cancel_token.cancelled() => {
+                            info!("Processor thread got cancel request while waiting for next retry");
+                            return;
+                        }
+                    }
                 }
             }
 
+            let swarm_targets: Vec<SwarmSink> = swarm_targets
+                .into_iter()
+                .map(|x| SwarmSink::new(x, DEFAULT_SWARM_QUEUE_SIZE))
+                .collect();
+
             let mut es_client: Option<Elasticsearch> = None;
             if let Some(ref mut es_url) = elastic_url {
-                match create_es_client(es_url, validate_es_cert) {
+                match create_es_client(es_url, validate_es_cert, http_timeout, user_agent.as_str()) {
                     Ok(client) => es_client = Some(client),
                     Err(e) => warn!("Failed to create ES client to {}: {}", es_url, e.to_string())
                 }
             }
+
+            let mut kafka_producer: Option<KafkaProducer> = None;
+            if let Some(ref brokers) = kafka_brokers {
+                match create_kafka_producer(brokers) {
+                    Ok(producer) => kafka_producer = Some(producer),
+                    Err(e) => warn!("Failed to create Kafka producer to {}: {}", brokers, e.to_string())
+                }
+            }
             
+            // NOTE: lives in the processor loop rather than the session loop, since the session loop's line reads are not cancel safe
+            let heartbeat_period = Duration::from_secs(heartbeat_secs.max(1));
+            let mut heartbeat = time::interval_at(Instant::now() + heartbeat_period, heartbeat_period);
+
+            let print_stats_period = Duration::from_secs(print_stats_interval_secs.max(1));
+            let mut print_stats = time::interval_at(Instant::now() + print_stats_period, print_stats_period);
+            let mut last_stats_frames: u64 = 0;
+            let mut last_stats_at = Instant::now();
+
+            let mut acars_reassembler = if reassemble_acars {
+                Some(AcarsReassembler::new(Duration::from_secs(reassemble_acars_timeout_secs)))
+            } else {
+                None
+            };
+
+            let webhook = webhook_url.map(|url| WebhookSink::new(http_client, url, webhook_headers, webhook_batch, webhook_queue));
+
+            let mut change_events: Vec<GroundStationChangeEvent> = Vec::new();
+            let mut change_events_flush_at = Instant::now();
+
             loop {
                 select! {
-                    Some(mut frame) = rx.recv() => {
-                        if let Some(ref acars) = frame.acars {
-                            // TODO[ACARS]: use acars-decoder-rust to decode ACARS content and save it to frame.indexed
+                    Some(frame) = rx.recv() => {
+                        let mut frames = vec![frame];
+                        if let Some(ref mut reassembler) = acars_reassembler {
+                            if let Some(reassembled) = reassembler.push(&frames[0]) {
+                                frames.push(reassembled);
+                            }
                         }
+
+                        for mut frame in frames.into_iter() {
+                            if redact_acars_text {
+                                frame.redact_acars_text();
+                            }
+
+                            if let Some(ref acars) = frame.acars {
+                                frame.indexed.label_description = describe_label(&acars.label, acars.sublabel.as_deref())
+                                    .map(|x| x.to_string());
+
+                                // TODO[ACARS]: use acars-decoder-rust to decode ACARS content and save it to frame.indexed
+                            }
+
+                            // NOTE: logged after redaction so --redact-acars-text also keeps message text out of the logs
+                            trace!("{:?}", frame);
                         
-                        if let Some(ref mut stream) = swarm_stream {
-                            let raw_json = match serde_json::to_string(&frame) {
-                                Ok(v) => v,
-                                Err(e) => {
-                                    error!("Failed to serialize CFF: {}", e.to_string());
-                                    continue;
+                            if !swarm_targets.is_empty() {
+                                let message = match encode_swarm_message(&frame, swarm_msgpack) {
+                                    Ok(v) => v,
+                                    Err(e) => {
+                                        error!("Failed to serialize CFF: {}", e.to_string());
+                                        continue;
+                                    }
+                                };
+
+                                // NOTE: For now, just skip frames for targets whose queue is full
+                                let message = Arc::new(message);
+                                for target in swarm_targets.iter() {
+                                    match target.send(message.clone()) {
+                                        Ok(true) => {
+                                            debug!("Swarm target {} queue full, {} frames dropped so far", target.addr(), target.dropped());
+                                            processor_frame_counters.swarm_dropped_frames.store(
+                                                swarm_targets.iter().map(|x| x.dropped()).sum(),
+                                                Ordering::Relaxed,
+                                            );
+                                        }
+                                        Ok(false) => {}
+                                        Err(e) => warn!("Failed to queue frame for Swarm target {}: {}", target.addr(), e.to_string()),
+                                    }
                                 }
-                            };
 
-                            if let Err(e) = stream.write_all(format!("{}\n", raw_json).as_bytes()).await {
-                                // NOTE: For now, just skip frames if we fail to write packet to swarm server
-                                
-                                match e.kind() {
-                                    io::ErrorKind::BrokenPipe => {
-                                        match TcpStream::connect(swarm_target.as_ref().unwrap()).await {
-                                            Ok(v) => swarm_stream = Some(v),
-                                            Err(e) => {
-                                                warn!("Failed to connect to swarm target: {}", e.to_string());
-                                            }  
-                                        };
+                                let swarm_connected = swarm_targets.iter().any(|x| x.is_connected());
+                                if processor_module_settings.read().await.status.swarm_connected != swarm_connected {
+                                    processor_module_settings.write().await.status.swarm_connected = swarm_connected;
+                                }
+                            } else if skip_empty_frames && frame.is_empty() {
+                                trace!("Skipping state DB update for empty frame");
+                            } else {
+                                let mut state_db = state_db.write().await;
+                                if let Err(e) = state_db.update(&frame).await {
+                                    warn!("Failed to update state DB with frame: {}", e.to_string());
+                                }
+                            }
+                        
+                            if let Some(ref producer) = kafka_producer {
+                                if let Err(e) = produce_frame(producer, &kafka_topic, &frame) {
+                                    warn!("{}", e.to_string());
+                                }
+                            }
+
+                            if let Some(ref webhook) = webhook {
+                                match webhook.send(&frame) {
+                                    Ok(true) => {
+                                        debug!("Webhook queue full, {} frames dropped so far", webhook.dropped());
+                                        processor_frame_counters.webhook_dropped_frames.store(webhook.dropped(), Ordering::Relaxed);
                                     }
-                                    _ => warn!("Failed to proxy frame to Swarm target: {}", e.to_string())
+                                    Ok(false) => {}
+                                    Err(e) => warn!("Failed to queue frame for webhook: {}", e.to_string()),
                                 }
                             }
-                        } else {
-                            let state_db = state_db.write().await;
-                            if let Err(e) = state_db.update(&frame).await {
-                                warn!("Failed to update state DB with frame: {}", e.to_string());
+
+                            if let Some(ref client) = es_client {
+                                let mut batch = frames_batch.lock().await;
+
+                                if batch.len() == 0 {
+                                    let frames_batch = frames_batch.clone();
+
+                                    batcher = Some(
+                                        create_es_batch_task(
+                                            client,
+                                            &elastic_index, 
+                                            &es_exclude_fields,
+                                            coord_precision,
+                                            frames_batch, 
+                                            Duration::from_millis(DEFAULT_BATCH_WAIT_MS)
+                                        )
+                                    );
+                                }
+
+                                batch.push(frame);
                             }
                         }
-                        
-                        if let Some(ref client) = es_client {
-                            let mut batch = frames_batch.lock().await;
-
-                            if batch.len() == 0 {
-                                let frames_batch = frames_batch.clone();
-
-                                batcher = Some(
-                                    create_es_batch_task(
-                                        client,
-                                        &elastic_index, 
-                                        frames_batch, 
-                                        Duration::from_millis(DEFAULT_BATCH_WAIT_MS)
-                                    )
-                                );
+                    }
+                    _ = print_stats.tick(), if print_stats_interval_secs > 0 => {
+                        let total_frames = processor_frame_counters.total_frames.load(Ordering::Relaxed);
+                        let (listening_band, session_uptime_secs) = {
+                            let settings = processor_module_settings.read().await;
+                            (
+                                settings.props.get(PROP_LISTENING_BAND).cloned().unwrap_or(serde_json::Value::Null),
+                                settings.status.session_uptime_secs(),
+                            )
+                        };
+                        let frames_per_sec = total_frames.saturating_sub(last_stats_frames) as f64 / last_stats_at.elapsed().as_secs_f64().max(1.0);
+                        last_stats_frames = total_frames;
+                        last_stats_at = Instant::now();
+
+                        // NOTE: the batcher holds the batch lock for the whole bulk request, so a busy lock means it is indexing
+                        let es_status = match (es_client.is_some(), frames_batch.try_lock()) {
+                            (false, _) => String::from("disabled"),
+                            (true, Ok(batch)) => format!("{} frames pending", batch.len()),
+                            (true, Err(_)) => String::from("indexing"),
+                        };
+
+                        info!(
+                            "Stats: {:.2} frames/s, total frames = {}, band = {}, session uptime = {}s, ES batch = {}",
+                            frames_per_sec,
+                            total_frames,
+                            listening_band,
+                            session_uptime_secs.unwrap_or(0),
+                            es_status
+                        );
+                    }
+                    _ = heartbeat.tick(), if heartbeat_secs > 0 => {
+                        let message = {
+                            let settings = processor_module_settings.read().await;
+                            HeartbeatMessage {
+                                heartbeat: Heartbeat {
+                                    ts: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+                                    module: module_id.to_string(),
+                                    listening_band: settings.props.get(PROP_LISTENING_BAND).cloned().unwrap_or(serde_json::Value::Null),
+                                    session_uptime_secs: settings.status.session_uptime_secs(),
+                                    session_frames: processor_frame_counters.session_frames.load(Ordering::Relaxed),
+                                    uptime_secs: started.elapsed().as_secs(),
+                                },
+                            }
+                        };
+                        info!(
+                            "Heartbeat: band = {}, session uptime = {}s, session frames = {}, uptime = {}s",
+                            message.heartbeat.listening_band,
+                            message.heartbeat.session_uptime_secs.unwrap_or(0),
+                            message.heartbeat.session_frames,
+                            message.heartbeat.uptime_secs
+                        );
+
+                        if !swarm_targets.is_empty() {
+                            match encode_swarm_message(&message, swarm_msgpack) {
+                                Ok(encoded) => {
+                                    let encoded = Arc::new(encoded);
+                                    for target in swarm_targets.iter() {
+                                        if let Err(e) = target.send(encoded.clone()) {
+                                            warn!("Failed to queue heartbeat for Swarm target {}: {}", target.addr(), e.to_string());
+                                        }
+                                    }
+                                }
+                                Err(e) => error!("Failed to serialize heartbeat: {}", e.to_string()),
                             }
+                        }
 
-                            batch.push(frame);
+                        if let Some(ref webhook) = webhook {
+                            if let Err(e) = webhook.send(&message) {
+                                warn!("Failed to queue heartbeat for webhook: {}", e.to_string());
+                            }
                         }
                     }
-                    Some(ref change_event) = change_event_rx.recv() => {
-                        let state_db = state_db.write().await;
-                        if let Err(e) = state_db.handle_gs_change_event(change_event).await {
-                            warn!("Failed to write ground station change even to state DB: {}", e.to_string());
+                    Some(change_event) = change_event_rx.recv() => {
+                        // NOTE: sending only fails when no client is subscribed to the event stream
+                        let _ = change_event_stream_tx.send(change_event.clone());
+
+                        if change_events.is_empty() {
+                            change_events_flush_at = Instant::now() + gs_change_batch_wait;
+                        }
+                        change_events.push(change_event);
+                        if change_events.len() >= gs_change_batch_size || gs_change_batch_wait.is_zero() {
+                            flush_gs_change_events(&state_db, &mut change_events).await;
                         }
                     }
+                    _ = time::sleep_until(change_events_flush_at), if !change_events.is_empty() => {
+                        flush_gs_change_events(&state_db, &mut change_events).await;
+                    }
                     _ = processor_cancel_token.cancelled() => {
                         info!("Processor thread got cancel request");
                         break;
                     }
                 }
             }
+
+            if !change_events.is_empty() {
+                flush_gs_change_events(&state_db, &mut change_events).await;
+            }
             
             if let Some(batcher) = batcher {
                 debug!("Batcher is active, waiting for completion before exiting to prevent data loss");
@@ -427,15 +872,33 @@ impl ModuleManager {
                 }
             }
 
-            if let Some(ref mut stream) = swarm_stream {
-                if let Err(e) = stream.shutdown().await {
-                    warn!("Failed to shutdown Swarm connection: {}", e.to_string());
+            if let Some(webhook) = webhook {
+                debug!("Waiting for queued webhook frames to be sent before exiting");
+                webhook.close().await;
+            }
+
+            if let Some(producer) = kafka_producer {
+                debug!("Flushing outstanding Kafka messages before exiting");
+
+                // NOTE: flush blocks the calling thread until librdkafka drains its queue, so it runs on the
+                //       blocking pool and never waits longer than the shutdown timeout allows
+                let flush_timeout = Duration::from_secs(DEFAULT_KAFKA_FLUSH_TIMEOUT_SECS).min(shutdown_timeout);
+                match tokio::task::spawn_blocking(move || producer.flush(flush_timeout)).await {
+                    Ok(Err(e)) => warn!("Failed to flush outstanding Kafka messages: {}", e.to_string()),
+                    Err(e) => warn!("Kafka flush task failed: {}", e.to_string()),
+                    Ok(Ok(_)) => {}
                 }
             }
+
+            for target in swarm_targets.into_iter() {
+                target.close().await;
+            }
         });
         
         let mut should_run = true;
         let mut reason = EndSessionReason::None;
+        let mut consecutive_crashes: u32 = 0;
+        let mut last_future_warn: Option<Instant> = None;
 
         while should_run {
 
@@ -444,6 +907,7 @@ impl ModuleManager {
                 Err(e) => {
                     error!("Failed to start session: {}", e.to_string());
                     reason = EndSessionReason::ProcessStartError;
+                    module_settings.write().await.status.record_end_reason(reason);
 
                     select! {
                         _ = sleep(Duration::from_secs(DEFAULT_FAILED_SESSION_START_WAIT_SECS)) => {}
@@ -456,8 +920,11 @@ impl ModuleManager {
                     continue;
                 }    
             };
+            module_settings.write().await.status.start_session();
             
+            let session_started = Instant::now();
             let mut since_last_msg = Instant::now();
+            let mut since_last_frame = Instant::now();
             
             loop {
                 let mut raw_msg = String::new();
@@ -493,20 +960,52 @@ impl ModuleManager {
                                     break    
                                 }
 
-                                if !disable_print_frame {
+                                if !disable_print_frame && print_sampler.should_print() {
                                     println!("{}", raw_msg.trim());
                                 }                        
                                 
-                                let frame = match module.process_message(session.get_listening_band(), &raw_msg).await {
+                                let mut frame = match module.process_message(session.get_listening_band(), &raw_msg).await {
                                     Ok(v) => v,
                                     Err(e) => {
                                         error!("Malformed frame, could not convert to common frame format: {}", e.to_string());
                                         continue;
                                     }
                                 };
-                                info!("{:?}", frame);
-                                if let Err(e) = tx.send(frame).await {
-                                    error!("Failed to send common frame to processing thread: {}", e.to_string());                                    
+                                since_last_frame = Instant::now();
+                                if drop_reserved_entities && frame.has_reserved_entity() {
+                                    debug!("Dropping frame with a reserved-type entity");
+                                    since_last_msg = Instant::now();
+                                    continue;
+                                }
+                                if max_future_skew_secs > 0 {
+                                    let secs_ahead = frame.secs_ahead_of(chrono::Utc::now());
+                                    if secs_ahead > max_future_skew_secs as i64 {
+                                        let rejected = frame_counters.future_frames_rejected.fetch_add(1, Ordering::Relaxed) + 1;
+                                        if last_future_warn.map_or(true, |x| x.elapsed() >= Duration::from_secs(DEFAULT_FUTURE_WARN_INTERVAL_SECS)) {
+                                            warn!(
+                                                "Rejecting frame timestamped {}s in the future, check the system clock ({} rejected so far)",
+                                                secs_ahead, rejected
+                                            );
+                                            last_future_warn = Some(Instant::now());
+                                        }
+                                        since_last_msg = Instant::now();
+                                        continue;
+                                    }
+                                }
+                                if embed_raw {
+                                    frame.raw = Some(raw_msg.trim().to_string());
+                                }
+                                if normalize_direction {
+                                    frame.normalize_direction();
+                                }
+                                frame_counters.record_frame();
+                                match tx.send(frame).await {
+                                    Ok(true) => {
+                                        debug!("Frame buffer full, {} frames dropped so far", tx.dropped());
+                                        frame_counters.dropped_frames.store(tx.dropped(), Ordering::Relaxed);
+                                    }
+                                    Ok(false) => {}
+                                    Err(e) => error!("Failed to send common frame to processing thread: {}", e.to_string()),
                                 }
                                 
                                 since_last_msg = Instant::now();
@@ -521,6 +1020,13 @@ impl ModuleManager {
                                     if inner_err.to_string() == SESSION_SCHEDULED_END {
                                         debug!("HFDL session ended by schedule");
                                         reason = EndSessionReason::SessionEnd;
+                                    } else if inner_err.to_string() == SESSION_DRY_RUN_END {
+                                        should_run = false;
+                                        reason = EndSessionReason::DryRun;
+                                    } else if inner_err.to_string() == SESSION_INPUT_CLOSED_END {
+                                        info!("Session input closed, exiting");
+                                        should_run = false;
+                                        reason = EndSessionReason::InputClosed;
                                     }
                                 }
 
@@ -533,6 +1039,12 @@ impl ModuleManager {
                             }
                         };
                     }
+                    // NOTE: unlike the session timeout, only reset by valid frames, so decoder chatter cannot keep a wedged session alive
+                    _ = time::sleep_until(since_last_frame + Duration::from_secs(no_frame_watchdog_secs)), if no_frame_watchdog_secs > 0 => {
+                        warn!("No valid frame processed for {} seconds, restarting session", no_frame_watchdog_secs);
+                        reason = EndSessionReason::NoFrameWatchdog;
+                        break;
+                    }
                     end_session_reason = end_session_signal.recv() => {
                         reason = end_session_reason.unwrap_or(EndSessionReason::UserAPIControl);
                         debug!("Got request to end current session: {:?}", reason);
@@ -549,14 +1061,14 @@ impl ModuleManager {
                         {
                             let settings = module_settings.read().await;
 
-                            match settings.props.get(PROP_SESSION_TIMEOUT_SEC) {
-                                Some(v) => session_timeout_secs = v.as_u64().unwrap_or(module.default_session_timeout_secs()),
-                                None => warn!("Failed to find session_timeout_secs key in module settings")
+                            match settings.get_u64(PROP_SESSION_TIMEOUT_SEC) {
+                                Ok(v) => session_timeout_secs = v,
+                                Err(e) => warn!("Failed to reload session timeout: {}", e.to_string())
                             }
 
-                            match settings.props.get(PROP_SESSION_INTERMISSION_SEC) {
-                                Some(v) => session_intermission_secs = v.as_u64().unwrap_or(DEFAULT_SESSION_INTERMISSION_SECS),
-                                None => warn!("Failed to find session_intermission_secs key in module settings")
+                            match settings.get_u64(PROP_SESSION_INTERMISSION_SEC) {
+                                Ok(v) => session_intermission_secs = v,
+                                Err(e) => warn!("Failed to reload session intermission: {}", e.to_string())
                             }
 
                             info!("Module session timeout, intermission wait time props reloaded");
@@ -571,13 +1083,61 @@ impl ModuleManager {
                 } 
             }
 
+            let mut crashed = false;
+            if matches!(reason, EndSessionReason::ReadEOF) {
+                match session.exit_status().await {
+                    Some(status) if status.success() => info!("Decoder process exited cleanly"),
+                    Some(status) => {
+                        error!("Decoder process crashed: {}", status);
+                        crashed = true;
+                    }
+                    None => {
+                        error!("Decoder process closed its output but did not exit");
+                        crashed = true;
+                    }
+                }
+            }
+
             session.end(reason).await;
+            info!("Session ended: reason = {}", reason.name());
+
+            // NOTE: a session that survived longer than the max backoff is not considered part of a crash loop
+            if !crashed || session_started.elapsed() > Duration::from_secs(DEFAULT_CRASH_BACKOFF_MAX_SECS) {
+                consecutive_crashes = 0;
+            }
+            if crashed {
+                consecutive_crashes += 1;
+            }
+            {
+                let mut settings = module_settings.write().await;
+                settings.status.consecutive_crashes = consecutive_crashes;
+                settings.status.record_end_reason(reason);
+            }
+
+            if should_run && consecutive_crashes > 0 {
+                let backoff_secs = DEFAULT_CRASH_BACKOFF_BASE_SECS
+                    .saturating_mul(1 << (consecutive_crashes - 1).min(16))
+                    .min(DEFAULT_CRASH_BACKOFF_MAX_SECS);
+                warn!("Decoder crashed {} time(s) in a row, waiting {} seconds before restarting", consecutive_crashes, backoff_secs);
+
+                select! {
+                    _ = sleep(Duration::from_secs(backoff_secs)) => {}
+                    _ = interrupt_signal.recv() => {
+                        warn!("Got interrupt during crash backoff, exiting session cleanly...");
+
+                        break;
+                    }
+                }
+            }
             
             if should_run && session_intermission_secs > 0 {
-                debug!("Session ended, waiting for {} seconds before continuing", session_intermission_secs);
+                let intermission = Duration::try_from_secs_f64(
+                    session_intermission_secs as f64 * (1.0 + session_intermission_jitter * rand::thread_rng().gen::<f64>())
+                ).unwrap_or(Duration::from_secs(session_intermission_secs));
+                debug!("Session ended, waiting for {:.1} seconds before continuing", intermission.as_secs_f64());
                 
                 select! {
-                    _ = sleep(Duration::from_secs(session_intermission_secs)) => {}
+                    _ = sleep(intermission) => {}
                     _ = interrupt_signal.recv() => {
                         warn!("Got interrupt during session intermission, exiting session cleanly...");
 
@@ -590,10 +1150,124 @@ impl ModuleManager {
         info!("Sending cancel request to spawned threads");
         cancel_token.cancel();
 
-        #[allow(unused_must_use)] {
-            tokio::join!(http_thread, processor_thread);
+        if time::timeout(shutdown_timeout, async { tokio::join!(&mut http_thread, &mut processor_thread) }).await.is_err() {
+            warn!("Spawned threads did not exit within {} seconds, aborting remaining tasks", shutdown_timeout.as_secs());
+            http_thread.abort();
+            processor_thread.abort();
         }
         
         info!("Exiting...");
     }
 }
+
+async fn flush_gs_change_events(state_db: &Data<RwLock<StateDB>>, change_events: &mut Vec<GroundStationChangeEvent>) {
+    let state_db = state_db.write().await;
+    if let Err(e) = state_db.handle_gs_change_events(change_events).await {
+        warn!("Failed to write some of {} ground station change events to state DB: {}", change_events.len(), e.to_string());
+    }
+    change_events.clear();
+}
+
+// NOTE: MessagePack messages are prefixed with their length as a big-endian u32, JSON messages are newline delimited
+fn encode_swarm_message<T: Serialize>(message: &T, msgpack: bool) -> Result<Vec<u8>, io::Error> {
+    if !msgpack {
+        let mut encoded = serde_json::to_vec(message)?;
+        encoded.push(b'\n');
+        return Ok(encoded);
+    }
+
+    let payload = match rmp_serde::to_vec_named(message) {
+        Ok(x) => x,
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+    };
+    let Ok(len) = u32::try_from(payload.len()) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("MessagePack message too large: {} bytes", payload.len())));
+    };
+
+    let mut encoded = Vec::with_capacity(payload.len() + 4);
+    encoded.extend_from_slice(&len.to_be_bytes());
+    encoded.extend(payload);
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::server::read_length_prefixed;
+
+    use super::*;
+
+    const MAX_FRAME_BYTES: u64 = 1048576;
+
+    fn test_frame() -> CommonFrame {
+        serde_json::from_value(json!({
+            "timestamp": "2023-07-01T00:00:00.000Z",
+            "freq": 8.977,
+            "signal": -20.5,
+            "err": false,
+            "paths": [],
+            "app": { "name": "test", "version": "0.0.0" },
+            "src": { "type": "Aircraft", "icao": "ABCDEF", "tail": "N12345" },
+            "dst": { "type": "Ground station", "id": 2, "gs": "Reykjavik" },
+            "acars": { "mode": "2", "more": false, "label": "H1", "text": "HELLO" },
+            "indexed": { "timestamp": "2023-07-01T00:00:00.000Z" },
+            "metadata": {},
+        }))
+        .unwrap()
+    }
+
+    // NOTE: mirrors the server, which reads the length prefix and decodes the payload back to JSON
+    async fn read_msgpack_message(encoded: &Vec<u8>) -> Value {
+        let mut reader = encoded.as_slice();
+        let payload = read_length_prefixed(&mut reader, MAX_FRAME_BYTES).await.unwrap().unwrap();
+        assert!(read_length_prefixed(&mut reader, MAX_FRAME_BYTES).await.unwrap().is_none());
+
+        rmp_serde::from_slice::<Value>(&payload).unwrap()
+    }
+
+    #[tokio::test]
+    async fn msgpack_frame_round_trips_through_server_reader() {
+        let frame = test_frame();
+        let encoded = encode_swarm_message(&frame, true).unwrap();
+
+        let decoded = read_msgpack_message(&encoded).await;
+        let decoded = serde_json::from_str::<CommonFrame>(&decoded.to_string()).unwrap();
+
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&frame).unwrap());
+    }
+
+    #[tokio::test]
+    async fn msgpack_heartbeat_round_trips_and_is_not_a_frame() {
+        let message = HeartbeatMessage {
+            heartbeat: Heartbeat {
+                ts: String::from("2023-07-01T00:00:00Z"),
+                module: String::from("hfdl"),
+                listening_band: json!([8912, 8927, 8936]),
+                session_uptime_secs: Some(60),
+                session_frames: 12,
+                uptime_secs: 3600,
+            },
+        };
+        let encoded = encode_swarm_message(&message, true).unwrap();
+
+        let decoded = read_msgpack_message(&encoded).await.to_string();
+        assert!(serde_json::from_str::<CommonFrame>(&decoded).is_err());
+
+        let decoded = serde_json::from_str::<HeartbeatMessage>(&decoded).unwrap();
+        assert_eq!(decoded.heartbeat.module, "hfdl");
+        assert_eq!(decoded.heartbeat.listening_band, json!([8912, 8927, 8936]));
+        assert_eq!(decoded.heartbeat.session_uptime_secs, Some(60));
+        assert_eq!(decoded.heartbeat.session_frames, 12);
+        assert_eq!(decoded.heartbeat.uptime_secs, 3600);
+    }
+
+    #[test]
+    fn json_messages_are_newline_delimited() {
+        let encoded = encode_swarm_message(&test_frame(), false).unwrap();
+
+        assert_eq!(encoded.last(), Some(&b'\n'));
+        assert_eq!(encoded.iter().filter(|&&x| x == b'\n').count(), 1);
+        assert!(serde_json::from_slice::<CommonFrame>(&encoded).is_ok());
+    }
+}
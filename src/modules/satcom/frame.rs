@@ -0,0 +1,151 @@
+use serde::Deserialize;
+
+use crate::common::frame;
+use crate::utils::normalize_tail;
+
+#[derive(Debug, Deserialize)]
+pub struct App {
+    pub name: String,
+    pub ver: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Timestamp {
+    pub sec: i64,
+
+    #[serde(default)]
+    pub usec: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Endpoint {
+    pub addr: String,
+
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Acars {
+    pub mode: String,
+    pub label: String,
+    pub blk_id: Option<String>,
+    pub ack: Option<String>,
+    pub msg_num: Option<String>,
+    pub reg: Option<String>,
+    pub flight: Option<String>,
+    pub sublabel: Option<String>,
+    pub mfi: Option<String>,
+    pub msg_text: Option<String>,
+
+    #[serde(default)]
+    pub more: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Isu {
+    pub src: Endpoint,
+    pub dst: Endpoint,
+
+    pub acars: Option<Acars>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Frame {
+    pub app: Option<App>,
+    pub t: Timestamp,
+
+    // NOTE: JAERO reports the channel frequency in MHz when it is known
+    pub freq: Option<f64>,
+
+    #[serde(default)]
+    pub level: f64,
+
+    pub isu: Isu,
+}
+
+impl Endpoint {
+    pub fn is_ground_station(&self) -> bool {
+        self.kind.to_lowercase().contains("ground")
+    }
+}
+
+impl Frame {
+    pub fn from_ground_station(&self) -> bool {
+        self.isu.src.is_ground_station()
+    }
+
+    fn aircraft(&self) -> &Endpoint {
+        if self.from_ground_station() {
+            &self.isu.dst
+        } else {
+            &self.isu.src
+        }
+    }
+
+    fn ground_earth_station(&self) -> &Endpoint {
+        if self.from_ground_station() {
+            &self.isu.src
+        } else {
+            &self.isu.dst
+        }
+    }
+
+    pub fn ground_station_id(&self) -> Option<u32> {
+        u32::from_str_radix(self.ground_earth_station().addr.trim(), 16).ok()
+    }
+
+    pub fn aircraft_entity(&self) -> frame::Entity {
+        let acars = self.isu.acars.as_ref();
+
+        frame::Entity {
+            kind: String::from("Aircraft"),
+            icao: Some(self.aircraft().addr.trim().to_uppercase()).filter(|x| !x.is_empty()),
+            gs: None,
+            id: None,
+            callsign: acars
+                .and_then(|x| x.flight.as_ref())
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty()),
+            tail: acars
+                .and_then(|x| x.reg.as_ref())
+                .map(|x| normalize_tail(x))
+                .filter(|x| !x.is_empty()),
+            coords: None,
+        }
+    }
+
+    // NOTE: the satellite stands in for the ground station, keyed by the ground earth station's address
+    pub fn ground_station_entity(&self, satellite: &String) -> frame::Entity {
+        frame::Entity {
+            kind: String::from("Ground station"),
+            icao: None,
+            gs: Some(satellite.clone()),
+            id: self.ground_station_id(),
+            callsign: None,
+            tail: None,
+            coords: None,
+        }
+    }
+
+    pub fn to_acars(&self) -> Option<frame::ACARS> {
+        let acars = self.isu.acars.as_ref()?;
+        let msg_num = acars.msg_num.clone().unwrap_or_default();
+
+        Some(frame::ACARS {
+            mode: acars.mode.clone(),
+            more: acars.more,
+            label: acars.label.clone(),
+            ack: acars.ack.clone().filter(|x| !x.is_empty()),
+            blk_id: acars.blk_id.clone().filter(|x| !x.is_empty()),
+            msg_num: msg_num.get(0..3).map(|x| x.to_string()),
+            msg_num_seq: msg_num.get(3..4).map(|x| x.to_string()),
+            tail: acars.reg.clone(),
+            flight: acars.flight.clone(),
+            sublabel: acars.sublabel.clone(),
+            mfi: acars.mfi.clone(),
+            cfi: None,
+            text: acars.msg_text.clone(),
+        })
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::SecondsFormat;
+use log::*;
+use std::collections::HashSet;
+use tokio::io::BufReader;
+
+use actix_web::web::Data;
+use async_trait::async_trait;
+use clap::{arg, ArgMatches, Command};
+use serde_json::json;
+use tokio::io;
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+
+use self::frame::Frame;
+use self::session::{SatcomInput, SatcomSession};
+
+use super::session::{DryRunSession, EndSessionReason};
+use super::settings::ModuleSettings;
+use super::XngModule;
+use crate::common::frame as cff;
+use crate::modules::PROP_LISTENING_BAND;
+use crate::server::db::StateDB;
+use crate::utils::timestamp::split_unix_time_to_utc_datetime;
+
+mod frame;
+mod module;
+mod session;
+
+const SATCOM_COMMAND: &'static str = "satcom";
+
+const DEFAULT_SESSION_TIMEOUT_SECS: u64 = 900;
+const DEFAULT_SATELLITE_NAME: &'static str = "Inmarsat";
+
+const SATCOM_MIN_KHZ: u64 = 1525000;
+const SATCOM_MAX_KHZ: u64 = 1559000;
+
+#[derive(Default)]
+pub struct SatcomModule {
+    name: &'static str,
+    settings: Option<Data<RwLock<ModuleSettings>>>,
+    state_db: Option<Data<RwLock<StateDB>>>,
+
+    listen: Option<String>,
+    satellite: String,
+    bands: Vec<u64>,
+
+    known_ground_stations: HashSet<u32>,
+
+    dry_run: bool,
+}
+
+#[async_trait]
+impl XngModule for SatcomModule {
+    fn id(&self) -> &'static str {
+        self.name
+    }
+
+    fn default_session_timeout_secs(&self) -> u64 {
+        DEFAULT_SESSION_TIMEOUT_SECS
+    }
+
+    fn get_arguments(&self) -> Command {
+        Command::new(SATCOM_COMMAND)
+            .about("Listen to Inmarsat satellite ACARS messages decoded by JAERO")
+            .args(&[
+                arg!(--"tcp-listen" <HOST_PORT> "Accept JAERO's JSON output on this TCP address instead of STDIN"),
+                arg!(--freq <KHZ> "L-band channel frequency in kHz JAERO is tuned to"),
+                arg!(--satellite <NAME> "Satellite name reported as the ground station (default: Inmarsat)"),
+            ])
+    }
+
+    async fn parse_arguments(&mut self, args: &ArgMatches) -> Result<(), io::Error> {
+        self.dry_run = args.get_flag("dry-run");
+
+        if args.get_flag("feed-airframes") || args.get_flag("feed-airframes-manual") {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Feeding airframes.io is not supported for satcom, configure JAERO's own feeder output instead"));
+        }
+
+        let Some(freq) = args.get_one::<String>("freq") else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing required --freq <KHZ> argument"));
+        };
+        let freq = match freq.trim().parse::<u64>() {
+            Ok(x) if x >= SATCOM_MIN_KHZ && x <= SATCOM_MAX_KHZ => x,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid L-band kHz frequency: {}", freq),
+                ))
+            }
+        };
+        self.bands = vec![freq];
+
+        self.listen = args.get_one::<String>("tcp-listen").cloned();
+        self.satellite = args
+            .get_one::<String>("satellite")
+            .unwrap_or(&String::from(DEFAULT_SATELLITE_NAME))
+            .to_string();
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        settings: Data<RwLock<ModuleSettings>>,
+        state_db: Data<RwLock<StateDB>>,
+    ) {
+        self.settings = Some(settings.clone());
+        self.state_db = Some(state_db.clone());
+    }
+
+    // NOTE: satcom has no frequency hopping, every session attaches to the same static band
+    async fn start_session(
+        &mut self,
+        _last_end_reason: EndSessionReason,
+    ) -> Result<Box<dyn super::session::Session>, io::Error> {
+        let settings = self.get_settings()?;
+
+        if self.dry_run {
+            match self.listen {
+                Some(ref addr) => println!("Listening for JAERO JSON on tcp://{}", addr),
+                None => println!("Reading JAERO JSON from STDIN"),
+            }
+            return Ok(Box::new(DryRunSession::new(self.bands.clone())));
+        }
+
+        let input = match self.listen {
+            Some(ref addr) => match TcpListener::bind(addr).await {
+                Ok(x) => SatcomInput::Listener(x),
+                Err(e) => {
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!("Failed to listen on {}: {}", addr, e.to_string()),
+                    ))
+                }
+            },
+            None => SatcomInput::Stdin(BufReader::new(io::stdin())),
+        };
+
+        settings.write().await.set(PROP_LISTENING_BAND, json!(self.bands))?;
+        debug!("New satcom session started, listening: {:?}", self.bands);
+
+        Ok(Box::new(SatcomSession::new(input, self.bands.clone())))
+    }
+
+    async fn process_message(
+        &mut self,
+        _current_band: &Vec<u64>,
+        msg: &str,
+    ) -> Result<crate::common::frame::CommonFrame, io::Error> {
+        let raw_frame = serde_json::from_str::<Frame>(msg)?;
+
+        let Some(arrival_time) = split_unix_time_to_utc_datetime(raw_frame.t.sec, raw_frame.t.usec * 1000) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid arrival time",
+            ));
+        };
+
+        let ground_station = raw_frame.ground_station_entity(&self.satellite);
+        if let Some(gs_id) = ground_station.id {
+            if self.known_ground_stations.insert(gs_id) {
+                if let Some(ref state_db) = self.state_db {
+                    let name = format!("{} GES {:02X}", self.satellite, gs_id);
+                    if let Err(e) = state_db.read().await.add_ground_station(gs_id, &name).await {
+                        warn!("Failed to add ground earth station {} to state DB: {}", name, e.to_string());
+                    }
+                }
+            }
+        }
+
+        let (frame_src, frame_dst) = if raw_frame.from_ground_station() {
+            (ground_station, raw_frame.aircraft_entity())
+        } else {
+            (raw_frame.aircraft_entity(), ground_station)
+        };
+
+        let (app_name, app_version) = match raw_frame.app {
+            Some(ref app) => (app.name.clone(), app.ver.clone()),
+            None => (String::from("JAERO"), String::new()),
+        };
+
+        Ok(cff::CommonFrame {
+            schema_version: cff::CFF_SCHEMA_VERSION,
+
+            timestamp: arrival_time.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            freq: raw_frame.freq.unwrap_or(self.bands[0] as f64 / 1000.0),
+            signal: raw_frame.level as f32,
+
+            err: false,
+
+            paths: Vec::new(),
+
+            app: cff::AppInfo {
+                name: app_name,
+                version: app_version,
+            },
+
+            indexed: cff::Indexed {
+                timestamp: arrival_time.to_rfc3339_opts(SecondsFormat::Micros, true),
+
+                ..Default::default()
+            },
+            metadata: cff::Metadata { hfdl: None, vdl2: None },
+
+            src: frame_src,
+            dst: Some(frame_dst),
+            acars: raw_frame.to_acars(),
+            from_ground_station: None,
+            raw: None,
+        })
+    }
+
+    async fn reload(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use log::*;
+
+use async_trait::async_trait;
+use std::process::ExitStatus;
+use tokio::io::{self, AsyncBufReadExt, BufReader, Stdin};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::modules::session::{EndSessionReason, Session, SESSION_INPUT_CLOSED_END};
+
+pub enum SatcomInput {
+    Listener(TcpListener),
+    Stdin(BufReader<Stdin>),
+}
+
+// NOTE: JAERO is not launched by xng; the session only attaches to its JSON output
+pub struct SatcomSession {
+    input: SatcomInput,
+    client: Option<BufReader<TcpStream>>,
+
+    bands: Vec<u64>,
+}
+
+#[async_trait]
+impl Session for SatcomSession {
+    async fn read_message(&mut self, msg: &mut String) -> Result<usize, io::Error> {
+        let listener = match self.input {
+            SatcomInput::Stdin(ref mut reader) => {
+                return match reader.read_line(msg).await {
+                    Ok(0) => Err(io::Error::new(io::ErrorKind::Other, SESSION_INPUT_CLOSED_END)),
+                    result => result,
+                };
+            }
+            SatcomInput::Listener(ref listener) => listener,
+        };
+
+        // NOTE: a disconnecting feeder does not end the session, the next connection is waited on instead
+        loop {
+            if self.client.is_none() {
+                let (stream, addr) = listener.accept().await?;
+                info!("JAERO feeder connected from {}", addr);
+                self.client = Some(BufReader::new(stream));
+            }
+
+            let Some(ref mut client) = self.client else {
+                continue;
+            };
+            match client.read_line(msg).await {
+                Ok(0) => {
+                    info!("JAERO feeder disconnected, waiting for a new connection");
+                    self.client = None;
+                    msg.clear();
+                }
+                Err(e) => {
+                    warn!("Failed to read from JAERO feeder, dropping connection: {}", e.to_string());
+                    self.client = None;
+                    msg.clear();
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn on_timeout(&mut self) -> bool {
+        false
+    }
+
+    async fn get_errors(&mut self) -> String {
+        String::new()
+    }
+
+    fn get_listening_band(&self) -> &Vec<u64> {
+        &self.bands
+    }
+
+    async fn exit_status(&mut self) -> Option<ExitStatus> {
+        None
+    }
+
+    async fn end(&mut self, reason: EndSessionReason) {
+        self.client = None;
+        debug!("Satcom session terminated: reason={:?}", reason);
+    }
+}
+
+impl SatcomSession {
+    pub fn new(input: SatcomInput, bands: Vec<u64>) -> SatcomSession {
+        SatcomSession {
+            input,
+            client: None,
+            bands,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use actix_web::http::header;
+use actix_web::web::Data;
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::Utc;
+use log::*;
+use rand::Rng;
+use serde::Serialize;
+use tokio::fs::File;
+use tokio::sync::RwLock;
+use tokio_util::io::ReaderStream;
+
+use crate::common::middleware::Authorized;
+use crate::modules::settings::ModuleSettings;
+use crate::server::db::StateDB;
+
+pub const ROUTE: &'static str = "/api/export/db/";
+
+const SQLITE_CONTENT_TYPE: &'static str = "application/vnd.sqlite3";
+
+#[derive(Serialize)]
+struct DbExportResponse {
+    ok: bool,
+    message: String,
+}
+
+fn error(mut builder: actix_web::HttpResponseBuilder, message: String) -> HttpResponse {
+    builder.json(DbExportResponse { ok: false, message })
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    // NOTE: the export hands out every tracked aircraft and flight, so unlike other endpoints it
+    //       refuses to run on an instance that was started without an API token
+    if req
+        .app_data::<Data<RwLock<ModuleSettings>>>()
+        .unwrap()
+        .read()
+        .await
+        .api_token
+        .is_none()
+    {
+        return error(
+            HttpResponse::Forbidden(),
+            format!("Database export requires an API token to be configured"),
+        );
+    }
+
+    // NOTE: the pool is cloned so the StateDB lock isn't held for the checkpoint and snapshot, which
+    //       would stall every writer waiting on it for as long as VACUUM INTO takes
+    let Some(db) = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await
+        .db_pool()
+        .cloned()
+    else {
+        return error(HttpResponse::NotFound(), format!("State DB is disabled"));
+    };
+
+    if let Err(e) = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&db)
+        .await
+    {
+        return error(
+            HttpResponse::InternalServerError(),
+            format!("Failed to checkpoint WAL: {}", e.to_string()),
+        );
+    }
+
+    // NOTE: VACUUM INTO writes a transactionally consistent copy, so ingestion can keep going while
+    //       the (possibly large) download is streamed from the copy
+    let export_path = std::env::temp_dir().join(format!(
+        "xng-export-{:016x}.sqlite3",
+        rand::thread_rng().gen::<u64>()
+    ));
+    if let Err(e) = sqlx::query("VACUUM INTO ?")
+        .bind(export_path.to_string_lossy().to_string())
+        .execute(&db)
+        .await
+    {
+        return error(
+            HttpResponse::InternalServerError(),
+            format!("Failed to snapshot state DB: {}", e.to_string()),
+        );
+    }
+
+    let file = match File::open(&export_path).await {
+        Ok(x) => x,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&export_path).await;
+            return error(
+                HttpResponse::InternalServerError(),
+                format!("Failed to open state DB snapshot: {}", e.to_string()),
+            );
+        }
+    };
+
+    // NOTE: unlinking while the handle is open keeps the snapshot readable until the stream is dropped
+    if let Err(e) = tokio::fs::remove_file(&export_path).await {
+        warn!(
+            "Failed to remove state DB snapshot {}: {}",
+            export_path.display(),
+            e.to_string()
+        );
+    }
+
+    HttpResponse::Ok()
+        .content_type(SQLITE_CONTENT_TYPE)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"xng-state-{}.sqlite3\"",
+                Utc::now().format("%Y%m%dT%H%M%SZ")
+            ),
+        ))
+        .streaming(ReaderStream::new(file))
+}
@@ -1,14 +1,26 @@
 use actix_web::{guard, web};
 
+mod db_export;
+mod schedule;
 mod session;
 mod settings;
+mod status;
+mod systable;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::resource(settings::ROUTE)
-            .guard(guard::Header("content-type", "application/json"))
             .route(web::get().to(settings::get))
-            .route(web::patch().to(settings::patch)),
+            .route(
+                web::patch()
+                    .guard(guard::Header("content-type", "application/json"))
+                    .to(settings::patch),
+            ),
+    );
+
+    cfg.service(
+        web::resource(schedule::ROUTE)
+            .route(web::get().to(schedule::get)),
     );
 
     cfg.service(
@@ -16,4 +28,18 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .guard(guard::Header("content-type", "application/json"))
             .route(web::delete().to(session::delete)),
     );
+
+    cfg.service(
+        web::resource(status::ROUTE)
+            .route(web::get().to(status::get)),
+    );
+
+    cfg.service(
+        web::resource(systable::ROUTE)
+            .route(web::get().to(systable::get)),
+    );
+}
+
+pub fn config_db_export(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource(db_export::ROUTE).route(web::get().to(db_export::get)));
 }
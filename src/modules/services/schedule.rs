@@ -0,0 +1,96 @@
+use actix_web::{web::Data, HttpRequest, HttpResponse};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::modules::hfdl::schedule::parse_session_schedule;
+use crate::modules::hfdl::PROP_SESSION_SCHEDULE;
+use crate::modules::settings::ModuleSettings;
+
+pub const ROUTE: &'static str = "/api/schedule/";
+
+#[derive(Serialize)]
+struct ScheduleEntry {
+    time: String,
+    band_contains: u32,
+    next_switch: DateTime<Local>,
+}
+
+#[derive(Serialize)]
+struct ScheduleBody {
+    schedule: String,
+
+    // NOTE: entries are ordered by their next switch, so the first is up next and the last is the one in effect
+    entries: Vec<ScheduleEntry>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_band_contains: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_switch: Option<DateTime<Local>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_band_contains: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct ScheduleResponse {
+    ok: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<ScheduleBody>,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let module_settings = req
+        .app_data::<Data<RwLock<ModuleSettings>>>()
+        .unwrap()
+        .read()
+        .await;
+
+    let Ok(schedule) = module_settings.get_str(PROP_SESSION_SCHEDULE) else {
+        return HttpResponse::NotFound().json(ScheduleResponse {
+            ok: false,
+            message: Some(format!("Module has no session schedule")),
+            body: None,
+        });
+    };
+
+    let entries = if schedule.is_empty() {
+        Vec::new()
+    } else {
+        match parse_session_schedule(&schedule) {
+            Ok(x) => x,
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(ScheduleResponse {
+                    ok: false,
+                    message: Some(format!("Stored session schedule is invalid: {}", e)),
+                    body: None,
+                })
+            }
+        }
+    };
+
+    HttpResponse::Ok().json(ScheduleResponse {
+        ok: true,
+        message: None,
+        body: Some(ScheduleBody {
+            current_band_contains: entries.last().map(|x| x.1),
+            next_switch: entries.first().map(|x| x.0),
+            next_band_contains: entries.first().map(|x| x.1),
+            entries: entries
+                .into_iter()
+                .map(|(dt, freq)| ScheduleEntry {
+                    time: dt.format("%H:%M").to_string(),
+                    band_contains: freq,
+                    next_switch: dt,
+                })
+                .collect(),
+            schedule,
+        }),
+    })
+}
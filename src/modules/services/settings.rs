@@ -24,12 +24,12 @@ pub async fn get(req: HttpRequest) -> HttpResponse {
         .read()
         .await;
 
+    let mut body = serde_json::to_value(&*module_settings).unwrap();
+    body["stations"] = serde_json::to_value(module_settings.reported_stations()).unwrap();
+
     HttpResponse::Ok()
         .content_type(ContentType::json())
-        .json(GetResponse {
-            ok: true,
-            body: serde_json::to_value(&*module_settings).unwrap(),
-        })
+        .json(GetResponse { ok: true, body })
 }
 
 #[derive(Deserialize)]
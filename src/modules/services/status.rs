@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::Ordering;
+
+use actix_web::{web::Data, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::modules::hfdl::{PROP_SESSION_METHOD, PROP_USE_AIRFRAMES_GS};
+use crate::modules::settings::ModuleSettings;
+use crate::modules::status::SdrStats;
+use crate::modules::PROP_LISTENING_BAND;
+use crate::utils::band_name;
+
+pub const ROUTE: &'static str = "/api/status/";
+
+#[derive(Serialize)]
+struct ModuleStatusBody {
+    listening_band: Value,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    listening_band_label: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_method: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_started: Option<DateTime<Utc>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_uptime_secs: Option<i64>,
+
+    session_frames: u64,
+    dropped_frames: u64,
+    webhook_dropped_frames: u64,
+    swarm_dropped_frames: u64,
+    future_frames_rejected: u64,
+
+    swarm_mode: bool,
+    swarm_connected: bool,
+    consecutive_crashes: u32,
+    session_end_reasons: BTreeMap<&'static str, u64>,
+
+    use_airframes_gs: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decoder_version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sdr_stats: Option<SdrStats>,
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    ok: bool,
+    body: ModuleStatusBody,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let module_settings = req
+        .app_data::<Data<RwLock<ModuleSettings>>>()
+        .unwrap()
+        .read()
+        .await;
+
+    let listening_band = module_settings
+        .props
+        .get(PROP_LISTENING_BAND)
+        .cloned()
+        .unwrap_or(Value::Null);
+    let listening_band_label = serde_json::from_value::<Vec<u64>>(listening_band.clone())
+        .ok()
+        .filter(|x| !x.is_empty())
+        .map(|x| band_name(&x));
+
+    HttpResponse::Ok().json(StatusResponse {
+        ok: true,
+        body: ModuleStatusBody {
+            listening_band,
+            listening_band_label,
+            session_method: module_settings
+                .props
+                .get(PROP_SESSION_METHOD)
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_string()),
+            session_started: module_settings.status.session_started,
+            session_uptime_secs: module_settings.status.session_uptime_secs(),
+            session_frames: module_settings.status.frames.session_frames.load(Ordering::Relaxed),
+            dropped_frames: module_settings.status.frames.dropped_frames.load(Ordering::Relaxed),
+            webhook_dropped_frames: module_settings.status.frames.webhook_dropped_frames.load(Ordering::Relaxed),
+            swarm_dropped_frames: module_settings.status.frames.swarm_dropped_frames.load(Ordering::Relaxed),
+            future_frames_rejected: module_settings.status.frames.future_frames_rejected.load(Ordering::Relaxed),
+            swarm_mode: module_settings.swarm_mode,
+            swarm_connected: module_settings.status.swarm_connected,
+            consecutive_crashes: module_settings.status.consecutive_crashes,
+            session_end_reasons: module_settings.status.session_end_reasons.clone(),
+            use_airframes_gs: module_settings
+                .props
+                .get(PROP_USE_AIRFRAMES_GS)
+                .and_then(|x| x.as_bool())
+                .unwrap_or(false),
+            decoder_version: module_settings.status.decoder_version.clone(),
+            sdr_stats: module_settings.status.sdr_stats.clone(),
+        },
+    })
+}
@@ -0,0 +1,41 @@
+use actix_web::{web::Data, HttpRequest, HttpResponse};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::modules::settings::ModuleSettings;
+
+pub const ROUTE: &'static str = "/api/systable/";
+
+#[derive(Serialize)]
+struct SystemTableResponse {
+    ok: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let module_settings = req
+        .app_data::<Data<RwLock<ModuleSettings>>>()
+        .unwrap()
+        .read()
+        .await;
+
+    match module_settings.systable {
+        Some(ref systable) => HttpResponse::Ok().json(SystemTableResponse {
+            ok: true,
+            message: None,
+            body: Some(systable.clone()),
+        }),
+        None => HttpResponse::NotFound().json(SystemTableResponse {
+            ok: false,
+            message: Some(format!("Module has no system table loaded")),
+            body: None,
+        }),
+    }
+}
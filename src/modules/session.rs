@@ -1,12 +1,19 @@
 use async_trait::async_trait;
+use std::process::ExitStatus;
 use tokio::io;
+use tokio::process::Command;
 
 pub const SESSION_SCHEDULED_END: &'static str = "SESSION_SCHEDULED_END";
+pub const SESSION_DRY_RUN_END: &'static str = "SESSION_DRY_RUN_END";
+pub const SESSION_INPUT_CLOSED_END: &'static str = "SESSION_INPUT_CLOSED_END";
+
+pub const DEFAULT_EXIT_STATUS_WAIT_SECS: u64 = 5;
 
 #[derive(Copy, Clone, Debug)]
 pub enum EndSessionReason {
     None,
     SessionTimeout,
+    NoFrameWatchdog,
     SessionEnd,
     SessionUpdate,
     UserInterrupt,
@@ -15,6 +22,27 @@ pub enum EndSessionReason {
     ReadError,
     ReadEOF,
     ProcessStartError,
+    DryRun,
+    InputClosed,
+}
+
+impl EndSessionReason {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EndSessionReason::None => "none",
+            EndSessionReason::SessionTimeout => "session_timeout",
+            EndSessionReason::NoFrameWatchdog => "no_frame_watchdog",
+            EndSessionReason::SessionEnd => "session_end",
+            EndSessionReason::SessionUpdate => "session_update",
+            EndSessionReason::UserInterrupt => "user_interrupt",
+            EndSessionReason::UserAPIControl => "user_api_control",
+            EndSessionReason::ReadError => "read_error",
+            EndSessionReason::ReadEOF => "read_eof",
+            EndSessionReason::ProcessStartError => "process_start_error",
+            EndSessionReason::DryRun => "dry_run",
+            EndSessionReason::InputClosed => "input_closed",
+        }
+    }
 }
 
 #[async_trait]
@@ -26,5 +54,62 @@ pub trait Session {
 
     fn get_listening_band(&self) -> &Vec<u64>;
 
+    async fn exit_status(&mut self) -> Option<ExitStatus>;
+
     async fn end(&mut self, reason: EndSessionReason);
 }
+
+// NOTE: stands in for a decoder process when --dry-run is given, ending on the first read
+pub struct DryRunSession {
+    bands: Vec<u64>,
+}
+
+impl DryRunSession {
+    pub fn new(bands: Vec<u64>) -> DryRunSession {
+        DryRunSession { bands }
+    }
+
+    // NOTE: prints the decoder command line the module would have spawned, quoting only arguments that need it
+    pub fn from_command(cmd: &Command, bands: Vec<u64>) -> DryRunSession {
+        let cmd = cmd.as_std();
+        let line: Vec<String> = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|x| x.to_string_lossy())
+            .map(|x| {
+                if x.is_empty() || x.chars().any(|c| c.is_whitespace() || c == '"' || c == '\'') {
+                    format!("{:?}", x)
+                } else {
+                    x.to_string()
+                }
+            })
+            .collect();
+        println!("{}", line.join(" "));
+
+        DryRunSession::new(bands)
+    }
+}
+
+#[async_trait]
+impl Session for DryRunSession {
+    async fn read_message(&mut self, _msg: &mut String) -> Result<usize, io::Error> {
+        Err(io::Error::new(io::ErrorKind::Other, SESSION_DRY_RUN_END))
+    }
+
+    async fn on_timeout(&mut self) -> bool {
+        true
+    }
+
+    async fn get_errors(&mut self) -> String {
+        String::new()
+    }
+
+    fn get_listening_band(&self) -> &Vec<u64> {
+        &self.bands
+    }
+
+    async fn exit_status(&mut self) -> Option<ExitStatus> {
+        None
+    }
+
+    async fn end(&mut self, _reason: EndSessionReason) {}
+}
@@ -2,13 +2,17 @@ use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
 use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use reqwest::Client;
 use serde::Serialize;
 use serde_json::Value;
+use tokio::io;
 use tokio::sync::mpsc::{Sender, UnboundedSender};
 
 use crate::common::events::GroundStationChangeEvent;
 
 use super::session::EndSessionReason;
+use super::status::ModuleStatus;
+use super::{MAX_ACTIVE_FREQ_TTL_SECS, PROP_ACTIVE_FREQ_TTL_SEC};
 
 pub type ValidatorCallback = fn(&Value) -> Result<(), String>;
 
@@ -151,6 +155,15 @@ pub struct ModuleSettings {
     #[serde(skip_serializing)]
     pub change_event_tx: Sender<GroundStationChangeEvent>,
 
+    #[serde(skip_serializing)]
+    pub http_client: Client,
+
+    #[serde(skip_serializing)]
+    pub status: ModuleStatus,
+
+    #[serde(skip_serializing)]
+    pub systable: Option<Value>,
+
     #[serde(skip_serializing)]
     validators: HashMap<String, ValidatorCallback>,
 }
@@ -160,6 +173,7 @@ impl ModuleSettings {
         reload_signaler: UnboundedSender<()>,
         end_session_signaler: UnboundedSender<EndSessionReason>,
         change_event_tx: Sender<GroundStationChangeEvent>,
+        http_client: Client,
         swarm_mode: bool,
         disable_api_control: bool,
         api_token: Option<&String>,
@@ -177,10 +191,75 @@ impl ModuleSettings {
             reload_signaler,
             end_session_signaler,
             change_event_tx,
+            http_client,
+            status: ModuleStatus::default(),
+            systable: None,
             validators: HashMap::new(),
         }
     }
 
+    fn get_typed<T>(&self, prop: &str, kind: &str, convert: fn(&Value) -> Option<T>) -> Result<T, io::Error> {
+        let Some(value) = self.props.get(prop) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Missing {} prop", prop),
+            ));
+        };
+
+        convert(value).ok_or(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} prop is not {}: {}", prop, kind, value),
+        ))
+    }
+
+    pub fn get_u64(&self, prop: &str) -> Result<u64, io::Error> {
+        self.get_typed(prop, "a positive number", |x| x.as_u64())
+    }
+
+    pub fn get_bool(&self, prop: &str) -> Result<bool, io::Error> {
+        self.get_typed(prop, "a boolean", |x| x.as_bool())
+    }
+
+    pub fn get_str(&self, prop: &str) -> Result<String, io::Error> {
+        self.get_typed(prop, "a string", |x| x.as_str().map(|y| y.to_string()))
+    }
+
+    pub fn get_u64_array(&self, prop: &str) -> Result<Vec<u64>, io::Error> {
+        self.get_typed(prop, "an array of positive numbers", |x| {
+            x.as_array()?.iter().map(|y| y.as_u64()).collect()
+        })
+    }
+
+    // NOTE: only updates existing props, new props must be registered through ModuleSettings::new or add_prop_with_validator
+    pub fn set(&mut self, prop: &str, value: Value) -> Result<(), io::Error> {
+        let Some(entry) = self.props.get_mut(prop) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Missing {} prop", prop),
+            ));
+        };
+        *entry = value;
+
+        Ok(())
+    }
+
+    // NOTE: separate from the stale timeout, this only filters a copy for reporting and never what is listened to
+    pub fn reported_stations(&self) -> Vec<GroundStation> {
+        let mut stations = self.stations.clone();
+        // NOTE: clamped again since chrono's Duration panics on huge second counts
+        let ttl_secs = self
+            .get_u64(PROP_ACTIVE_FREQ_TTL_SEC)
+            .unwrap_or(0)
+            .min(MAX_ACTIVE_FREQ_TTL_SECS);
+        if ttl_secs > 0 {
+            for station in stations.iter_mut() {
+                station.invalidate(Duration::seconds(ttl_secs as i64));
+            }
+        }
+
+        stations
+    }
+
     pub fn add_prop_with_validator(
         &mut self,
         prop: String,
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::session::EndSessionReason;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SdrStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gain: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overloads: Option<u64>,
+
+    pub updated: DateTime<Utc>,
+}
+
+impl SdrStats {
+    // NOTE: stats lines may only carry a subset of the values, so missing ones keep their last seen value
+    pub fn merge(&mut self, other: SdrStats) {
+        self.sample_rate = other.sample_rate.or(self.sample_rate);
+        self.gain = other.gain.or(self.gain);
+        self.overloads = other.overloads.or(self.overloads);
+        self.updated = other.updated;
+    }
+}
+
+// NOTE: updated for every frame, so these are atomics shared with the frame loop and processor instead of
+//       fields behind the module settings write lock
+#[derive(Default)]
+pub struct FrameCounters {
+    pub session_frames: AtomicU64,
+    pub total_frames: AtomicU64,
+    pub dropped_frames: AtomicU64,
+    pub webhook_dropped_frames: AtomicU64,
+    pub swarm_dropped_frames: AtomicU64,
+    pub future_frames_rejected: AtomicU64,
+}
+
+impl FrameCounters {
+    pub fn record_frame(&self) {
+        self.session_frames.fetch_add(1, Ordering::Relaxed);
+        self.total_frames.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ModuleStatus {
+    pub session_started: Option<DateTime<Utc>>,
+    pub frames: Arc<FrameCounters>,
+    pub swarm_connected: bool,
+    pub consecutive_crashes: u32,
+    pub session_end_reasons: BTreeMap<&'static str, u64>,
+
+    pub decoder_version: Option<String>,
+    pub sdr_stats: Option<SdrStats>,
+}
+
+impl ModuleStatus {
+    pub fn start_session(&mut self) {
+        self.session_started = Some(Utc::now());
+        self.frames.session_frames.store(0, Ordering::Relaxed);
+    }
+
+    pub fn record_end_reason(&mut self, reason: EndSessionReason) {
+        *self.session_end_reasons.entry(reason.name()).or_insert(0) += 1;
+    }
+
+    pub fn session_uptime_secs(&self) -> Option<i64> {
+        self.session_started
+            .map(|x| (Utc::now() - x).num_seconds())
+    }
+}
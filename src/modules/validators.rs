@@ -1,5 +1,19 @@
 use serde_json::Value;
 
+use super::MAX_ACTIVE_FREQ_TTL_SECS;
+
 pub fn validate_listening_bands(_value: &Value) -> Result<(), String> {
     Err(String::from("listening_bands is read-only"))
 }
+
+pub fn validate_active_freq_ttl_sec(value: &Value) -> Result<(), String> {
+    let Some(ttl) = value.as_u64() else {
+        return Err(format!("Expected non-negative seconds"));
+    };
+
+    if ttl > MAX_ACTIVE_FREQ_TTL_SECS {
+        return Err(format!("TTL cannot exceed {} seconds", MAX_ACTIVE_FREQ_TTL_SECS));
+    }
+
+    Ok(())
+}
@@ -3,16 +3,84 @@ use sqlx::SqlitePool;
 use tokio::io;
 
 use crate::server::db::migrations::n0001_create_init_tables::CreateInitTables;
+use crate::server::db::migrations::n0002_add_flight_leg_columns::AddFlightLegColumns;
+use crate::server::db::migrations::n0003_add_distance_column::AddDistanceColumn;
+use crate::server::db::migrations::n0004_add_hfdl_link_columns::AddHfdlLinkColumns;
+use crate::server::db::migrations::n0005_create_hourly_stats::CreateHourlyStats;
+use crate::server::db::migrations::n0006_add_aircraft_events_ts_index::AddAircraftEventsTsIndex;
+use crate::server::db::migrations::n0007_add_ground_station_short_column::AddGroundStationShortColumn;
+use crate::server::db::migrations::n0008_create_heard_on_events::CreateHeardOnEvents;
+use crate::server::db::migrations::n0009_add_aircraft_events_ts_only_index::AddAircraftEventsTsOnlyIndex;
+use crate::server::db::migrations::n0010_remove_broadcast_aircraft::RemoveBroadcastAircraft;
 
 mod n0001_create_init_tables;
+mod n0002_add_flight_leg_columns;
+mod n0003_add_distance_column;
+mod n0004_add_hfdl_link_columns;
+mod n0005_create_hourly_stats;
+mod n0006_add_aircraft_events_ts_index;
+mod n0007_add_ground_station_short_column;
+mod n0008_create_heard_on_events;
+mod n0009_add_aircraft_events_ts_only_index;
+mod n0010_remove_broadcast_aircraft;
 
 #[async_trait]
-pub trait Migration {
+pub trait Migration: Send + Sync {
     async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error>;
 }
 
-pub async fn run(db: &SqlitePool) -> Result<(), io::Error> {
-    let xng_migrations: Vec<Box<dyn Migration>> = vec![Box::new(CreateInitTables)];
+pub async fn add_column_if_missing(
+    db: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), io::Error> {
+    let count: i64 = match sqlx::query_scalar(
+        "
+        SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?
+        ",
+    )
+    .bind(table)
+    .bind(column)
+    .fetch_one(db)
+    .await
+    {
+        Ok(x) => x,
+        Err(e) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to inspect columns of {}: {}", table, e.to_string()),
+            ))
+        }
+    };
+    if count > 0 {
+        return Ok(());
+    }
+
+    let query = format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition);
+    if let Err(e) = sqlx::query(query.as_str()).execute(db).await {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Failed to run query: {}\n\n{}", e.to_string(), query),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn run(db: &SqlitePool, keep_broadcast: bool) -> Result<(), io::Error> {
+    let xng_migrations: Vec<Box<dyn Migration>> = vec![
+        Box::new(CreateInitTables),
+        Box::new(AddFlightLegColumns),
+        Box::new(AddDistanceColumn),
+        Box::new(AddHfdlLinkColumns),
+        Box::new(CreateHourlyStats),
+        Box::new(AddAircraftEventsTsIndex),
+        Box::new(AddGroundStationShortColumn),
+        Box::new(CreateHeardOnEvents),
+        Box::new(AddAircraftEventsTsOnlyIndex),
+        Box::new(RemoveBroadcastAircraft { keep_broadcast }),
+    ];
 
     for migration in xng_migrations.iter() {
         migration.migrate(db).await?;
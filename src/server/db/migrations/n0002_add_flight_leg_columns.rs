@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tokio::io;
+
+use super::{add_column_if_missing, Migration};
+
+pub struct AddFlightLegColumns;
+
+#[async_trait]
+impl Migration for AddFlightLegColumns {
+    async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error> {
+        add_column_if_missing(db, "aircraft_events", "flight_id", "TEXT").await?;
+        add_column_if_missing(db, "aircraft_events", "flight_leg_num", "INTEGER").await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tokio::io;
+
+use super::{add_column_if_missing, Migration};
+
+pub struct AddHfdlLinkColumns;
+
+#[async_trait]
+impl Migration for AddHfdlLinkColumns {
+    async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error> {
+        add_column_if_missing(db, "aircraft_events", "bit_rate", "INTEGER").await?;
+        add_column_if_missing(db, "aircraft_events", "slot", "TEXT").await?;
+
+        Ok(())
+    }
+}
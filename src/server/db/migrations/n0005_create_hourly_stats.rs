@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tokio::io;
+
+use super::Migration;
+
+pub struct CreateHourlyStats;
+
+#[async_trait]
+impl Migration for CreateHourlyStats {
+    async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error> {
+        let query = "
+            CREATE TABLE IF NOT EXISTS hourly_stats (
+                hour   DATETIME NOT NULL,
+                metric TEXT NOT NULL,
+                key    INTEGER NOT NULL,
+
+                count  INTEGER NOT NULL,
+
+                PRIMARY KEY(hour, metric, key)
+            )
+        ";
+        if let Err(e) = sqlx::query(query).execute(db).await {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to run query: {}\n\n{}", e.to_string(), query),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tokio::io;
+
+use super::{add_column_if_missing, Migration};
+
+pub struct AddGroundStationShortColumn;
+
+#[async_trait]
+impl Migration for AddGroundStationShortColumn {
+    async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error> {
+        add_column_if_missing(db, "ground_stations", "short", "TEXT").await?;
+
+        Ok(())
+    }
+}
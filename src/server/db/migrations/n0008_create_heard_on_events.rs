@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tokio::io;
+
+use super::Migration;
+
+pub struct CreateHeardOnEvents;
+
+#[async_trait]
+impl Migration for CreateHeardOnEvents {
+    async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error> {
+        let query = "
+            CREATE TABLE IF NOT EXISTS heard_on_events (
+                id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                aircraft_events_id INTEGER NOT NULL,
+                gs_id              INTEGER NOT NULL,
+
+                FOREIGN KEY(aircraft_events_id) REFERENCES aircraft_events(id) ON DELETE CASCADE
+                UNIQUE(aircraft_events_id, gs_id)
+            )
+        ";
+        if let Err(e) = sqlx::query(query).execute(db).await {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to run query: {}\n\n{}", e.to_string(), query),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tokio::io;
+
+use super::Migration;
+
+pub struct AddAircraftEventsTsOnlyIndex;
+
+#[async_trait]
+impl Migration for AddAircraftEventsTsOnlyIndex {
+    async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error> {
+        let query = "
+            CREATE INDEX IF NOT EXISTS aircraft_events_ts ON aircraft_events (ts)
+        ";
+        if let Err(e) = sqlx::query(query).execute(db).await {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to run query: {}\n\n{}", e.to_string(), query),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use tokio::io;
+
+use crate::common::frame::BROADCAST_ADDR;
+
+use super::Migration;
+
+// NOTE: broadcast frames stopped being recorded as an aircraft, this clears the phantom rows left behind by
+//       older versions. With --keep-broadcast the rows are wanted, so they are left alone
+pub struct RemoveBroadcastAircraft {
+    pub keep_broadcast: bool,
+}
+
+#[async_trait]
+impl Migration for RemoveBroadcastAircraft {
+    async fn migrate(&self, db: &SqlitePool) -> Result<(), io::Error> {
+        if self.keep_broadcast {
+            return Ok(());
+        }
+        let Ok(icao) = u32::from_str_radix(BROADCAST_ADDR, 16) else {
+            return Ok(());
+        };
+
+        let queries = [
+            "
+            DELETE FROM propagation_events WHERE aircraft_events_id IN (SELECT id FROM aircraft_events WHERE aircraft_icao = ?)
+            ",
+            "
+            DELETE FROM heard_on_events WHERE aircraft_events_id IN (SELECT id FROM aircraft_events WHERE aircraft_icao = ?)
+            ",
+            "
+            DELETE FROM aircraft_events WHERE aircraft_icao = ?
+            ",
+            "
+            DELETE FROM aircrafts WHERE icao = ?
+            ",
+        ];
+
+        let mut tx = match db.begin().await {
+            Ok(x) => x,
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to begin transaction: {}", e.to_string()),
+                ))
+            }
+        };
+        for query in queries.iter() {
+            if let Err(e) = sqlx::query(query).bind(icao).execute(&mut tx).await {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to run query: {}\n\n{}", e.to_string(), query),
+                ));
+            }
+        }
+        if let Err(e) = tx.commit().await {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to commit broadcast aircraft cleanup: {}", e.to_string()),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -1,24 +1,95 @@
+use actix_web::web::Data;
+use chrono::{DateTime, Utc};
 use log::*;
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::{migrate::MigrateDatabase, Sqlite, SqlitePool};
+use std::str::FromStr;
+use std::time::Duration;
 use tokio::io;
+use tokio::select;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+use tokio_util::sync::CancellationToken;
 
 use crate::common::events::GroundStationChangeEvent;
-use crate::common::frame::CommonFrame;
+use crate::common::frame::{CommonFrame, Entity};
+use crate::utils::geo::haversine_km;
 
 use self::migrations as db_migrations;
+use self::summary_cache::{load_latest_events, LatestAircraftEvent, SummaryCache};
 
 mod migrations;
+pub mod summary_cache;
+
+pub const HOURLY_STATS_METRIC_FREQ: &'static str = "freq";
+pub const HOURLY_STATS_METRIC_GS: &'static str = "gs";
+
+const DEFAULT_RECOVER_AFTER_WRITE_FAILURES: u32 = 5;
+
+const GS_CHANGE_EVENT_INSERT: &'static str = "
+    INSERT INTO ground_station_change_events (gs_id, ts, type, old, new) VALUES (?, ?, \"freq_change\", ?, ?)
+";
 
 pub struct StateDB {
     db: Option<SqlitePool>,
+    read_db: Option<SqlitePool>,
+    summary_cache: Data<RwLock<SummaryCache>>,
+
+    db_url: Option<String>,
+    use_read_pool: bool,
+    auto_recover: bool,
+    write_failures: u32,
+
+    min_event_interval_secs: u64,
+    coord_precision: Option<u32>,
+    keep_broadcast: bool,
 }
 
 impl StateDB {
-    pub async fn new(db_url: Option<String>) -> Result<StateDB, io::Error> {
+    pub async fn new(
+        db_url: Option<String>,
+        summary_cache_ttl_secs: u64,
+        use_read_pool: bool,
+        auto_recover: bool,
+        min_event_interval_secs: u64,
+        coord_precision: Option<u32>,
+        keep_broadcast: bool,
+    ) -> Result<StateDB, io::Error> {
+        let summary_cache = Data::new(RwLock::new(SummaryCache::new(summary_cache_ttl_secs)));
         let Some(db_url) = db_url else {
-            return Ok(StateDB { db: None });  
+            return Ok(StateDB {
+                db: None,
+                read_db: None,
+                summary_cache,
+                db_url: None,
+                use_read_pool,
+                auto_recover,
+                write_failures: 0,
+                min_event_interval_secs,
+                coord_precision,
+                keep_broadcast,
+            });
         };
         
+        let (db, read_db) = Self::connect(&db_url, use_read_pool, keep_broadcast).await?;
+
+        Ok(StateDB {
+            db: Some(db),
+            read_db,
+            summary_cache,
+            db_url: Some(db_url),
+            use_read_pool,
+            auto_recover,
+            write_failures: 0,
+            min_event_interval_secs,
+            coord_precision,
+            keep_broadcast,
+        })
+    }
+
+    // NOTE: creates the database if it does not exist (e.g. the file was deleted), so it doubles as the recovery path
+    async fn connect(db_url: &String, use_read_pool: bool, keep_broadcast: bool) -> Result<(SqlitePool, Option<SqlitePool>), io::Error> {
         if !Sqlite::database_exists(db_url.as_str())
             .await
             .unwrap_or(false)
@@ -46,33 +117,68 @@ impl StateDB {
             }
         };
 
-        if let Err(e) = db_migrations::run(&db).await {
+        if let Err(e) = db_migrations::run(&db, keep_broadcast).await {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
                 format!("Failed to run migrations: {}", e.to_string()),
             ));
         }
 
-        Ok(StateDB { db: Some(db) })
+        let mut read_db: Option<SqlitePool> = None;
+        if use_read_pool {
+            if let Err(e) = sqlx::query("PRAGMA journal_mode = WAL").execute(&db).await {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to enable WAL journal mode: {}", e.to_string()),
+                ));
+            }
+
+            let read_options = match SqliteConnectOptions::from_str(db_url.as_str()) {
+                Ok(x) => x.read_only(true),
+                Err(e) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Invalid state DB URL {}: {}", db_url, e.to_string()),
+                    ))
+                }
+            };
+            read_db = match SqlitePool::connect_with(read_options).await {
+                Ok(x) => {
+                    debug!("Read-only state DB pool opened for {}", db_url);
+                    Some(x)
+                }
+                Err(e) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("Failed to open read-only connection to {}: {}", db_url, e.to_string()),
+                    ))
+                }
+            };
+        }
+
+        Ok((db, read_db))
     }
 
     pub async fn create_ground_station(
         &self,
         id: u32,
         name: &String,
+        short: Option<&str>,
         latitude: f64,
         longitude: f64,
     ) -> Result<(), sqlx::Error> {
         if let Some(ref db) = self.db {
+            // NOTE: existing stations only pick up the short code, so rows created before it was stored get one too
             sqlx::query(
                 "
-                INSERT INTO ground_stations (id, name, latitude, longitude, msgs_heard_from, msgs_heard_to)
-                VALUES (?, ?, ?, ?, 0, 0)
-                ON CONFLICT DO NOTHING
+                INSERT INTO ground_stations (id, name, short, latitude, longitude, msgs_heard_from, msgs_heard_to)
+                VALUES (?, ?, ?, ?, ?, 0, 0)
+                ON CONFLICT (id) DO UPDATE SET short = excluded.short WHERE excluded.short IS NOT NULL
                 "
             )
             .bind(id)
             .bind(name)
+            .bind(short)
             .bind(latitude)
             .bind(longitude)
             .execute(db)
@@ -82,41 +188,35 @@ impl StateDB {
         Ok(())
     }
 
-    pub fn db_pool(&self) -> Option<&SqlitePool> {
-        self.db.as_ref()
+    // NOTE: adds a station ahead of its first frame, or fills in its name if it was stored without one
+    pub async fn add_ground_station(&self, id: u32, name: &String) -> Result<(), sqlx::Error> {
+        if let Some(ref db) = self.db {
+            sqlx::query(
+                "
+                INSERT INTO ground_stations (id, name, msgs_heard_from, msgs_heard_to)
+                VALUES (?, ?, 0, 0)
+                ON CONFLICT (id) DO UPDATE SET name = excluded.name WHERE name IS NULL OR name = ''
+                "
+            )
+            .bind(id)
+            .bind(name)
+            .execute(db)
+            .await?;
+        }
+
+        Ok(())
     }
 
-    pub async fn handle_gs_change_event(
-        &self,
-        event: &GroundStationChangeEvent,
-    ) -> Result<(), sqlx::Error> {
+    // NOTE: only fills in names of stations that were already heard, so no empty rows are created for the rest
+    pub async fn update_ground_station_name(&self, id: u32, name: &String) -> Result<(), sqlx::Error> {
         if let Some(ref db) = self.db {
-            let gs_id = if let Some(num) = event.id.as_u64() {
-                num
-            } else if let Some(val) = event.id.as_str() {
-                match u64::from_str_radix(val, 16) {
-                    Ok(x) => x,
-                    Err(e) => {
-                        return Err(sqlx::Error::TypeNotFound {
-                            type_name: format!("String is not hexidecimal: {}", e.to_string()),
-                        });
-                    }
-                }
-            } else {
-                return Err(sqlx::Error::TypeNotFound {
-                    type_name: String::from("Expecting string or number, got something else."),
-                });
-            };
-
             sqlx::query(
                 "
-                INSERT INTO ground_station_change_events (gs_id, ts, type, old, new) VALUES (?, ?, \"freq_change\", ?, ?)        
+                UPDATE ground_stations SET name = ? WHERE id = ? AND (name IS NULL OR name = '')
                 "
             )
-            .bind(gs_id as u32)
-            .bind(&event.ts)
-            .bind(&event.old)
-            .bind(&event.new)
+            .bind(name)
+            .bind(id)
             .execute(db)
             .await?;
         }
@@ -124,16 +224,217 @@ impl StateDB {
         Ok(())
     }
 
-    pub async fn update(&self, frame: &CommonFrame) -> Result<(), sqlx::Error> {
+    pub fn db_pool(&self) -> Option<&SqlitePool> {
+        self.db.as_ref()
+    }
+
+    pub fn db_read_pool(&self) -> Option<&SqlitePool> {
+        self.read_db.as_ref().or(self.db.as_ref())
+    }
+
+    pub fn summary_cache(&self) -> Data<RwLock<SummaryCache>> {
+        self.summary_cache.clone()
+    }
+
+    // NOTE: frequencies a ground station was last heard transmitting on within the window, in kHz
+    pub async fn recent_active_frequencies(&self, within: chrono::Duration) -> Result<Vec<u32>, sqlx::Error> {
+        let Some(ref db) = self.db else {
+            return Ok(Vec::new());
+        };
+
+        sqlx::query_scalar::<_, u32>(
+            "
+            SELECT khz FROM frequency_stats WHERE last_heard >= ? ORDER BY khz ASC
+            "
+        )
+        .bind(Utc::now() - within)
+        .fetch_all(db)
+        .await
+    }
+
+    // NOTE: written in a single transaction, since a systable or airframes.io refresh emits many events at once.
+    //       If the batch fails as a whole, the events are retried one at a time so a single bad event can't
+    //       take the rest of the batch down with it
+    pub async fn handle_gs_change_events(
+        &self,
+        events: &[GroundStationChangeEvent],
+    ) -> Result<(), sqlx::Error> {
+        let Some(ref db) = self.db else {
+            return Ok(());
+        };
+
+        let events: Vec<(u32, &GroundStationChangeEvent)> = events
+            .iter()
+            .filter_map(|event| match event.gs_id() {
+                Some(gs_id) => Some((gs_id as u32, event)),
+                None => {
+                    warn!("Skipping ground station change event, expecting number or hexidecimal string ID, got {}", event.id);
+                    None
+                }
+            })
+            .collect();
+
+        let batch_result: Result<(), sqlx::Error> = async {
+            let mut tx = db.begin().await?;
+            for (gs_id, event) in events.iter() {
+                sqlx::query(GS_CHANGE_EVENT_INSERT)
+                    .bind(gs_id)
+                    .bind(&event.ts)
+                    .bind(&event.old)
+                    .bind(&event.new)
+                    .execute(&mut tx)
+                    .await?;
+            }
+            tx.commit().await
+        }
+        .await;
+        let Err(e) = batch_result else {
+            return Ok(());
+        };
+
+        warn!(
+            "Failed to write {} ground station change events in one transaction, retrying one at a time: {}",
+            events.len(),
+            e.to_string()
+        );
+        let mut last_err: Option<sqlx::Error> = None;
+        for (gs_id, event) in events.iter() {
+            if let Err(e) = sqlx::query(GS_CHANGE_EVENT_INSERT)
+                .bind(gs_id)
+                .bind(&event.ts)
+                .bind(&event.old)
+                .bind(&event.new)
+                .execute(db)
+                .await
+            {
+                warn!("Failed to write ground station change event for {}: {}", gs_id, e.to_string());
+                last_err = Some(e);
+            }
+        }
+
+        match last_err {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    async fn db_size_bytes(db: &SqlitePool) -> Result<i64, sqlx::Error> {
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(db).await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(db).await?;
+        Ok(page_count * page_size)
+    }
+
+    // NOTE: callers hold the StateDB write lock, so no frame updates are written while the database is rebuilt
+    pub async fn vacuum(&self) -> Result<Option<(i64, i64)>, sqlx::Error> {
+        let Some(ref db) = self.db else {
+            return Ok(None);
+        };
+
+        let started = Instant::now();
+        let size_before = Self::db_size_bytes(db).await?;
+
+        sqlx::query("VACUUM").execute(db).await?;
+        if self.use_read_pool {
+            sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(db).await?;
+        }
+
+        let size_after = Self::db_size_bytes(db).await?;
+        info!(
+            "State DB vacuumed in {:.1}s, reclaimed {} bytes ({} -> {} bytes)",
+            started.elapsed().as_secs_f64(),
+            size_before - size_after,
+            size_before,
+            size_after
+        );
+
+        Ok(Some((size_before, size_after)))
+    }
+
+    // NOTE: the old pools are only replaced once a new connection succeeds, so failed attempts are retried after another batch of write failures
+    async fn recover(&mut self) {
+        let Some(db_url) = self.db_url.clone() else {
+            return;
+        };
+
+        warn!("State DB failed {} writes in a row, attempting to reconnect to {}", self.write_failures, db_url);
+        self.write_failures = 0;
+
+        match Self::connect(&db_url, self.use_read_pool, self.keep_broadcast).await {
+            Ok((db, read_db)) => {
+                if let Some(old_db) = self.db.replace(db) {
+                    old_db.close().await;
+                }
+                if let Some(old_read_db) = std::mem::replace(&mut self.read_db, read_db) {
+                    old_read_db.close().await;
+                }
+                self.summary_cache.write().await.invalidate();
+
+                info!("State DB recovered, reconnected to {}", db_url);
+            }
+            Err(e) => error!("Failed to recover state DB: {}", e.to_string()),
+        }
+    }
+
+    pub async fn update(&mut self, frame: &CommonFrame) -> Result<(), sqlx::Error> {
+        let result = match self.coord_precision {
+            Some(decimals) => {
+                let mut frame = frame.clone();
+                frame.round_coords(decimals);
+                self.update_frame(&frame).await
+            }
+            None => self.update_frame(frame).await,
+        };
+
+        if result.is_ok() {
+            self.write_failures = 0;
+        } else if self.db.is_some() {
+            self.write_failures += 1;
+            if self.auto_recover && self.write_failures >= DEFAULT_RECOVER_AFTER_WRITE_FAILURES {
+                self.recover().await;
+            }
+        }
+
+        result
+    }
+
+    async fn update_frame(&self, frame: &CommonFrame) -> Result<(), sqlx::Error> {
+        // NOTE: reserved-type entities are neither aircraft nor ground stations, there is nothing to record
+        if frame.has_reserved_entity() {
+            return Ok(());
+        }
+
         if let Some(ref db) = self.db {
-            let (aircraft, ground_station, from_ground_station) = if frame.src.is_ground_station() {
-                (frame.dst.as_ref(), Some(&frame.src), true)
+            let (aircraft, ground_station) = if frame.src.is_ground_station() {
+                (frame.dst.as_ref(), Some(&frame.src))
             } else {
-                (Some(&frame.src), frame.dst.as_ref(), false)
+                (Some(&frame.src), frame.dst.as_ref())
+            };
+            // NOTE: broadcast frames still count towards frequency and ground station stats, just not as an aircraft
+            let aircraft = aircraft.filter(|x| !x.is_ground_station() && (self.keep_broadcast || !x.is_broadcast()));
+            let from_ground_station = frame.from_ground_station.unwrap_or(frame.src.is_ground_station());
+
+            // NOTE: recorded ahead of the ground station checks, VHF ACARS never identifies the ground station
+            let icao_id = match aircraft {
+                Some(aircraft) => self.upsert_aircraft(db, aircraft).await?,
+                None => None,
+            };
+
+            // NOTE: skipped rather than returned as an error, so odd frames never count towards --state-db-auto-recover
+            let Some(ground_station) = ground_station.filter(|x| x.is_ground_station()) else {
+                warn!(
+                    "Not recording frame without a ground station, src = {} {:?}, dst = {:?}",
+                    frame.src.kind,
+                    frame.src.icao,
+                    frame.dst.as_ref().map(|x| (x.kind.as_str(), x.icao.as_deref())),
+                );
+                return Ok(());
             };
-            let ground_station = ground_station.unwrap();
             let Some(gs_id) = ground_station.id else {
-                return Err(sqlx::Error::TypeNotFound { type_name: String::from("Unexpected ground station with no ID") })    
+                // NOTE: anonymous ground stations (VHF ACARS) are expected, only ones with an address are suspicious
+                if ground_station.icao.is_some() {
+                    warn!("Not recording frame from ground station with no ID: {:?}", ground_station.icao);
+                }
+                return Ok(());
             };
 
             if from_ground_station {
@@ -176,44 +477,58 @@ impl StateDB {
             .execute(db)
             .await?;
 
+            let hour = DateTime::parse_from_rfc3339(frame.timestamp.as_str())
+                .map(|x| x.with_timezone(&Utc))
+                .unwrap_or(Utc::now())
+                .format("%Y-%m-%dT%H:00:00Z")
+                .to_string();
+            for (metric, key) in [
+                (HOURLY_STATS_METRIC_FREQ, (frame.freq * 1000.0) as u32),
+                (HOURLY_STATS_METRIC_GS, gs_id),
+            ] {
+                sqlx::query(
+                    "
+                    INSERT INTO hourly_stats (hour, metric, key, count) VALUES (?, ?, ?, 1)
+                    ON CONFLICT (hour, metric, key) DO UPDATE SET count = count + 1
+                    "
+                )
+                .bind(&hour)
+                .bind(metric)
+                .bind(key)
+                .execute(db)
+                .await?;
+            }
+
             if let Some(aircraft) = aircraft {
-                let icao_addr = aircraft.icao.clone();
-                let icao_id = if let Some(ref addr) = icao_addr {
-                    match u32::from_str_radix(addr.as_str(), 16) {
-                        Ok(v) => Some(v),
-                        Err(e) => {
-                            debug!(
-                                "Failed to convert ICAO hex to number for {}: {}",
-                                addr,
-                                e.to_string()
-                            );
-                            None
-                        }
+                if let Some(ref coords) = aircraft.coords {
+                    if self.is_event_throttled(db, icao_id, frame.indexed.timestamp.as_str()).await? {
+                        trace!(
+                            "Skipping aircraft event for {:?}, last event is less than {} seconds old",
+                            aircraft.icao,
+                            self.min_event_interval_secs
+                        );
+                        return Ok(());
                     }
-                } else {
-                    None
-                };
 
-                if icao_addr.is_some() && icao_id.is_some() {
-                    sqlx::query(
-                        "
-                        INSERT INTO aircrafts (icao, addr, tail, msg_count) VALUES (?, ?, ?, 1)
-                        ON CONFLICT (icao) DO UPDATE SET tail = ?, msg_count = msg_count + 1
-                        ",
-                    )
-                    .bind(icao_id.unwrap())
-                    .bind(icao_addr.unwrap())
-                    .bind(&aircraft.tail)
-                    .bind(&aircraft.tail)
-                    .execute(db)
-                    .await?;
-                }
+                    let gs_coords = if let Some(ref gs_coords) = ground_station.coords {
+                        Some((gs_coords.y, gs_coords.x))
+                    } else {
+                        sqlx::query_as::<_, (Option<f64>, Option<f64>)>(
+                            "
+                            SELECT latitude, longitude FROM ground_stations WHERE id = ?
+                            "
+                        )
+                        .bind(gs_id)
+                        .fetch_optional(db)
+                        .await?
+                        .and_then(|(lat, lon)| lat.zip(lon))
+                    };
+                    let distance_km = gs_coords.map(|(gs_lat, gs_lon)| haversine_km(coords.y, coords.x, gs_lat, gs_lon));
 
-                if let Some(ref coords) = aircraft.coords {
                     let result = sqlx::query(
                         "
-                        INSERT INTO aircraft_events (aircraft_icao, gs_id, callsign, tail, ts, signal, freq_mhz, latitude, longitude, altitude)
-                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                        INSERT INTO aircraft_events (aircraft_icao, gs_id, callsign, tail, ts, signal, freq_mhz, latitude, longitude, altitude, flight_id, flight_leg_num, distance_km, bit_rate, slot)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                         "
                     )
                     .bind(icao_id)
@@ -226,11 +541,32 @@ impl StateDB {
                     .bind(coords.y)
                     .bind(coords.x)
                     .bind(coords.z)
+                    .bind(&frame.indexed.flight_id)
+                    .bind(frame.indexed.flight_leg_num)
+                    .bind(distance_km)
+                    .bind(frame.metadata.hfdl.as_ref().and_then(|x| x.bit_rate))
+                    .bind(frame.metadata.hfdl.as_ref().and_then(|x| x.slot.as_ref()).map(|x| x.as_str()))
                     .execute(db)
                     .await?;
 
                     let aircraft_event_id = result.last_insert_rowid();
 
+                    if let Ok(ts) = DateTime::parse_from_rfc3339(frame.indexed.timestamp.as_str()) {
+                        self.summary_cache.write().await.insert(LatestAircraftEvent {
+                            ts: ts.with_timezone(&Utc),
+                            icao_addr: icao_id.map(|x| format!("{:06x}", x)),
+                            callsign: aircraft.callsign.clone(),
+                            tail: aircraft.tail.clone(),
+                            gs_id,
+                            signal: frame.signal as f64,
+                            freq_mhz: frame.freq,
+                            latitude: coords.y,
+                            longitude: coords.x,
+                            altitude: Some(coords.z as u32),
+                            prev_coords: None,
+                        });
+                    }
+
                     for path in frame.paths.iter() {
                         if let Some(gs_id) = path.party.id {
                             sqlx::query(
@@ -245,9 +581,146 @@ impl StateDB {
                             .await?;
                         }
                     }
+
+                    // NOTE: unlike propagation_events, recorded whether or not a propagation path could be computed
+                    if let Some(ref hfdl) = frame.metadata.hfdl {
+                        for entry in hfdl.heard_on.iter() {
+                            sqlx::query(
+                                "
+                                INSERT INTO heard_on_events (aircraft_events_id, gs_id) VALUES (?, ?)
+                                ON CONFLICT DO NOTHING
+                                "
+                            )
+                            .bind(aircraft_event_id)
+                            .bind(entry.id)
+                            .execute(db)
+                            .await?;
+                        }
+                    }
                 }
             }
         }
         Ok(())
     }
+
+    async fn upsert_aircraft(&self, db: &SqlitePool, aircraft: &Entity) -> Result<Option<u32>, sqlx::Error> {
+        let Some(ref addr) = aircraft.icao else {
+            return Ok(None);
+        };
+        let icao_id = match u32::from_str_radix(addr.as_str(), 16) {
+            Ok(v) => v,
+            Err(e) => {
+                debug!(
+                    "Failed to convert ICAO hex to number for {}: {}",
+                    addr,
+                    e.to_string()
+                );
+                return Ok(None);
+            }
+        };
+
+        sqlx::query(
+            "
+            INSERT INTO aircrafts (icao, addr, tail, msg_count) VALUES (?, ?, ?, 1)
+            ON CONFLICT (icao) DO UPDATE SET tail = ?, msg_count = msg_count + 1
+            ",
+        )
+        .bind(icao_id)
+        .bind(addr)
+        .bind(&aircraft.tail)
+        .bind(&aircraft.tail)
+        .execute(db)
+        .await?;
+
+        Ok(Some(icao_id))
+    }
+
+    // NOTE: frames whose timestamp cannot be parsed are never throttled, neither are aircraft without an ICAO address
+    async fn is_event_throttled(&self, db: &SqlitePool, icao_id: Option<u32>, ts: &str) -> Result<bool, sqlx::Error> {
+        if self.min_event_interval_secs == 0 {
+            return Ok(false);
+        }
+        let Some(icao_id) = icao_id else {
+            return Ok(false);
+        };
+        let Ok(ts) = DateTime::parse_from_rfc3339(ts) else {
+            return Ok(false);
+        };
+
+        let last_ts: Option<String> = sqlx::query_scalar(
+            "
+            SELECT max(ts) FROM aircraft_events WHERE aircraft_icao = ?
+            "
+        )
+        .bind(icao_id)
+        .fetch_one(db)
+        .await?;
+        let Some(last_ts) = last_ts.and_then(|x| DateTime::parse_from_rfc3339(x.as_str()).ok()) else {
+            return Ok(false);
+        };
+
+        let elapsed = ts.signed_duration_since(last_ts).num_seconds();
+        Ok(elapsed >= 0 && (elapsed as u64) < self.min_event_interval_secs)
+    }
+}
+
+pub fn create_vacuum_task(
+    state_db: Data<RwLock<StateDB>>,
+    interval: Duration,
+    cancel_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = time::interval_at(Instant::now() + interval, interval);
+
+        loop {
+            select! {
+                _ = ticker.tick() => {
+                    if let Err(e) = state_db.write().await.vacuum().await {
+                        warn!("Failed to vacuum state DB: {}", e.to_string());
+                    }
+                }
+                _ = cancel_token.cancelled() => {
+                    debug!("Vacuum task got cancel request");
+                    return;
+                }
+            }
+        }
+    })
+}
+
+// NOTE: reloads every summary the flight API asked for each TTL, so reads never wait on an expired cache
+pub fn create_summary_cache_task(
+    state_db: Data<RwLock<StateDB>>,
+    interval: Duration,
+    cancel_token: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = time::interval_at(Instant::now() + interval, interval);
+
+        loop {
+            select! {
+                _ = ticker.tick() => {
+                    let (db, summary_cache) = {
+                        let state_db = state_db.read().await;
+                        (state_db.db_read_pool().cloned(), state_db.summary_cache())
+                    };
+                    let Some(db) = db else {
+                        continue;
+                    };
+
+                    let fields = summary_cache.read().await.wanted_fields();
+                    for field in fields.iter() {
+                        match load_latest_events(&db, field.as_str(), None).await {
+                            Ok(events) => summary_cache.write().await.replace(field.as_str(), &events),
+                            Err(e) => warn!("Failed to refresh {} flight summary cache: {}", field, e.to_string()),
+                        }
+                    }
+                }
+                _ = cancel_token.cancelled() => {
+                    debug!("Summary cache task got cancel request");
+                    return;
+                }
+            }
+        }
+    })
 }
@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+pub const DEFAULT_SUMMARY_CACHE_TTL_SECS: u64 = 60;
+pub const MAX_SUMMARY_CACHE_TTL_SECS: u64 = 24 * 3600;
+
+pub const FIELD_AIRCRAFT_ICAO: &'static str = "aircraft_icao";
+pub const FIELD_CALLSIGN: &'static str = "callsign";
+pub const FIELD_TAIL: &'static str = "tail";
+
+#[derive(Clone, Debug)]
+pub struct LatestAircraftEvent {
+    pub ts: DateTime<Utc>,
+
+    pub icao_addr: Option<String>,
+    pub callsign: Option<String>,
+    pub tail: Option<String>,
+
+    pub gs_id: u32,
+
+    pub signal: f64,
+    pub freq_mhz: f64,
+
+    pub latitude: f64,
+    pub longitude: f64,
+
+    pub altitude: Option<u32>,
+
+    pub prev_coords: Option<(f64, f64)>,
+}
+
+impl LatestAircraftEvent {
+    pub fn key(&self, field: &str) -> Option<Option<String>> {
+        match field {
+            FIELD_AIRCRAFT_ICAO => Some(self.icao_addr.clone()),
+            FIELD_CALLSIGN => Some(self.callsign.clone()),
+            FIELD_TAIL => Some(self.tail.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct LatestAircraftEventRow {
+    ts: DateTime<Utc>,
+
+    icao_addr: Option<String>,
+    callsign: Option<String>,
+    tail: Option<String>,
+
+    gs_id: u32,
+
+    signal: f64,
+    freq_mhz: f64,
+
+    latitude: f64,
+    longitude: f64,
+
+    altitude: Option<u32>,
+
+    prev_latitude: Option<f64>,
+    prev_longitude: Option<f64>,
+}
+
+// NOTE: the since bound is applied before the window function so the aircraft_events ts index limits the scan,
+//       which also means the previous coordinates only come from events inside the window
+pub async fn load_latest_events(
+    db: &SqlitePool,
+    field: &str,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<LatestAircraftEvent>, sqlx::Error> {
+    let query = format!("
+        WITH grouped_events AS (
+            SELECT ROW_NUMBER() OVER (PARTITION BY ae.{} ORDER BY ae.ts DESC) AS row, ae.* FROM aircraft_events ae
+            {}
+        )
+        SELECT 
+            ge.*, 
+            ge2.latitude AS prev_latitude, 
+            ge2.longitude AS prev_longitude,
+            iif(ge.aircraft_icao IS NULL, NULL, printf('%06x', ge.aircraft_icao)) AS icao_addr
+        FROM grouped_events ge
+        LEFT JOIN grouped_events ge2 ON ge.{} = ge2.{} AND ge2.row = 2
+        WHERE ge.row = 1 
+        ORDER BY ge.ts DESC
+    ", field, if since.is_some() { "WHERE ae.ts >= ?" } else { "" }, field, field);
+
+    let mut query_builder = sqlx::query_as::<_, LatestAircraftEventRow>(query.as_str());
+    if let Some(since) = since {
+        query_builder = query_builder.bind(since);
+    }
+
+    Ok(query_builder
+        .fetch_all(db)
+        .await?
+        .into_iter()
+        .map(|x| LatestAircraftEvent {
+            ts: x.ts,
+            icao_addr: x.icao_addr,
+            callsign: x.callsign,
+            tail: x.tail,
+            gs_id: x.gs_id,
+            signal: x.signal,
+            freq_mhz: x.freq_mhz,
+            latitude: x.latitude,
+            longitude: x.longitude,
+            altitude: x.altitude,
+            prev_coords: match (x.prev_latitude, x.prev_longitude) {
+                (Some(x), Some(y)) => Some((x, y)),
+                (None, _) | (_, None) => None,
+            },
+        })
+        .collect())
+}
+
+pub struct SummaryCache {
+    ttl: Duration,
+    summaries: HashMap<String, HashMap<Option<String>, LatestAircraftEvent>>,
+
+    // NOTE: fields the flight summary API asked for, which the refresh task keeps loaded even after an invalidate
+    wanted: HashSet<String>,
+}
+
+impl SummaryCache {
+    pub fn new(ttl_secs: u64) -> SummaryCache {
+        SummaryCache {
+            ttl: Duration::from_secs(ttl_secs.min(MAX_SUMMARY_CACHE_TTL_SECS)),
+            summaries: HashMap::new(),
+            wanted: HashSet::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        self.is_enabled().then_some(self.ttl)
+    }
+
+    pub fn want(&mut self, field: &str) {
+        if self.is_enabled() {
+            self.wanted.insert(field.to_string());
+        }
+    }
+
+    pub fn wanted_fields(&self) -> Vec<String> {
+        self.wanted.iter().cloned().collect()
+    }
+
+    // NOTE: entries don't expire on read, the refresh task reloads them every TTL and ingestion keeps them current
+    //       in between, so a request never pays for a full reload
+    pub fn get(&self, field: &str) -> Option<Vec<LatestAircraftEvent>> {
+        let Some(summary) = self.summaries.get(field) else {
+            return None;
+        };
+
+        let mut events: Vec<LatestAircraftEvent> = summary.values().cloned().collect();
+        events.sort_by(|x, y| y.ts.cmp(&x.ts));
+        Some(events)
+    }
+
+    pub fn replace(&mut self, field: &str, events: &Vec<LatestAircraftEvent>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.summaries.insert(
+            field.to_string(),
+            events
+                .iter()
+                .filter_map(|x| x.key(field).map(|key| (key, x.clone())))
+                .collect(),
+        );
+    }
+
+    pub fn insert(&mut self, event: LatestAircraftEvent) {
+        for (field, summary) in self.summaries.iter_mut() {
+            let Some(key) = event.key(field.as_str()) else {
+                continue;
+            };
+
+            match summary.get_mut(&key) {
+                Some(latest) => {
+                    if event.ts >= latest.ts {
+                        let prev_coords = Some((latest.latitude, latest.longitude));
+                        *latest = LatestAircraftEvent {
+                            prev_coords,
+                            ..event.clone()
+                        };
+                    }
+                }
+                None => {
+                    summary.insert(
+                        key,
+                        LatestAircraftEvent {
+                            prev_coords: None,
+                            ..event.clone()
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn invalidate(&mut self) {
+        self.summaries.clear();
+    }
+}
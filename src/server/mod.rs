@@ -1,28 +1,40 @@
 use actix_web::web::Data;
 use actix_web::{middleware, App, HttpServer};
+use chrono::Utc;
 use clap::{arg, ArgMatches, Command};
 use elasticsearch::Elasticsearch;
 use log::*;
 use reqwest::Url;
 use serde_valid::Validate;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::io::{self, AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
 use tokio::net::TcpListener;
 use tokio::select;
 use tokio::signal::unix::{signal, SignalKind};
-use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
-use tokio::time::{self, Duration};
+use tokio::time::{self, Duration, Instant};
 use tokio_util::sync::CancellationToken;
 
 use crate::common;
 use crate::common::arguments::{
-    parse_disable_cross_site, parse_disable_state_db, parse_elastic_index, parse_elastic_url,
-    parse_listen_host, parse_listen_port, parse_state_db_url,
+    parse_api_token, parse_disable_cross_site, parse_disable_state_db, parse_elastic_index, parse_elastic_url, parse_es_exclude_fields, parse_coord_precision,
+    parse_http_timeout, parse_user_agent, parse_listen_host, parse_listen_port, parse_state_db_url,
+    parse_summary_cache_ttl, parse_frame_buffer, parse_backpressure_policy, parse_state_db_read_pool,
+    parse_state_db_auto_recover, parse_keep_broadcast, parse_min_event_interval, parse_max_future_skew, parse_auto_vacuum_interval, parse_shutdown_timeout, parse_swarm_token, DEFAULT_ELASTIC_INDEX,
 };
 use crate::common::batcher::create_es_batch_task;
-use crate::common::es_utils::create_es_client;
-use crate::common::frame::CommonFrame;
-use crate::server::db::StateDB;
+use crate::common::es_utils::{create_es_client, EsIndexTarget};
+use crate::common::events::HeartbeatMessage;
+use crate::common::frame::{CommonFrame, CFF_SCHEMA_VERSION};
+use crate::common::frame_queue::frame_channel;
+use crate::common::frame_schema::FrameSchema;
+use crate::common::middleware::ServerApiToken;
+use crate::common::swarm_auth::{verify_swarm_auth_line, DEFAULT_SWARM_AUTH_TIMEOUT_SECS, SWARM_MSGPACK_LINE};
+use crate::server::db::{create_summary_cache_task, create_vacuum_task, StateDB};
 use crate::server::services as server_services;
 
 pub mod db;
@@ -36,6 +48,8 @@ const DEFAULT_STATE_DB_URL: &'static str = "sqlite://state.sqlite3";
 pub const SERVER_COMMAND: &'static str = "server";
 pub const DEFAULT_INGEST_PORT: u16 = 5552;
 pub const DEFAULT_INACTIVE_TIMEOUT_SECS: u64 = 3600;
+pub const DEFAULT_MAX_FRAME_BYTES: u64 = 1048576;
+pub const DEFAULT_FUTURE_WARN_INTERVAL_SECS: u64 = 60;
 
 pub const DEFAULT_CHANNEL_BUFFER: usize = 4096;
 pub const DEFAULT_BATCH_WAIT_MS: u64 = 200;
@@ -46,21 +60,282 @@ pub fn get_server_arguments() -> Command {
             .about("Aggregator server mode")
             .args(&[
                 arg!(--tcp <PORT> "TCP port to listen for frames on (default: 5552)"),
-                arg!(--"inactive-timeout" <SECONDS> "Disconnect client if inactive for specified seconds (default: 60)")
+                arg!(--"api-host" <HOST> "Host for API server to listen on (default: --listen-host)"),
+                arg!(--"api-port" <PORT> "Port for API server to listen on (default: --listen-port)"),
+                arg!(--"ingest-host" <HOST> "Host for TCP frame ingest to listen on (default: --listen-host)"),
+                arg!(--"ingest-port" <PORT> "Port for TCP frame ingest to listen on (default: --tcp)"),
+                arg!(--"inactive-timeout" <SECONDS> "Disconnect client if inactive for specified seconds (default: 60)"),
+                arg!(--"max-frame-bytes" <BYTES> "Disconnect client if a single frame exceeds specified bytes (default: 1048576)"),
+                arg!(--"swarm-auth-timeout" <SECONDS> "Disconnect client if it does not authenticate with --swarm-token within specified seconds (default: 10)"),
+                arg!(--stdin "Also read newline delimited common JSON frames from STDIN, with --disable-tcp the server exits once STDIN is closed"),
+                arg!(--"disable-tcp" "Do not listen for frames over TCP (use with --stdin)"),
+                arg!(--"min-frame-freq-mhz" <MHZ> "Drop ingested frames with a frequency below specified MHz"),
+                arg!(--"max-frame-freq-mhz" <MHZ> "Drop ingested frames with a frequency above specified MHz"),
+                arg!(--"frame-schema" <FILE> "Drop ingested frames that do not conform to this JSON Schema, checked in addition to the built-in validation"),
             ]),
     )
 }
 
+#[derive(Clone)]
+struct FrameFreqFilter {
+    min_mhz: Option<f64>,
+    max_mhz: Option<f64>,
+    rejected: Arc<AtomicU64>,
+}
+
+impl FrameFreqFilter {
+    fn new(args: &ArgMatches) -> Result<FrameFreqFilter, io::Error> {
+        let min_mhz = Self::parse_mhz(args, "min-frame-freq-mhz")?;
+        let max_mhz = Self::parse_mhz(args, "max-frame-freq-mhz")?;
+        if let (Some(min), Some(max)) = (min_mhz, max_mhz) {
+            if min > max {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("--min-frame-freq-mhz {} is above --max-frame-freq-mhz {}", min, max),
+                ));
+            }
+        }
+
+        Ok(FrameFreqFilter {
+            min_mhz,
+            max_mhz,
+            rejected: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    fn parse_mhz(args: &ArgMatches, arg: &str) -> Result<Option<f64>, io::Error> {
+        let Some(raw) = args.get_one::<String>(arg) else {
+            return Ok(None);
+        };
+
+        match raw.parse::<f64>() {
+            Ok(x) if x.is_finite() && x >= 0.0 => Ok(Some(x)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid --{} {}, expected a non-negative frequency in MHz", arg, raw),
+            )),
+        }
+    }
+
+    fn accepts(&self, frame: &CommonFrame, source: &str) -> bool {
+        let below = self.min_mhz.map_or(false, |x| frame.freq < x);
+        let above = self.max_mhz.map_or(false, |x| frame.freq > x);
+        if !below && !above {
+            return true;
+        }
+
+        let rejected = self.rejected.fetch_add(1, Ordering::Relaxed) + 1;
+        warn!(
+            "Dropping frame from feeder {} with out of range frequency {} MHz ({} rejected so far)",
+            source, frame.freq, rejected
+        );
+        false
+    }
+}
+
+// NOTE: a feeder with a bad clock sends every frame ahead, so each feeder is only warned about once a minute
+#[derive(Clone)]
+struct FrameFutureFilter {
+    max_skew_secs: u64,
+    rejected: Arc<AtomicU64>,
+    last_warned: Arc<StdMutex<HashMap<String, Instant>>>,
+}
+
+impl FrameFutureFilter {
+    fn new(args: &ArgMatches) -> FrameFutureFilter {
+        FrameFutureFilter {
+            max_skew_secs: parse_max_future_skew(args),
+            rejected: Arc::new(AtomicU64::new(0)),
+            last_warned: Arc::new(StdMutex::new(HashMap::new())),
+        }
+    }
+
+    fn accepts(&self, frame: &CommonFrame, source: &str) -> bool {
+        if self.max_skew_secs == 0 {
+            return true;
+        }
+        let secs_ahead = frame.secs_ahead_of(Utc::now());
+        if secs_ahead <= self.max_skew_secs as i64 {
+            return true;
+        }
+
+        let rejected = self.rejected.fetch_add(1, Ordering::Relaxed) + 1;
+        let should_warn = match self.last_warned.lock() {
+            Ok(mut last_warned) => match last_warned.get(source) {
+                Some(x) if x.elapsed() < Duration::from_secs(DEFAULT_FUTURE_WARN_INTERVAL_SECS) => false,
+                _ => {
+                    last_warned.insert(source.to_string(), Instant::now());
+                    true
+                }
+            },
+            Err(_) => true,
+        };
+        if should_warn {
+            warn!(
+                "Rejecting frame from feeder {} timestamped {}s in the future, check its clock ({} rejected so far)",
+                source, secs_ahead, rejected
+            );
+        }
+        false
+    }
+}
+
+// NOTE: schema version mismatches are only warned about once per feeder to avoid flooding the log
+fn parse_frame(
+    msg: &str,
+    source: &str,
+    version_warned: &mut bool,
+    freq_filter: &FrameFreqFilter,
+    future_filter: &FrameFutureFilter,
+    frame_schema: &Option<Arc<FrameSchema>>,
+) -> Option<CommonFrame> {
+    let frame = match serde_json::from_str::<CommonFrame>(msg) {
+        Ok(frame) => frame,
+        Err(e) => {
+            if let Ok(message) = serde_json::from_str::<HeartbeatMessage>(msg) {
+                debug!(
+                    "Heartbeat from feeder {} ({}): band = {}, uptime = {}s",
+                    source, message.heartbeat.module, message.heartbeat.listening_band, message.heartbeat.uptime_secs
+                );
+                return None;
+            }
+
+            error!("Malformed common frame: {}", e.to_string());
+            return None;
+        }
+    };
+
+    if frame.schema_version != CFF_SCHEMA_VERSION && !*version_warned {
+        warn!(
+            "Feeder {} is {} (common frame schema version {}, expected {}), mismatched fields may be dropped or misinterpreted",
+            source,
+            if frame.schema_version < CFF_SCHEMA_VERSION { "older" } else { "newer" },
+            frame.schema_version,
+            CFF_SCHEMA_VERSION
+        );
+        *version_warned = true;
+    }
+
+    if let Err(e) = frame.validate() {
+        error!("Common Frame failed validation: {}", e.to_string());
+        return None;
+    }
+
+    if let Some(ref frame_schema) = frame_schema {
+        if let Err(e) = frame_schema.validate(msg) {
+            error!("Common frame from feeder {} does not conform to {}: {}", source, frame_schema.path().to_string_lossy(), e);
+            return None;
+        }
+    }
+
+    if !freq_filter.accepts(&frame, source) {
+        return None;
+    }
+
+    if !future_filter.accepts(&frame, source) {
+        return None;
+    }
+
+    Some(frame)
+}
+
+// NOTE: a clean EOF is only expected between messages, an EOF within the length prefix counts as one too
+pub(crate) async fn read_length_prefixed<R: AsyncRead + Unpin>(reader: &mut R, max_frame_bytes: u64) -> Result<Option<Vec<u8>>, io::Error> {
+    let len = match reader.read_u32().await {
+        Ok(x) => x as u64,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if len > max_frame_bytes {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame of {} bytes exceeds {} bytes", len, max_frame_bytes),
+        ));
+    }
+
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    Ok(Some(payload))
+}
+
 pub async fn start(args: &ArgMatches) {
+    let read_stdin = args.get_flag("stdin");
+    let shutdown_timeout = parse_shutdown_timeout(args);
+    let disable_tcp = args.get_flag("disable-tcp");
+    if disable_tcp && !read_stdin {
+        error!("TCP ingest is disabled without --stdin, there is no source of frames");
+        return;
+    }
+
     let listen_host = parse_listen_host(args, DEFAULT_LISTEN_HOST);
-    let listen_port = parse_listen_port(args, DEFAULT_LISTEN_PORT);
 
-    let ingest_port: u16 = parse_listen_port(args, DEFAULT_INGEST_PORT);
+    let api_host = args
+        .get_one::<String>("api-host")
+        .unwrap_or(&listen_host)
+        .clone();
+    let api_port = args
+        .get_one::<String>("api-port")
+        .unwrap_or(&String::from("default"))
+        .parse::<u16>()
+        .unwrap_or(parse_listen_port(args, DEFAULT_LISTEN_PORT));
+
+    let ingest_host = args
+        .get_one::<String>("ingest-host")
+        .unwrap_or(&listen_host)
+        .clone();
+    let ingest_port = args
+        .get_one::<String>("ingest-port")
+        .or(args.get_one::<String>("tcp"))
+        .unwrap_or(&String::from("default"))
+        .parse::<u16>()
+        .unwrap_or(parse_listen_port(args, DEFAULT_INGEST_PORT));
     let inactive_timeout_secs: u64 = args
         .get_one::<String>("inactive-timeout")
         .unwrap_or(&String::from("default"))
         .parse::<u64>()
         .unwrap_or(DEFAULT_INACTIVE_TIMEOUT_SECS);
+    let max_frame_bytes: u64 = args
+        .get_one::<String>("max-frame-bytes")
+        .unwrap_or(&String::from("default"))
+        .parse::<u64>()
+        .unwrap_or(DEFAULT_MAX_FRAME_BYTES);
+    let freq_filter = match FrameFreqFilter::new(args) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}", e.to_string());
+            return;
+        }
+    };
+    let future_filter = FrameFutureFilter::new(args);
+    let frame_schema = match args.get_one::<String>("frame-schema").map(PathBuf::from) {
+        Some(path) => match FrameSchema::load(&path) {
+            Ok(v) => {
+                info!("Validating ingested frames against JSON schema {}", path.to_string_lossy());
+                Some(Arc::new(v))
+            }
+            Err(e) => {
+                error!("{}", e.to_string());
+                return;
+            }
+        },
+        None => None,
+    };
+    let api_token = match parse_api_token(args) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}", e.to_string());
+            return;
+        }
+    };
+    let swarm_token = parse_swarm_token(args).cloned();
+    let swarm_auth_timeout_secs: u64 = args
+        .get_one::<String>("swarm-auth-timeout")
+        .unwrap_or(&String::from("default"))
+        .parse::<u64>()
+        .unwrap_or(DEFAULT_SWARM_AUTH_TIMEOUT_SECS);
+    if swarm_token.is_some() {
+        info!("Swarm authentication enabled, clients must authenticate within {} seconds", swarm_auth_timeout_secs);
+    }
 
     let mut elastic_url = if let Some(raw_url) = parse_elastic_url(args) {
         match Url::parse(raw_url) {
@@ -76,8 +351,30 @@ pub async fn start(args: &ArgMatches) {
     } else {
         None
     };
-    let elastic_index = parse_elastic_index(args);
+    let elastic_index = parse_elastic_index(args, DEFAULT_ELASTIC_INDEX);
     let validate_es_cert = args.get_flag("validate-es-cert");
+    let es_exclude_fields = match parse_es_exclude_fields(args) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}", e.to_string());
+            return;
+        }
+    };
+    let coord_precision = match parse_coord_precision(args) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}", e.to_string());
+            return;
+        }
+    };
+    let http_timeout = match parse_http_timeout(args) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}", e.to_string());
+            return;
+        }
+    };
+    let user_agent = parse_user_agent(args);
 
     let state_db_url = match Url::parse(parse_state_db_url(args, DEFAULT_STATE_DB_URL).as_str()) {
         Ok(v) => {
@@ -96,11 +393,19 @@ pub async fn start(args: &ArgMatches) {
         debug!("State DB disabled");
     }
 
-    let state_db = match StateDB::new(if disable_state_db {
-        None
-    } else {
-        Some(state_db_url.to_string())
-    })
+    let state_db = match StateDB::new(
+        if disable_state_db {
+            None
+        } else {
+            Some(state_db_url.to_string())
+        },
+        parse_summary_cache_ttl(args),
+        parse_state_db_read_pool(args),
+        parse_state_db_auto_recover(args),
+        parse_min_event_interval(args),
+        coord_precision,
+        parse_keep_broadcast(args),
+    )
     .await
     {
         Ok(v) => Data::new(RwLock::new(v)),
@@ -111,19 +416,46 @@ pub async fn start(args: &ArgMatches) {
     };
 
     let cancel_token = CancellationToken::new();
+    if let Some(interval) = parse_auto_vacuum_interval(args) {
+        if !disable_state_db {
+            info!("State DB auto vacuum enabled, every {} hours", interval.as_secs() / 3600);
+            create_vacuum_task(state_db.clone(), interval, cancel_token.clone());
+        }
+    }
+    if let Some(interval) = state_db.read().await.summary_cache().read().await.refresh_interval() {
+        if !disable_state_db {
+            create_summary_cache_task(state_db.clone(), interval, cancel_token.clone());
+        }
+    }
     let http_cancel_token = cancel_token.clone();
     let ingest_cancel_token = cancel_token.clone();
 
     let http_state_db = state_db.clone();
-    let http_listen_host = listen_host.clone();
-    let http_listen_port = listen_port.clone();
+    let http_api_token = Data::new(ServerApiToken(api_token));
+    let http_es_index_target = elastic_url.as_ref().and_then(|es_url| {
+        match EsIndexTarget::new(es_url, validate_es_cert, http_timeout, user_agent.as_str(), &elastic_index) {
+            Ok(x) => Some(Data::new(x)),
+            Err(e) => {
+                warn!("Failed to create ES client for the API to {}: {}", es_url, e.to_string());
+                None
+            }
+        }
+    });
+    let http_listen_host = api_host.clone();
+    let http_listen_port = api_port.clone();
 
-    let http_thread = tokio::spawn(async move {
+    let mut http_thread = tokio::spawn(async move {
         let restricted_origin = format!("http://{}:{}", http_listen_host, http_listen_port);
 
         let server = HttpServer::new(move || {
             App::new()
                 .app_data(http_state_db.clone())
+                .app_data(http_api_token.clone())
+                .configure(|cfg| {
+                    if let Some(ref target) = http_es_index_target {
+                        cfg.app_data(target.clone());
+                    }
+                })
                 .wrap(middleware::DefaultHeaders::new().add((
                     "Access-Control-Allow-Origin",
                     if disable_cross_site {
@@ -152,93 +484,250 @@ pub async fn start(args: &ArgMatches) {
         }
     });
 
-    let (tx, mut rx) = mpsc::channel::<CommonFrame>(DEFAULT_CHANNEL_BUFFER);
+    let frame_buffer = parse_frame_buffer(args, DEFAULT_CHANNEL_BUFFER);
+    let backpressure_policy = match parse_backpressure_policy(args) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Invalid backpressure policy: {}", e.to_string());
+            return;
+        }
+    };
+    let (tx, mut rx) = frame_channel(frame_buffer, backpressure_policy);
+
+    let stdin_closed_token = CancellationToken::new();
+    if read_stdin {
+        let stdin_tx = tx.clone();
+        let stdin_closed_token = stdin_closed_token.clone();
+        let freq_filter = freq_filter.clone();
+        let future_filter = future_filter.clone();
+        let frame_schema = frame_schema.clone();
+
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(io::stdin());
+            let mut frames: u64 = 0;
+            let mut version_warned = false;
+
+            info!("Reading frames from STDIN");
+
+            loop {
+                let mut msg = String::new();
+
+                match reader.read_line(&mut msg).await {
+                    Ok(0) => break,
+                    Ok(size) => {
+                        if size as u64 > max_frame_bytes {
+                            warn!("Skipping STDIN frame exceeding {} bytes ({} bytes read)", max_frame_bytes, size);
+                            continue;
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to read from STDIN: {}", e.to_string());
+                        break;
+                    }
+                }
 
-    let ingest_thread = tokio::spawn(async move {
-        let listener = match TcpListener::bind(format!("{}:{}", listen_host, ingest_port)).await {
-            Ok(x) => x,
-            Err(e) => {
-                error!(
-                    "Failed to listen on {}:{} => {}",
-                    listen_host,
-                    ingest_port,
-                    e.to_string()
-                );
-                return;
-            }
-        };
+                if msg.trim().is_empty() {
+                    continue;
+                }
 
-        info!(
-            "Aggregator server listening on {}:{}",
-            listen_host, ingest_port
-        );
+                let Some(frame) = parse_frame(&msg, "STDIN", &mut version_warned, &freq_filter, &future_filter, &frame_schema) else {
+                    continue;
+                };
 
-        loop {
-            select! {
-                Ok((client, client_addr)) = listener.accept() => {
-                    info!("New client from {} accepted.", client_addr.ip());
+                match stdin_tx.send(frame).await {
+                    Ok(true) => debug!("Frame buffer full, {} frames dropped so far", stdin_tx.dropped()),
+                    Ok(false) => frames += 1,
+                    Err(e) => {
+                        error!("Failed to send common frame to parse thread: {}", e.to_string());
+                        break;
+                    }
+                }
+            }
 
-                    let tx = tx.clone();
+            info!("STDIN closed after {} frames", frames);
+            stdin_closed_token.cancel();
+        });
+    }
 
-                    tokio::spawn(async move {
-                        let mut reader = BufReader::new(client);
+    let ingest_thread = if disable_tcp {
+        None
+    } else {
+        Some(tokio::spawn(async move {
+            let listener = match TcpListener::bind(format!("{}:{}", ingest_host, ingest_port)).await {
+                Ok(x) => x,
+                Err(e) => {
+                    error!(
+                        "Failed to listen on {}:{} => {}",
+                        ingest_host,
+                        ingest_port,
+                        e.to_string()
+                    );
+                    return;
+                }
+            };
 
-                        loop {
-                            let mut msg = String::new();
+            info!(
+                "Aggregator server listening on {}:{}",
+                ingest_host, ingest_port
+            );
+
+            loop {
+                select! {
+                    Ok((client, client_addr)) = listener.accept() => {
+                        info!("New client from {} accepted.", client_addr.ip());
+
+                        let tx = tx.clone();
+                        let freq_filter = freq_filter.clone();
+                        let future_filter = future_filter.clone();
+                        let frame_schema = frame_schema.clone();
+                        let swarm_token = swarm_token.clone();
+
+                        tokio::spawn(async move {
+                            let mut reader = BufReader::new(client);
+                            let client_ip = client_addr.ip().to_string();
+                            let mut version_warned = false;
+                            let mut msgpack = false;
+
+                            // NOTE: the auth line is bounded by max_frame_bytes like any frame so an unauthenticated client cannot exhaust memory
+                            if let Some(ref token) = swarm_token {
+                                let mut auth_line = String::new();
+                                let authenticated = match time::timeout(
+                                    Duration::from_secs(swarm_auth_timeout_secs),
+                                    (&mut reader).take(max_frame_bytes).read_line(&mut auth_line)
+                                ).await {
+                                    Ok(Ok(_)) => verify_swarm_auth_line(&auth_line, token),
+                                    _ => false,
+                                };
+
+                                if !authenticated {
+                                    warn!("Client from {} failed to authenticate, disconnecting", client_addr.ip());
+                                    return;
+                                }
+                                debug!("Client from {} authenticated", client_addr.ip());
+                            }
 
-                            let Ok(result) = time::timeout(
-                                Duration::from_secs(inactive_timeout_secs),
-                                reader.read_line(&mut msg)
-                            ).await else {
-                                info!("Client from {} idled for longer than {} seconds. ", client_addr.ip(), inactive_timeout_secs);
-                                break;
-                            };
+                            loop {
+                                if msgpack {
+                                    let Ok(result) = time::timeout(
+                                        Duration::from_secs(inactive_timeout_secs),
+                                        read_length_prefixed(&mut reader, max_frame_bytes)
+                                    ).await else {
+                                        info!("Client from {} idled for longer than {} seconds. ", client_addr.ip(), inactive_timeout_secs);
+                                        break;
+                                    };
+
+                                    let payload = match result {
+                                        Ok(Some(x)) => x,
+                                        Ok(None) => {
+                                            debug!("Got EOF, shutting down client socket");
+                                            break;
+                                        }
+                                        Err(e) => {
+                                            warn!("Failed to read MessagePack frame from client {}, disconnecting: {}", client_addr.ip(), e.to_string());
+                                            break;
+                                        }
+                                    };
+
+                                    // NOTE: decoded back to JSON so MessagePack frames go through the exact same validation as JSON ones
+                                    let msg = match rmp_serde::from_slice::<serde_json::Value>(&payload) {
+                                        Ok(x) => x.to_string(),
+                                        Err(e) => {
+                                            error!("Malformed MessagePack frame from {}: {}", client_addr.ip(), e.to_string());
+                                            continue;
+                                        }
+                                    };
+                                    if let Some(frame) = parse_frame(&msg, &client_ip, &mut version_warned, &freq_filter, &future_filter, &frame_schema) {
+                                        match tx.send(frame).await {
+                                            Ok(true) => debug!("Frame buffer full, {} frames dropped so far", tx.dropped()),
+                                            Ok(false) => {}
+                                            Err(e) => error!("Failed to send common frame to parse thread: {}", e.to_string()),
+                                        }
+                                    }
+                                    continue;
+                                }
 
-                            let Ok(size) = result else {
-                                debug!("Failed to unwrap result from read_line");
-                                break;
-                            };
+                                let mut msg = String::new();
+
+                                let Ok(result) = time::timeout(
+                                    Duration::from_secs(inactive_timeout_secs),
+                                    (&mut reader).take(max_frame_bytes + 1).read_line(&mut msg)
+                                ).await else {
+                                    info!("Client from {} idled for longer than {} seconds. ", client_addr.ip(), inactive_timeout_secs);
+                                    break;
+                                };
+
+                                // NOTE: a failed read (e.g. a reset right after the feeder's last write) still leaves what was read in msg,
+                                //       so it is handled like a final line without a trailing newline before closing
+                                let (size, read_err) = match result {
+                                    Ok(size) => (size, None),
+                                    Err(e) => (msg.len(), Some(e)),
+                                };
+
+                                if size == 0 {
+                                    match read_err {
+                                        Some(e) => debug!("Failed to read from client {}: {}", client_addr.ip(), e.to_string()),
+                                        None => debug!("Got EOF, shutting down client socket"),
+                                    }
+                                    break;
+                                }
 
-                            if size == 0 {
-                                debug!("Got EOF, shutting down client socket");
-                                break;
-                            }
+                                if size as u64 > max_frame_bytes {
+                                    warn!(
+                                        "Client from {} sent a frame exceeding {} bytes ({} bytes read), disconnecting",
+                                        client_addr.ip(),
+                                        max_frame_bytes,
+                                        size
+                                    );
+                                    break;
+                                }
 
-                            let frame = match serde_json::from_str::<CommonFrame>(&msg) {
-                                Ok(frame) => frame,
-                                Err(e) => {
-                                    error!("Malformed common frame: {}", e.to_string());
-                                    continue
+                                if msg.trim_end() == SWARM_MSGPACK_LINE {
+                                    debug!("Client from {} switched to MessagePack frames", client_addr.ip());
+                                    msgpack = true;
+                                    continue;
                                 }
-                            };
 
-                            if let Err(e) = frame.validate() {
-                                error!("Common Frame failed validation: {}", e.to_string());
-                                continue;
-                            }
+                                let partial = !msg.ends_with('\n');
+                                match parse_frame(&msg, &client_ip, &mut version_warned, &freq_filter, &future_filter, &frame_schema) {
+                                    Some(frame) => {
+                                        if partial {
+                                            info!("Recovered trailing frame without a newline from {}", client_addr.ip());
+                                        }
+
+                                        match tx.send(frame).await {
+                                            Ok(true) => debug!("Frame buffer full, {} frames dropped so far", tx.dropped()),
+                                            Ok(false) => {}
+                                            Err(e) => error!("Failed to send common frame to parse thread: {}", e.to_string()),
+                                        }
+                                    }
+                                    None if partial => warn!("Discarded unparseable trailing partial frame from {}", client_addr.ip()),
+                                    None => {}
+                                }
 
-                            if let Err(e) = tx.send(frame).await {
-                                error!("Failed to send common frame to parse thread: {}", e.to_string());
+                                if let Some(e) = read_err {
+                                    debug!("Failed to read from client {}: {}", client_addr.ip(), e.to_string());
+                                    break;
+                                }
                             }
-                        }
-                    });
-                }
-                _ = ingest_cancel_token.cancelled() => {
-                    info!("Ingest thread got cancel request");
-                    break;
+                        });
+                    }
+                    _ = ingest_cancel_token.cancelled() => {
+                        info!("Ingest thread got cancel request");
+                        break;
+                    }
                 }
             }
-        }
 
-        info!("Ingest thread exited");
-    });
+            info!("Ingest thread exited");
+        }))
+    };
 
     let frames_batch: Data<Mutex<Vec<CommonFrame>>> = Data::new(Mutex::new(Vec::new()));
     let mut batcher: Option<JoinHandle<()>> = None;
 
     let mut es_client: Option<Elasticsearch> = None;
     if let Some(ref mut es_url) = elastic_url {
-        match create_es_client(es_url, validate_es_cert) {
+        match create_es_client(es_url, validate_es_cert, http_timeout, user_agent.as_str()) {
             Ok(client) => es_client = Some(client),
             Err(e) => warn!(
                 "Failed to create ES client to {}: {}",
@@ -259,11 +748,13 @@ pub async fn start(args: &ArgMatches) {
         }
     };
 
+    let mut stdin_closed = false;
+
     loop {
         select! {
             Some(frame) = rx.recv() => {
                 {
-                    let state_db = state_db.write().await;
+                    let mut state_db = state_db.write().await;
                     if let Err(e) = state_db.update(&frame).await {
                         warn!("Failed to updated state DB with frame: {}", e.to_string());
                     }
@@ -279,6 +770,8 @@ pub async fn start(args: &ArgMatches) {
                             create_es_batch_task(
                                 client,
                                 &elastic_index,
+                                &es_exclude_fields,
+                                coord_precision,
                                 frames_batch,
                                 Duration::from_millis(DEFAULT_BATCH_WAIT_MS)
                             )
@@ -288,6 +781,20 @@ pub async fn start(args: &ArgMatches) {
                     debug!("Pushing frame to batch...");
                     batch.push(frame);
                 }
+
+                if stdin_closed && rx.is_empty() {
+                    info!("All STDIN frames processed, exiting");
+                    break;
+                }
+            }
+            // NOTE: TCP feeders keep the server running after STDIN closes, only a STDIN-only server is done
+            _ = stdin_closed_token.cancelled(), if read_stdin && disable_tcp && !stdin_closed => {
+                stdin_closed = true;
+
+                if rx.is_empty() {
+                    info!("All STDIN frames processed, exiting");
+                    break;
+                }
             }
             _ = interrupt_signal.recv() => {
                 info!("Interrupt signal detected, attempting to cleanly exit");
@@ -297,22 +804,35 @@ pub async fn start(args: &ArgMatches) {
         }
     }
 
-    if let Some(batcher) = batcher {
+    let shutdown_deadline = time::Instant::now() + shutdown_timeout;
+
+    if let Some(mut batcher) = batcher {
         debug!("Batcher is active, waiting for completion before exiting to prevent data loss");
-        if let Err(e) = batcher.await {
-            warn!(
+        match time::timeout_at(shutdown_deadline, &mut batcher).await {
+            Ok(Err(e)) => warn!(
                 "Error occurred while waiting for batcher to finish: {}",
                 e.to_string()
-            );
+            ),
+            Err(_) => {
+                warn!("Batcher did not finish within shutdown timeout, aborting");
+                batcher.abort();
+            }
+            _ => {}
         }
     }
 
     debug!("Signaling HTTP and ingest thread to cancel");
     cancel_token.cancel();
 
-    #[allow(unused_must_use)]
-    {
-        tokio::join!(http_thread, ingest_thread);
+    if time::timeout_at(shutdown_deadline, &mut http_thread).await.is_err() {
+        warn!("HTTP thread did not exit within shutdown timeout, aborting");
+        http_thread.abort();
+    }
+    if let Some(mut ingest_thread) = ingest_thread {
+        if time::timeout_at(shutdown_deadline, &mut ingest_thread).await.is_err() {
+            warn!("Ingest thread did not exit within shutdown timeout, aborting");
+            ingest_thread.abort();
+        }
     }
 
     info!("Server exited");
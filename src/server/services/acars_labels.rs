@@ -0,0 +1,22 @@
+use actix_web::web::Data;
+use actix_web::{HttpRequest, HttpResponse};
+use tokio::sync::RwLock;
+
+use crate::common::acars_labels::ACARS_LABELS;
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::ServerServiceResponseBuilder;
+
+pub const ROUTE: &'static str = "/api/acars/labels/";
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    response.body(ACARS_LABELS)
+}
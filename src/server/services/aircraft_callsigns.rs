@@ -0,0 +1,99 @@
+use actix_web::web::Data;
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
+
+pub const ROUTE: &'static str = "/api/aircraft/{icao}/callsigns/";
+
+#[derive(FromRow, Serialize)]
+struct CallsignHistory {
+    callsign: String,
+    first_heard: DateTime<Utc>,
+    last_heard: DateTime<Utc>,
+    count: u32,
+}
+
+#[derive(Serialize)]
+struct AircraftCallsigns {
+    icao: String,
+    callsigns: Vec<CallsignHistory>,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let raw_icao = req.match_info().get("icao").unwrap_or("");
+    let icao = match u32::from_str_radix(raw_icao, 16) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::BadRequest(), format!("Invalid ICAO address {}: {}", raw_icao, e.to_string()))
+        }
+    };
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        let has_events = match sqlx::query_scalar::<_, bool>(
+            "
+            SELECT EXISTS(SELECT 1 FROM aircraft_events WHERE aircraft_icao = ?)
+            ",
+        )
+        .bind(icao)
+        .fetch_one(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+        if !has_events {
+            return response.error(HttpResponse::NotFound(), format!("No events found for ICAO address {:06x}", icao));
+        }
+
+        let callsigns = match sqlx::query_as::<_, CallsignHistory>(
+            "
+            SELECT
+                callsign,
+                min(ts) AS first_heard,
+                max(ts) AS last_heard,
+                count(*) AS count
+            FROM aircraft_events
+            WHERE aircraft_icao = ? AND callsign IS NOT NULL AND callsign != ''
+            GROUP BY callsign
+            ORDER BY last_heard DESC
+            ",
+        )
+        .bind(icao)
+        .fetch_all(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        response.body(AircraftCallsigns {
+            icao: format!("{:06x}", icao),
+            callsigns,
+        })
+    } else {
+        response.db_disabled()
+    }
+}
@@ -0,0 +1,86 @@
+use actix_web::web::Data;
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::ServerServiceResponseBuilder;
+
+pub const ROUTE: &'static str = "/api/aircraft/{icao}/events/";
+
+#[derive(Serialize)]
+struct DeletedEvents {
+    icao: String,
+    removed: u64,
+}
+
+pub async fn delete(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let raw_icao = req.match_info().get("icao").unwrap_or("");
+    let icao = match u32::from_str_radix(raw_icao, 16) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::BadRequest(), format!("Invalid ICAO address {}: {}", raw_icao, e.to_string()))
+        }
+    };
+
+    if let Some(db) = state_db.db_pool() {
+        let mut tx = match db.begin().await {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Failed to begin transaction: {}", e.to_string()))
+            }
+        };
+
+        let removed = match sqlx::query(
+            "
+            DELETE FROM aircraft_events WHERE aircraft_icao = ?
+            ",
+        )
+        .bind(icao)
+        .execute(&mut tx)
+        .await
+        {
+            Ok(x) => x.rows_affected(),
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        // NOTE: recounted from whatever events are left in the same transaction, rather than subtracting, so the
+        //       count can't drift from the table
+        if let Err(e) = sqlx::query(
+            "
+            UPDATE aircrafts SET msg_count = (SELECT count(*) FROM aircraft_events WHERE aircraft_icao = ?) WHERE icao = ?
+            ",
+        )
+        .bind(icao)
+        .bind(icao)
+        .execute(&mut tx)
+        .await
+        {
+            return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()));
+        }
+
+        if let Err(e) = tx.commit().await {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to commit transaction: {}", e.to_string()));
+        }
+
+        state_db.summary_cache().write().await.invalidate();
+
+        response.body(DeletedEvents {
+            icao: format!("{:06x}", icao),
+            removed,
+        })
+    } else {
+        response.db_disabled()
+    }
+}
@@ -0,0 +1,97 @@
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::ServerServiceResponseBuilder;
+
+pub const ROUTE: &'static str = "/api/aircraft/prune/";
+
+#[derive(Debug, Deserialize)]
+struct PruneParams {
+    before: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct PrunedAircraft {
+    aircraft_removed: u64,
+    events_removed: u64,
+}
+
+pub async fn delete(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<PruneParams>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    if let Some(db) = state_db.db_pool() {
+        let mut tx = match db.begin().await {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Failed to begin transaction: {}", e.to_string()))
+            }
+        };
+
+        // NOTE: events go first so their count is known, the aircraft are still selected by the same condition afterwards
+        let events_removed = match sqlx::query(
+            "
+            DELETE FROM aircraft_events
+            WHERE aircraft_icao IN (
+                SELECT a.icao FROM aircrafts a
+                WHERE NOT EXISTS (SELECT 1 FROM aircraft_events ae WHERE ae.aircraft_icao = a.icao AND ae.ts >= ?)
+            )
+            ",
+        )
+        .bind(params.before)
+        .execute(&mut tx)
+        .await
+        {
+            Ok(x) => x.rows_affected(),
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        let aircraft_removed = match sqlx::query(
+            "
+            DELETE FROM aircrafts
+            WHERE NOT EXISTS (SELECT 1 FROM aircraft_events ae WHERE ae.aircraft_icao = aircrafts.icao AND ae.ts >= ?)
+            ",
+        )
+        .bind(params.before)
+        .execute(&mut tx)
+        .await
+        {
+            Ok(x) => x.rows_affected(),
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        if let Err(e) = tx.commit().await {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to commit transaction: {}", e.to_string()));
+        }
+
+        state_db.summary_cache().write().await.invalidate();
+
+        response.body(PrunedAircraft {
+            aircraft_removed,
+            events_removed,
+        })
+    } else {
+        response.db_disabled()
+    }
+}
@@ -7,7 +7,7 @@ use tokio::sync::RwLock;
 use crate::common::middleware::Authorized;
 use crate::server::db::StateDB;
 
-use super::ServerServiceResponse;
+use super::ServerServiceResponseBuilder;
 
 pub const ROUTE: &'static str = "/api/cleanup/";
 
@@ -17,21 +17,20 @@ struct CleanupParams {
 }
 
 pub async fn delete(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
     let params = match web::Query::<CleanupParams>::from_query(req.query_string()) {
         Ok(x) => x,
         Err(e) => {
-            return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                ok: false,
-                message: Some(format!("Failed to get query params: {}", e.to_string())),
-            })
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
         }
     };
 
-    let state_db = req
-        .app_data::<Data<RwLock<StateDB>>>()
-        .unwrap()
-        .read()
-        .await;
     if let Some(db) = state_db.db_pool() {
         let deletions = [
             (
@@ -46,6 +45,12 @@ pub async fn delete(req: HttpRequest, _: Authorized) -> HttpResponse {
                 DELETE FROM ground_station_change_events WHERE ts < ? 
                 ",
             ),
+            (
+                "hourly stats buckets",
+                "
+                DELETE FROM hourly_stats WHERE hour < ? 
+                ",
+            ),
         ];
 
         let mut ok = true;
@@ -65,18 +70,18 @@ pub async fn delete(req: HttpRequest, _: Authorized) -> HttpResponse {
             }
         }
 
-        HttpResponse::Ok().json(ServerServiceResponse {
+        state_db.summary_cache().write().await.invalidate();
+
+        response.message(
+            HttpResponse::Ok(),
             ok,
-            message: Some(format!(
+            format!(
                 "Delete operation {}: {}",
                 if ok { "succeeded" } else { "failed" },
                 msgs.join(", ")
-            )),
-        })
+            ),
+        )
     } else {
-        HttpResponse::NotImplemented().json(ServerServiceResponse {
-            ok: false,
-            message: Some(format!("State DB is disabled")),
-        })
+        response.db_disabled()
     }
 }
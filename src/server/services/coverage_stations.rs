@@ -0,0 +1,120 @@
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
+
+pub const ROUTE: &'static str = "/api/coverage/stations/";
+
+#[derive(Debug, Deserialize)]
+struct CoverageStationsParam {
+    since: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct CoverageRow {
+    gs_id: u32,
+
+    name: Option<String>,
+    short: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+
+    aircraft_count: u32,
+    event_count: u32,
+    last_heard: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct StationCoverage {
+    id: u32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short: Option<String>,
+
+    coords: Option<(f64, f64)>,
+
+    aircraft_count: u32,
+    event_count: u32,
+    last_heard: DateTime<Utc>,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<CoverageStationsParam>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        let results = match sqlx::query_as::<_, CoverageRow>(
+            "
+            SELECT
+                ae.gs_id,
+                gs.name,
+                gs.short,
+                gs.latitude,
+                gs.longitude,
+                COUNT(DISTINCT ae.aircraft_icao) AS aircraft_count,
+                COUNT(*) AS event_count,
+                max(ae.ts) AS last_heard
+            FROM aircraft_events ae
+            JOIN ground_stations gs ON ae.gs_id = gs.id
+            WHERE ifnull(ae.ts >= ?, 1)
+            GROUP BY ae.gs_id
+            ORDER BY event_count DESC, ae.gs_id ASC
+            ",
+        )
+        .bind(params.since)
+        .fetch_all(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        response.body(
+            results
+                .into_iter()
+                .map(|result| StationCoverage {
+                    id: result.gs_id,
+                    name: result.name,
+                    short: result.short,
+                    coords: match (result.latitude, result.longitude) {
+                        (Some(lat), Some(lon)) => Some((lon, lat)),
+                        _ => None,
+                    },
+                    aircraft_count: result.aircraft_count,
+                    event_count: result.event_count,
+                    last_heard: result.last_heard,
+                })
+                .collect::<Vec<StationCoverage>>(),
+        )
+    } else {
+        response.db_disabled()
+    }
+}
@@ -0,0 +1,57 @@
+use actix_web::web::Data;
+use actix_web::{HttpRequest, HttpResponse};
+use log::*;
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::common::es_utils::{create_es_index, es_index_exists, get_xng_index_mapping, EsIndexTarget};
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::ServerServiceResponseBuilder;
+
+pub const ROUTE: &'static str = "/api/es/reindex/";
+
+const ACTION_CREATED: &'static str = "created";
+const ACTION_ALREADY_EXISTS: &'static str = "already_exists";
+
+#[derive(Serialize)]
+struct ReindexResult {
+    index: String,
+    action: &'static str,
+}
+
+// NOTE: only creates a missing index, an existing index is never deleted or remapped from the API
+pub async fn post(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let Some(target) = req.app_data::<Data<EsIndexTarget>>() else {
+        return response.error(HttpResponse::NotImplemented(), format!("Elasticsearch is not configured"));
+    };
+
+    let exists = match es_index_exists(&target.client, target.index.as_str()).await {
+        Ok(x) => x,
+        Err(e) => return response.error(HttpResponse::BadGateway(), e.to_string()),
+    };
+    if exists {
+        return response.body(ReindexResult {
+            index: target.index.clone(),
+            action: ACTION_ALREADY_EXISTS,
+        });
+    }
+
+    if let Err(e) = create_es_index(&target.client, target.index.as_str(), get_xng_index_mapping()).await {
+        return response.error(HttpResponse::BadGateway(), e.to_string());
+    }
+    info!("Created Elasticsearch index {} on API request", target.index);
+
+    response.body(ReindexResult {
+        index: target.index.clone(),
+        action: ACTION_CREATED,
+    })
+}
@@ -8,7 +8,7 @@ use tokio::sync::RwLock;
 use crate::common::middleware::Authorized;
 use crate::server::db::StateDB;
 
-use super::ServerServiceResponse;
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
 
 pub const ROUTE: &'static str = "/api/extremities/";
 
@@ -73,12 +73,6 @@ struct ExtremitiesData {
     westmost: Option<AircraftEvent>,
 }
 
-#[derive(Serialize)]
-struct ExtremitiesResponse {
-    ok: bool,
-    body: ExtremitiesData,
-}
-
 async fn get_flight_event(
     db: &SqlitePool,
     dir: ExtremityDirection,
@@ -142,39 +136,36 @@ struct ExtremitiesParam {
 }
 
 pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
     let params = match web::Query::<ExtremitiesParam>::from_query(req.query_string()) {
         Ok(x) => x,
         Err(e) => {
-            return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                ok: false,
-                message: Some(format!("Failed to get query params: {}", e.to_string())),
-            })
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
         }
     };
 
-    let state_db = req
-        .app_data::<Data<RwLock<StateDB>>>()
-        .unwrap()
-        .read()
-        .await;
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
 
-    if let Some(db) = state_db.db_pool() {
         let lat = params.lat.unwrap_or(0.0);
         let lon = params.lon.unwrap_or(0.0);
 
-        HttpResponse::Ok().json(ExtremitiesResponse {
-            ok: true,
-            body: ExtremitiesData {
-                northmost: get_flight_event(db, ExtremityDirection::North, lat, lon).await,
-                eastmost: get_flight_event(db, ExtremityDirection::East, lat, lon).await,
-                southmost: get_flight_event(db, ExtremityDirection::South, lat, lon).await,
-                westmost: get_flight_event(db, ExtremityDirection::West, lat, lon).await,
-            },
+        response.body(ExtremitiesData {
+            northmost: get_flight_event(db, ExtremityDirection::North, lat, lon).await,
+            eastmost: get_flight_event(db, ExtremityDirection::East, lat, lon).await,
+            southmost: get_flight_event(db, ExtremityDirection::South, lat, lon).await,
+            westmost: get_flight_event(db, ExtremityDirection::West, lat, lon).await,
         })
     } else {
-        HttpResponse::NotImplemented().json(ServerServiceResponse {
-            ok: false,
-            message: Some(format!("State DB is disabled")),
-        })
+        response.db_disabled()
     }
 }
@@ -8,13 +8,10 @@ use sqlx::FromRow;
 use tokio::sync::RwLock;
 
 use crate::common::middleware::Authorized;
+use crate::server::db::summary_cache::{load_latest_events, FIELD_AIRCRAFT_ICAO, FIELD_CALLSIGN, FIELD_TAIL};
 use crate::server::db::StateDB;
 
-use super::ServerServiceResponse;
-
-pub const FIELD_AIRCRAFT_ICAO: &'static str = "aircraft_icao";
-pub const FIELD_CALLSIGN: &'static str = "callsign";
-pub const FIELD_TAIL: &'static str = "tail";
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
 
 pub const ROUTE: &'static str = "/api/flight/";
 pub const VALID_FIELDS: [&'static str; 3] = [FIELD_AIRCRAFT_ICAO, FIELD_CALLSIGN, FIELD_TAIL];
@@ -26,28 +23,6 @@ struct FlightParams {
     since: Option<DateTime<Utc>>,
 }
 
-#[derive(FromRow)]
-struct FlightSummaryRow {
-    ts: DateTime<Utc>,
-
-    icao_addr: Option<String>,
-    callsign: Option<String>,
-    tail: Option<String>,
-
-    gs_id: u32,
-
-    signal: f64,
-    freq_mhz: f64,
-
-    latitude: f64,
-    longitude: f64,
-
-    altitude: Option<u32>,
-
-    prev_latitude: Option<f64>,
-    prev_longitude: Option<f64>,
-}
-
 #[derive(Debug, Serialize)]
 struct FlightSummary {
     icao: Option<String>,
@@ -66,12 +41,6 @@ struct FlightSummary {
     prev_coords: Option<(f64, f64)>,
 }
 
-#[derive(Serialize)]
-struct FlightSummaryResponse {
-    ok: bool,
-    body: Vec<FlightSummary>,
-}
-
 #[derive(FromRow)]
 struct FlightDetailRow {
     ts: DateTime<Utc>,
@@ -89,6 +58,9 @@ struct FlightDetailRow {
     longitude: f64,
 
     altitude: Option<u32>,
+
+    flight_id: Option<String>,
+    flight_leg_num: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -106,81 +78,76 @@ struct FlightDetail {
 
     coords: (f64, f64),
     altitude: Option<u32>,
-}
 
-#[derive(Serialize)]
-struct FlightDetailResponse {
-    ok: bool,
-    body: Vec<FlightDetail>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flight_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flight_leg_num: Option<u32>,
 }
 
 pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
     let default_field = String::from("callsign");
 
     let params = match web::Query::<FlightParams>::from_query(req.query_string()) {
         Ok(x) => x,
         Err(e) => {
-            return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                ok: false,
-                message: Some(format!("Failed to get query params: {}", e.to_string())),
-            })
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
         }
     };
 
-    let state_db = req
-        .app_data::<Data<RwLock<StateDB>>>()
-        .unwrap()
-        .read()
-        .await;
-    if let Some(db) = state_db.db_pool() {
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
         let field = params.field.as_ref().unwrap_or(&default_field);
         if !VALID_FIELDS
             .iter()
             .any(|&x| field.to_lowercase().as_str() == x)
         {
-            return HttpResponse::BadRequest().json(ServerServiceResponse {
-                ok: false,
-                message: Some(format!(
-                    "{} is not a valid field, expected: {:?}",
-                    field, VALID_FIELDS
-                )),
-            });
+            return response.error(
+                HttpResponse::BadRequest(),
+                format!("{} is not a valid field, expected: {:?}", field, VALID_FIELDS),
+            );
         }
 
         if params.value.is_none() {
-            let query = format!("
-                WITH grouped_events AS (
-                    SELECT ROW_NUMBER() OVER (PARTITION BY ae.{} ORDER BY ae.ts DESC) AS row, ae.* FROM aircraft_events ae
-                )
-                SELECT 
-                    ge.*, 
-                    ge2.latitude AS prev_latitude, 
-                    ge2.longitude AS prev_longitude,
-                    iif(ge.aircraft_icao IS NULL, NULL, printf('%06x', ge.aircraft_icao)) AS icao_addr
-                FROM grouped_events ge
-                LEFT JOIN grouped_events ge2 ON ge.{} = ge2.{} AND ge2.row = 2
-                WHERE ge.row = 1 
-                    AND ifnull(ge.ts >= ?, 1)
-                ORDER BY ge.ts DESC
-            ", field, field, field);
-            let results = match sqlx::query_as::<_, FlightSummaryRow>(query.as_str())
-                .bind(params.since)
-                .fetch_all(db)
-                .await
-            {
-                Ok(x) => x,
-                Err(e) => {
-                    return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                        ok: false,
-                        message: Some(format!("Query failed: {}", e.to_string())),
-                    })
+            let field = field.to_lowercase();
+            let summary_cache = state_db.summary_cache();
+
+            let cached = summary_cache.read().await.get(field.as_str());
+            let events = if let Some(events) = cached {
+                events
+            } else {
+                // NOTE: a cold miss is served straight from the DB, bounded by since if given, and the refresh task
+                //       loads the complete summary into the cache on its next tick
+                summary_cache.write().await.want(field.as_str());
+
+                let events = match load_latest_events(db, field.as_str(), params.since).await {
+                    Ok(x) => x,
+                    Err(e) => {
+                        return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+                    }
+                };
+                if params.since.is_none() {
+                    summary_cache.write().await.replace(field.as_str(), &events);
                 }
+                events
             };
 
-            HttpResponse::Ok().json(FlightSummaryResponse {
-                ok: true,
-                body: results
+            response.body(
+                events
                     .iter()
+                    .filter(|x| params.since.map_or(true, |since| x.ts >= since))
                     .map(|x| FlightSummary {
                         icao: x.icao_addr.clone(),
                         callsign: x.callsign.clone(),
@@ -194,13 +161,10 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
 
                         coords: (x.latitude, x.longitude),
                         altitude: x.altitude,
-                        prev_coords: match (x.prev_latitude, x.prev_longitude) {
-                            (Some(x), Some(y)) => Some((x, y)),
-                            (None, _) | (_, None) => None,
-                        },
+                        prev_coords: x.prev_coords,
                     })
-                    .collect(),
-            })
+                    .collect::<Vec<FlightSummary>>(),
+            )
         } else {
             let query = format!(
                 "
@@ -219,15 +183,15 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
                 icao_value = match params.value.as_ref().unwrap().parse::<u32>() {
                     Ok(x) => x,
                     Err(e) => {
-                        return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                            ok: false,
-                            message: Some(format!(
+                        return response.error(
+                            HttpResponse::InternalServerError(),
+                            format!(
                                 "Provided value for field {} is not a valid number, {}: {}",
                                 FIELD_AIRCRAFT_ICAO,
                                 params.value.as_ref().unwrap(),
                                 e.to_string()
-                            )),
-                        })
+                            ),
+                        )
                     }
                 };
             }
@@ -241,16 +205,12 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
             let results = match query_builder.bind(params.since).fetch_all(db).await {
                 Ok(x) => x,
                 Err(e) => {
-                    return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                        ok: false,
-                        message: Some(format!("Query failed: {}", e.to_string())),
-                    })
+                    return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
                 }
             };
 
-            HttpResponse::Ok().json(FlightDetailResponse {
-                ok: true,
-                body: results
+            response.body(
+                results
                     .iter()
                     .map(|x| FlightDetail {
                         ts: x.ts,
@@ -262,14 +222,13 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
                         gs_id: x.gs_id,
                         coords: (x.latitude, x.longitude),
                         altitude: x.altitude,
+                        flight_id: x.flight_id.clone(),
+                        flight_leg_num: x.flight_leg_num,
                     })
-                    .collect(),
-            })
+                    .collect::<Vec<FlightDetail>>(),
+            )
         }
     } else {
-        HttpResponse::NotImplemented().json(ServerServiceResponse {
-            ok: false,
-            message: Some(format!("State DB is disabled")),
-        })
+        response.db_disabled()
     }
 }
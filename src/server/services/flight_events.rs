@@ -9,7 +9,7 @@ use crate::common::middleware::Authorized;
 use crate::server::db::StateDB;
 use crate::utils::normalize_tail;
 
-use super::ServerServiceResponse;
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
 
 pub const ROUTE: &'static str = "/api/flight/events/";
 
@@ -30,6 +30,9 @@ struct GroundStation {
     #[serde(skip_serializing_if = "Option::is_none")]
     name: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short: Option<String>,
+
     coords: Option<(f64, f64)>,
 }
 
@@ -56,12 +59,9 @@ struct FlightEvent {
     altitude: Option<u32>,
 
     gs: GroundStation,
-}
 
-#[derive(Serialize)]
-struct FlightEventsResponse {
-    ok: bool,
-    body: Vec<FlightEvent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    heard_on: Vec<u32>,
 }
 
 #[derive(FromRow)]
@@ -73,6 +73,7 @@ struct EventRow {
     tail: Option<String>,
     gs_id: u32,
     gs_name: Option<String>,
+    gs_short: Option<String>,
     gs_lat: Option<f64>,
     gs_lon: Option<f64>,
     signal: f64,
@@ -80,25 +81,30 @@ struct EventRow {
     latitude: f64,
     longitude: f64,
     altitude: Option<u32>,
+    heard_on: Option<String>,
 }
 
 pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
     let params = match web::Query::<FlightEventsParam>::from_query(req.query_string()) {
         Ok(x) => x,
         Err(e) => {
-            return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                ok: false,
-                message: Some(format!("Failed to get query params: {}", e.to_string())),
-            })
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
         }
     };
 
-    let state_db = req
-        .app_data::<Data<RwLock<StateDB>>>()
-        .unwrap()
-        .read()
-        .await;
-    if let Some(db) = state_db.db_pool() {
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
         let results = match sqlx::query_as::<_, EventRow>(
             "
             SELECT 
@@ -109,13 +115,15 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
                 ae.tail,
                 gs.id AS gs_id,
                 gs.name AS gs_name,
+                gs.short AS gs_short,
                 gs.latitude AS gs_lat,
                 gs.longitude AS gs_lon, 
                 ae.signal, 
                 ae.freq_mhz, 
                 ae.latitude, 
                 ae.longitude, 
-                ae.altitude 
+                ae.altitude,
+                (SELECT group_concat(hoe.gs_id) FROM heard_on_events hoe WHERE hoe.aircraft_events_id = ae.id) AS heard_on
             FROM aircraft_events ae
             LEFT JOIN aircrafts a ON a.icao = ae.aircraft_icao OR a.tail = COALESCE(ae.tail, \"\")
             JOIN ground_stations gs ON gs.id = ae.gs_id
@@ -137,41 +145,35 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
         .fetch_all(db)
         .await {
             Ok(x) => x,
-            Err(e) => return HttpResponse::InternalServerError().json(
-                ServerServiceResponse {
-                    ok: false,
-                    message: Some(format!("Query failed: {}", e.to_string())),
-                }
-            )
+            Err(e) => return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
         };
 
-        HttpResponse::Ok().json(FlightEventsResponse {
-            ok: true,
-            body: results.into_iter().map(|result| FlightEvent {
-                id: result.id,
-                ts: result.ts,
-                icao: result.icao_addr,
-                callsign: result.callsign,
-                tail: result.tail,
-                signal: result.signal,
-                freq_mhz: result.freq_mhz,
-                coords: (result.longitude, result.latitude),
-                altitude: result.altitude,
-                gs: GroundStation {
-                    id: result.gs_id,
-                    name: result.gs_name,
-                    coords: if result.gs_lat.is_some() && result.gs_lon.is_some() {
-                        Some((result.gs_lon.unwrap(), result.gs_lat.unwrap()))
-                    } else {
-                        None
-                    },
-                }
-            }).collect(),
-        })
+        response.body(results.into_iter().map(|result| FlightEvent {
+            id: result.id,
+            ts: result.ts,
+            icao: result.icao_addr,
+            callsign: result.callsign,
+            tail: result.tail,
+            signal: result.signal,
+            freq_mhz: result.freq_mhz,
+            coords: (result.longitude, result.latitude),
+            altitude: result.altitude,
+            gs: GroundStation {
+                id: result.gs_id,
+                name: result.gs_name,
+                short: result.gs_short,
+                coords: if result.gs_lat.is_some() && result.gs_lon.is_some() {
+                    Some((result.gs_lon.unwrap(), result.gs_lat.unwrap()))
+                } else {
+                    None
+                },
+            },
+            heard_on: result
+                .heard_on
+                .map(|x| x.split(',').filter_map(|y| y.parse::<u32>().ok()).collect())
+                .unwrap_or_default(),
+        }).collect::<Vec<FlightEvent>>())
     } else {
-        HttpResponse::NotImplemented().json(ServerServiceResponse {
-            ok: false,
-            message: Some(format!("State DB is disabled")),
-        })
+        response.db_disabled()
     }
 }
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
+
+pub const ROUTE: &'static str = "/api/frequency/{khz}/activity/";
+
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+const DEFAULT_BUCKET_MINUTES: i64 = 60;
+const MAX_BUCKET_MINUTES: i64 = 1440;
+const MAX_BUCKETS: i64 = 10000;
+
+#[derive(Debug, Deserialize)]
+struct FrequencyActivityParam {
+    since: Option<DateTime<Utc>>,
+    bucket_minutes: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct Bucket {
+    start: DateTime<Utc>,
+    count: u32,
+}
+
+#[derive(Serialize)]
+struct FrequencyActivity {
+    freq_mhz: f64,
+    bucket_minutes: i64,
+    buckets: Vec<Bucket>,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<FrequencyActivityParam>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    let khz = match req.match_info().get("khz").unwrap_or("").parse::<u32>() {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::BadRequest(), format!("Invalid kHz frequency: {}", e.to_string()))
+        }
+    };
+
+    let bucket_minutes = params.bucket_minutes.unwrap_or(DEFAULT_BUCKET_MINUTES);
+    if bucket_minutes < 1 || bucket_minutes > MAX_BUCKET_MINUTES {
+        return response.error(
+            HttpResponse::BadRequest(),
+            format!("Invalid bucket_minutes {}, expected 1 to {}", bucket_minutes, MAX_BUCKET_MINUTES),
+        );
+    }
+    let bucket_secs = bucket_minutes * 60;
+
+    // NOTE: buckets are aligned to multiples of the bucket size since the unix epoch
+    let now = Utc::now().timestamp();
+    let since = params.since.unwrap_or(Utc::now() - Duration::hours(DEFAULT_WINDOW_HOURS)).timestamp();
+    let first_bucket = since - since.rem_euclid(bucket_secs);
+    let last_bucket = now - now.rem_euclid(bucket_secs);
+    if (last_bucket - first_bucket) / bucket_secs >= MAX_BUCKETS {
+        return response.error(
+            HttpResponse::BadRequest(),
+            format!("Window spans more than {} buckets, use a later since or larger bucket_minutes", MAX_BUCKETS),
+        );
+    }
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        let results = match sqlx::query_as::<_, (i64, u32)>(
+            "
+            SELECT
+                (CAST(strftime('%s', ae.ts) AS INTEGER) / ?) * ? AS bucket,
+                COUNT(*) AS count
+            FROM aircraft_events ae
+            WHERE CAST(round(ae.freq_mhz * 1000) AS INTEGER) = ?
+                AND ae.ts >= ?
+            GROUP BY bucket
+            ",
+        )
+        .bind(bucket_secs)
+        .bind(bucket_secs)
+        .bind(khz)
+        .bind(Utc.timestamp_opt(first_bucket, 0).single())
+        .fetch_all(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+        let counts: HashMap<i64, u32> = results.into_iter().collect();
+
+        let mut buckets: Vec<Bucket> = Vec::new();
+        let mut bucket = first_bucket;
+        while bucket <= last_bucket {
+            if let Some(start) = Utc.timestamp_opt(bucket, 0).single() {
+                buckets.push(Bucket {
+                    start,
+                    count: counts.get(&bucket).copied().unwrap_or(0),
+                });
+            }
+            bucket += bucket_secs;
+        }
+
+        response.body(FrequencyActivity {
+            freq_mhz: khz as f64 / 1000.0,
+            bucket_minutes,
+            buckets,
+        })
+    } else {
+        response.db_disabled()
+    }
+}
@@ -0,0 +1,94 @@
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
+
+pub const ROUTE: &'static str = "/api/frequency/history/";
+
+#[derive(Debug, Deserialize)]
+struct FrequencyHistoryParam {
+    since: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct HistoryRow {
+    freq_mhz: f64,
+    first_heard: DateTime<Utc>,
+    last_heard: DateTime<Utc>,
+    event_count: u32,
+}
+
+#[derive(Serialize)]
+struct FrequencyHistory {
+    freq_mhz: f64,
+    first_heard: DateTime<Utc>,
+    last_heard: DateTime<Utc>,
+    event_count: u32,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<FrequencyHistoryParam>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        let results = match sqlx::query_as::<_, HistoryRow>(
+            "
+            SELECT
+                ae.freq_mhz,
+                min(ae.ts) AS first_heard,
+                max(ae.ts) AS last_heard,
+                COUNT(*) AS event_count
+            FROM aircraft_events ae
+            WHERE ifnull(ae.ts >= ?, 1)
+            GROUP BY ae.freq_mhz
+            ORDER BY ae.freq_mhz ASC
+            ",
+        )
+        .bind(params.since)
+        .fetch_all(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        response.body(
+            results
+                .into_iter()
+                .map(|result| FrequencyHistory {
+                    freq_mhz: result.freq_mhz,
+                    first_heard: result.first_heard,
+                    last_heard: result.last_heard,
+                    event_count: result.event_count,
+                })
+                .collect::<Vec<FrequencyHistory>>(),
+        )
+    } else {
+        response.db_disabled()
+    }
+}
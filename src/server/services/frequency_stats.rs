@@ -1,17 +1,28 @@
-use actix_web::web::Data;
+use actix_web::web::{self, Data};
 use actix_web::{HttpRequest, HttpResponse};
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use tokio::sync::RwLock;
 
 use crate::common::middleware::Authorized;
 use crate::server::db::StateDB;
 
-use super::ServerServiceResponse;
+use super::ServerServiceResponseBuilder;
 
 pub const ROUTE: &'static str = "/api/frequency/stats/";
 
+const ETAG_QUERY: &'static str = "
+    SELECT printf('%s-%d-%d', max(f.last_heard), total(f.to_gs + f.from_gs), (SELECT max(id) FROM aircraft_events)) FROM frequency_stats f
+";
+
+#[derive(Debug, Deserialize)]
+struct FrequencyStatsParam {
+    since: Option<DateTime<Utc>>,
+    sort: Option<String>,
+    order: Option<String>,
+}
+
 #[derive(FromRow)]
 struct EventRow {
     khz: u32,
@@ -22,8 +33,11 @@ struct EventRow {
     last_heard: DateTime<Utc>,
 
     name: Option<String>,
+    short: Option<String>,
     latitude: Option<f64>,
     longitude: Option<f64>,
+
+    window_count: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -33,6 +47,9 @@ struct GroundStation {
     #[serde(skip_serializing_if = "Option::is_none")]
     name: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short: Option<String>,
+
     coords: Option<(f64, f64)>,
 }
 
@@ -44,12 +61,9 @@ struct FrequencyStats {
     to_gs: u32,
     from_gs: u32,
     last_heard: DateTime<Utc>,
-}
 
-#[derive(Serialize)]
-struct FreqStatsResponse {
-    ok: bool,
-    body: Vec<FrequencyStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window_count: Option<u32>,
 }
 
 pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
@@ -58,36 +72,91 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
         .unwrap()
         .read()
         .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<FrequencyStatsParam>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    // NOTE: sort columns and direction are whitelisted since they cannot be bound as parameters
+    let sort_column = match params.sort.as_deref().unwrap_or("khz") {
+        "khz" => "f.khz",
+        "count" => "coalesce(window_count, f.to_gs + f.from_gs)",
+        "last_heard" => "f.last_heard",
+        x => {
+            return response.error(HttpResponse::BadRequest(), format!("Invalid sort '{}', expected one of: khz, count, last_heard", x))
+        }
+    };
+    let sort_order = match params.order.as_deref().unwrap_or("asc") {
+        "asc" => "ASC",
+        "desc" => "DESC",
+        x => {
+            return response.error(HttpResponse::BadRequest(), format!("Invalid order '{}', expected one of: asc, desc", x))
+        }
+    };
 
-    if let Some(db) = state_db.db_pool() {
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        // NOTE: the window counts are aggregated once over a ts range, which the aircraft_events ts index covers,
+        //       instead of a correlated count per frequency_stats row
         let results = match sqlx::query_as::<_, EventRow>(
-            "
-            SELECT f.khz, f.gs_id, f.to_gs, f.from_gs, f.last_heard, gs.name, gs.latitude, gs.longitude FROM frequency_stats f
-            JOIN ground_stations gs ON f.gs_id = gs.id
-            ORDER BY f.khz ASC
-            ",
+            format!(
+                "
+                WITH window_counts AS (
+                    SELECT ae.gs_id, CAST(round(ae.freq_mhz * 1000) AS INTEGER) AS khz, count(*) AS window_count
+                    FROM aircraft_events ae
+                    WHERE ae.ts >= ?
+                    GROUP BY 1, 2
+                )
+                SELECT 
+                    f.khz, 
+                    f.gs_id, 
+                    f.to_gs, 
+                    f.from_gs, 
+                    f.last_heard, 
+                    gs.name, 
+                    gs.short, 
+                    gs.latitude, 
+                    gs.longitude,
+                    iif(? IS NULL, NULL, ifnull(wc.window_count, 0)) AS window_count
+                FROM frequency_stats f
+                JOIN ground_stations gs ON f.gs_id = gs.id
+                LEFT JOIN window_counts wc ON wc.gs_id = f.gs_id AND wc.khz = f.khz
+                WHERE ifnull(f.last_heard >= ?, 1)
+                ORDER BY {} {}, f.khz ASC
+                ",
+                sort_column, sort_order
+            )
+            .as_str(),
         )
+        .bind(params.since)
+        .bind(params.since)
+        .bind(params.since)
         .fetch_all(db)
         .await
         {
             Ok(x) => x,
             Err(e) => {
-                return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                    ok: false,
-                    message: Some(format!("Query failed: {}", e.to_string())),
-                })
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
             }
         };
 
-        HttpResponse::Ok().json(FreqStatsResponse {
-            ok: true,
-            body: results
+        response.body(
+            results
                 .into_iter()
                 .map(|result| FrequencyStats {
                     freq_mhz: result.khz as f64 / 1000.0,
                     gs: GroundStation {
                         id: result.gs_id,
                         name: result.name,
+                        short: result.short,
                         coords: if result.latitude.is_some() && result.longitude.is_some() {
                             Some((result.longitude.unwrap(), result.latitude.unwrap()))
                         } else {
@@ -97,13 +166,11 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
                     to_gs: result.to_gs,
                     from_gs: result.from_gs,
                     last_heard: result.last_heard,
+                    window_count: result.window_count,
                 })
-                .collect(),
-        })
+                .collect::<Vec<FrequencyStats>>(),
+        )
     } else {
-        HttpResponse::NotImplemented().json(ServerServiceResponse {
-            ok: false,
-            message: Some(format!("State DB is disabled")),
-        })
+        response.db_disabled()
     }
 }
@@ -1,29 +1,28 @@
 use actix_web::web::Data;
 use actix_web::{HttpRequest, HttpResponse};
 
-use serde::Serialize;
 use tokio::sync::RwLock;
 
 use crate::common::middleware::Authorized;
-use crate::modules::settings::{GroundStation, ModuleSettings};
+use crate::modules::settings::ModuleSettings;
+use crate::server::db::StateDB;
 
-pub const ROUTE: &'static str = "/api/ground-station/active/";
+use super::ServerServiceResponseBuilder;
 
-#[derive(Serialize)]
-struct GSActiveResponse {
-    ok: bool,
-    body: Vec<GroundStation>,
-}
+pub const ROUTE: &'static str = "/api/ground-station/active/";
 
 pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = match req.app_data::<Data<RwLock<StateDB>>>() {
+        Some(x) => Some(x.read().await),
+        None => None,
+    };
+    let response = ServerServiceResponseBuilder::new(state_db.as_deref());
+
     let module_settings = req
         .app_data::<Data<RwLock<ModuleSettings>>>()
         .unwrap()
         .read()
         .await;
 
-    HttpResponse::Ok().json(GSActiveResponse {
-        ok: true,
-        body: module_settings.stations.clone(),
-    })
+    response.body(module_settings.reported_stations())
 }
@@ -0,0 +1,157 @@
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
+
+pub const ROUTE: &'static str = "/api/ground-station/{id}/aircraft/";
+
+const DEFAULT_AIRCRAFT_LIMIT: u32 = 250;
+
+#[derive(Debug, Deserialize)]
+struct GroundStationAircraftParam {
+    since: Option<DateTime<Utc>>,
+    limit: Option<u32>,
+}
+
+#[derive(FromRow)]
+struct HeardAircraftRow {
+    ts: DateTime<Utc>,
+    icao_addr: Option<String>,
+    callsign: Option<String>,
+    tail: Option<String>,
+    signal: f64,
+    freq_mhz: f64,
+    latitude: f64,
+    longitude: f64,
+    altitude: Option<u32>,
+    event_count: u32,
+}
+
+#[derive(Serialize)]
+struct HeardAircraft {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icao: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callsign: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tail: Option<String>,
+
+    last_heard: DateTime<Utc>,
+    last_signal: f64,
+    last_freq_mhz: f64,
+
+    coords: (f64, f64),
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    altitude: Option<u32>,
+
+    event_count: u32,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<GroundStationAircraftParam>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    let gs_id = match req.match_info().get("id").unwrap_or("").parse::<u32>() {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::BadRequest(), format!("Invalid ground station ID: {}", e.to_string()))
+        }
+    };
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        let exists = match sqlx::query_scalar::<_, i64>(
+            "
+            SELECT COUNT(*) FROM ground_stations gs WHERE gs.id = ?
+            ",
+        )
+        .bind(gs_id)
+        .fetch_one(db)
+        .await
+        {
+            Ok(x) => x > 0,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+        if !exists {
+            return response.error(HttpResponse::NotFound(), format!("Ground station {} does not exist", gs_id));
+        }
+
+        let results = match sqlx::query_as::<_, HeardAircraftRow>(
+            "
+            WITH grouped_events AS (
+                SELECT 
+                    ROW_NUMBER() OVER (PARTITION BY coalesce(ae.aircraft_icao, ae.tail, ae.callsign) ORDER BY ae.ts DESC) AS row,
+                    COUNT(*) OVER (PARTITION BY coalesce(ae.aircraft_icao, ae.tail, ae.callsign)) AS event_count,
+                    ae.*
+                FROM aircraft_events ae
+                WHERE ae.gs_id = ?
+                    AND ifnull(ae.ts >= ?, 1)
+            )
+            SELECT 
+                ge.*,
+                iif(ge.aircraft_icao IS NULL, NULL, printf('%06x', ge.aircraft_icao)) AS icao_addr
+            FROM grouped_events ge
+            WHERE ge.row = 1
+            ORDER BY ge.ts DESC
+            LIMIT ?
+            ",
+        )
+        .bind(gs_id)
+        .bind(params.since)
+        .bind(params.limit.unwrap_or(DEFAULT_AIRCRAFT_LIMIT))
+        .fetch_all(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        response.body(
+            results
+                .into_iter()
+                .map(|result| HeardAircraft {
+                    icao: result.icao_addr,
+                    callsign: result.callsign,
+                    tail: result.tail,
+                    last_heard: result.ts,
+                    last_signal: result.signal,
+                    last_freq_mhz: result.freq_mhz,
+                    coords: (result.longitude, result.latitude),
+                    altitude: result.altitude,
+                    event_count: result.event_count,
+                })
+                .collect::<Vec<HeardAircraft>>(),
+        )
+    } else {
+        response.db_disabled()
+    }
+}
@@ -1,19 +1,32 @@
-use actix_web::http::header::ContentType;
-use actix_web::web::{self, Data};
+use std::time::Duration;
+
+use actix_web::http::header;
+use actix_web::web::{self, Bytes, Data};
 use actix_web::{HttpRequest, HttpResponse};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::FromRow;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio::time::{interval_at, Instant};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::{BroadcastStream, IntervalStream};
+use tokio_stream::StreamExt;
 
+use crate::common::events;
 use crate::common::middleware::Authorized;
 use crate::server::db::StateDB;
-use crate::server::services::ServerServiceResponse;
+use crate::server::services::ServerServiceResponseBuilder;
 
 pub const ROUTE: &'static str = "/api/ground-station/events/";
+pub const STREAM_ROUTE: &'static str = "/api/ground-station/events/stream/";
+
+const ETAG_QUERY: &'static str = "
+    SELECT printf('%s-%d-%d-%d', max(ts), min(id), max(id), count(*)) FROM ground_station_change_events
+";
 
 const DEFAULT_GSCE_LIMIT: u32 = 250;
+const DEFAULT_STREAM_KEEP_ALIVE_SECS: u64 = 15;
 
 #[derive(Serialize)]
 pub struct GroundStationChangeEvent {
@@ -29,6 +42,21 @@ pub struct GroundStationChangeEvent {
     new: Value,
 }
 
+impl GroundStationChangeEvent {
+    fn from_event(event: &events::GroundStationChangeEvent) -> Option<GroundStationChangeEvent> {
+        Some(GroundStationChangeEvent {
+            ts: DateTime::parse_from_rfc3339(event.ts.as_str())
+                .map(|x| x.with_timezone(&Utc))
+                .unwrap_or(Utc::now()),
+            gs_id: event.gs_id()?,
+            name: event.name.clone(),
+            kind: String::from("freq_change"),
+            old: serde_json::from_str(event.old.as_str()).unwrap_or(Value::Null),
+            new: serde_json::from_str(event.new.as_str()).unwrap_or(Value::Null),
+        })
+    }
+}
+
 #[derive(FromRow)]
 struct GSCEventRow {
     ts: DateTime<Utc>,
@@ -39,12 +67,6 @@ struct GSCEventRow {
     new: String,
 }
 
-#[derive(Serialize)]
-struct GroundStationEventResponse {
-    ok: bool,
-    body: Vec<GroundStationChangeEvent>,
-}
-
 #[derive(Debug, Deserialize)]
 struct GroundStationEventsParam {
     limit: Option<u32>,
@@ -52,23 +74,26 @@ struct GroundStationEventsParam {
 }
 
 pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
     let params = match web::Query::<GroundStationEventsParam>::from_query(req.query_string()) {
         Ok(x) => x,
         Err(e) => {
-            return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                ok: false,
-                message: Some(format!("Failed to get query params: {}", e.to_string())),
-            })
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
         }
     };
 
-    let state_db = req
-        .app_data::<Data<RwLock<StateDB>>>()
-        .unwrap()
-        .read()
-        .await;
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
 
-    if let Some(db) = state_db.db_pool() {
         let results = match sqlx::query_as::<_, GSCEventRow>(
             "
             SELECT gsce.ts, gsce.gs_id, gs.name, gsce.type AS kind, gsce.old, gsce.new FROM ground_station_change_events gsce 
@@ -85,35 +110,51 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
         {
             Ok(x) => x,
             Err(e) => {
-                return HttpResponse::InternalServerError().json(
-                    ServerServiceResponse {
-                        ok: false,
-                        message: Some(format!("Query failed: {}", e.to_string()))
-                    }
-                )
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
             }
         };
 
-        HttpResponse::Ok()
-            .content_type(ContentType::json())
-            .json(GroundStationEventResponse {
-                ok: true,
-                body: results
-                    .into_iter()
-                    .map(|result| GroundStationChangeEvent {
-                        ts: result.ts,
-                        gs_id: result.gs_id as u64,
-                        name: result.name,
-                        kind: result.kind,
-                        old: serde_json::from_str(result.old.as_str()).unwrap_or(Value::Null),
-                        new: serde_json::from_str(result.new.as_str()).unwrap_or(Value::Null),
-                    })
-                    .collect(),
-            })
+        response.body(
+            results
+                .into_iter()
+                .map(|result| GroundStationChangeEvent {
+                    ts: result.ts,
+                    gs_id: result.gs_id as u64,
+                    name: result.name,
+                    kind: result.kind,
+                    old: serde_json::from_str(result.old.as_str()).unwrap_or(Value::Null),
+                    new: serde_json::from_str(result.new.as_str()).unwrap_or(Value::Null),
+                })
+                .collect::<Vec<GroundStationChangeEvent>>(),
+        )
     } else {
-        HttpResponse::NotImplemented().json(ServerServiceResponse {
-            ok: false,
-            message: Some(format!("State DB is disabled")),
-        })
+        response.db_disabled()
     }
 }
+
+// NOTE: change events are only produced by listening modules, the aggregator server has nothing to stream
+pub async fn stream(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let Some(change_event_tx) = req.app_data::<Data<broadcast::Sender<events::GroundStationChangeEvent>>>() else {
+        return ServerServiceResponseBuilder::new(None).error(
+            HttpResponse::NotFound(),
+            String::from("Ground station change events are not streamed by this instance"),
+        );
+    };
+
+    let events = BroadcastStream::new(change_event_tx.subscribe()).filter_map(|result| match result {
+        Ok(event) => GroundStationChangeEvent::from_event(&event)
+            .and_then(|x| serde_json::to_string(&x).ok())
+            .map(|x| format!("event: gs_change\ndata: {}\n\n", x)),
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(format!(": lagged, {} events skipped\n\n", skipped)),
+    });
+
+    let keep_alive_period = Duration::from_secs(DEFAULT_STREAM_KEEP_ALIVE_SECS);
+    let keep_alive = IntervalStream::new(interval_at(Instant::now() + keep_alive_period, keep_alive_period))
+        .map(|_| String::from(": keep-alive\n\n"));
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events.merge(keep_alive).map(|x| Ok::<Bytes, actix_web::Error>(Bytes::from(x))))
+}
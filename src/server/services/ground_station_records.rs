@@ -0,0 +1,158 @@
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::{ServerServiceResponseBuilder, AIRCRAFT_EVENTS_ETAG_QUERY};
+
+pub const ROUTE: &'static str = "/api/ground-station/{id}/records/";
+
+const DEFAULT_RECORDS_LIMIT: u32 = 25;
+
+#[derive(Debug, Deserialize)]
+struct GroundStationRecordsParam {
+    since: Option<DateTime<Utc>>,
+    limit: Option<u32>,
+}
+
+#[derive(FromRow)]
+struct RecordRow {
+    ts: DateTime<Utc>,
+    icao_addr: Option<String>,
+    callsign: Option<String>,
+    tail: Option<String>,
+    signal: f64,
+    freq_mhz: f64,
+    latitude: f64,
+    longitude: f64,
+    altitude: Option<u32>,
+    distance_km: f64,
+}
+
+#[derive(Serialize)]
+struct DistanceRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icao: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callsign: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tail: Option<String>,
+
+    ts: DateTime<Utc>,
+    signal: f64,
+    freq_mhz: f64,
+
+    coords: (f64, f64),
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    altitude: Option<u32>,
+
+    distance_km: f64,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<GroundStationRecordsParam>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    let gs_id = match req.match_info().get("id").unwrap_or("").parse::<u32>() {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::BadRequest(), format!("Invalid ground station ID: {}", e.to_string()))
+        }
+    };
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, AIRCRAFT_EVENTS_ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        let exists = match sqlx::query_scalar::<_, i64>(
+            "
+            SELECT COUNT(*) FROM ground_stations gs WHERE gs.id = ?
+            ",
+        )
+        .bind(gs_id)
+        .fetch_one(db)
+        .await
+        {
+            Ok(x) => x > 0,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+        if !exists {
+            return response.error(HttpResponse::NotFound(), format!("Ground station {} does not exist", gs_id));
+        }
+
+        // NOTE: only the furthest reception per aircraft is kept so a single flight cannot fill the leaderboard
+        let results = match sqlx::query_as::<_, RecordRow>(
+            "
+            WITH grouped_events AS (
+                SELECT 
+                    ROW_NUMBER() OVER (PARTITION BY coalesce(ae.aircraft_icao, ae.tail, ae.callsign) ORDER BY ae.distance_km DESC) AS row,
+                    ae.*
+                FROM aircraft_events ae
+                WHERE ae.gs_id = ?
+                    AND ae.distance_km IS NOT NULL
+                    AND ifnull(ae.ts >= ?, 1)
+            )
+            SELECT 
+                ge.*,
+                iif(ge.aircraft_icao IS NULL, NULL, printf('%06x', ge.aircraft_icao)) AS icao_addr
+            FROM grouped_events ge
+            WHERE ge.row = 1
+            ORDER BY ge.distance_km DESC
+            LIMIT ?
+            ",
+        )
+        .bind(gs_id)
+        .bind(params.since)
+        .bind(params.limit.unwrap_or(DEFAULT_RECORDS_LIMIT))
+        .fetch_all(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        response.body(
+            results
+                .into_iter()
+                .map(|result| DistanceRecord {
+                    icao: result.icao_addr,
+                    callsign: result.callsign,
+                    tail: result.tail,
+                    ts: result.ts,
+                    signal: result.signal,
+                    freq_mhz: result.freq_mhz,
+                    coords: (result.longitude, result.latitude),
+                    altitude: result.altitude,
+                    distance_km: result.distance_km,
+                })
+                .collect::<Vec<DistanceRecord>>(),
+        )
+    } else {
+        response.db_disabled()
+    }
+}
@@ -7,14 +7,21 @@ use tokio::sync::RwLock;
 use crate::common::middleware::Authorized;
 use crate::server::db::StateDB;
 
-use super::ServerServiceResponse;
+use super::ServerServiceResponseBuilder;
 
 pub const ROUTE: &'static str = "/api/ground-station/stats/";
 
+const ETAG_QUERY: &'static str = "
+    SELECT printf('%d-%d-%d-', count(*), total(msgs_heard_from), total(msgs_heard_to)) ||
+        coalesce(group_concat(printf('%d|%s|%s|%s|%s', id, name, short, latitude, longitude), ';'), '')
+    FROM (SELECT * FROM ground_stations ORDER BY id)
+";
+
 #[derive(FromRow)]
 struct GSStatRow {
     id: u32,
     name: Option<String>,
+    short: Option<String>,
     latitude: Option<f64>,
     longitude: Option<f64>,
     msgs_heard_from: u32,
@@ -28,6 +35,9 @@ struct GroundStation {
     #[serde(skip_serializing_if = "Option::is_none")]
     name: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     coords: Option<(f64, f64)>,
 
@@ -35,19 +45,20 @@ struct GroundStation {
     msgs_heard_to: u32,
 }
 
-#[derive(Serialize)]
-struct GroundStationResponse {
-    ok: bool,
-    body: Vec<GroundStation>,
-}
-
 pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
     let state_db = req
         .app_data::<Data<RwLock<StateDB>>>()
         .unwrap()
         .read()
         .await;
-    if let Some(db) = state_db.db_pool() {
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
         let results = match sqlx::query_as::<_, GSStatRow>(
             "
             SELECT * FROM ground_stations gs
@@ -59,20 +70,17 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
         {
             Ok(x) => x,
             Err(e) => {
-                return HttpResponse::InternalServerError().json(ServerServiceResponse {
-                    ok: false,
-                    message: Some(format!("Query failed: {}", e.to_string())),
-                })
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
             }
         };
 
-        HttpResponse::Ok().json(GroundStationResponse {
-            ok: true,
-            body: results
+        response.body(
+            results
                 .into_iter()
                 .map(|result| GroundStation {
                     id: result.id,
                     name: result.name,
+                    short: result.short,
                     coords: if result.latitude.is_some() && result.longitude.is_some() {
                         Some((result.longitude.unwrap(), result.latitude.unwrap()))
                     } else {
@@ -81,12 +89,9 @@ pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
                     msgs_heard_from: result.msgs_heard_from,
                     msgs_heard_to: result.msgs_heard_to,
                 })
-                .collect(),
-        })
+                .collect::<Vec<GroundStation>>(),
+        )
     } else {
-        HttpResponse::NotImplemented().json(ServerServiceResponse {
-            ok: false,
-            message: Some(format!("State DB is disabled")),
-        })
+        response.db_disabled()
     }
 }
@@ -1,29 +1,153 @@
-use actix_web::{guard, web};
+use actix_web::http::header;
+use actix_web::{guard, web, HttpRequest, HttpResponse, HttpResponseBuilder};
+use log::*;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
 
+use crate::server::db::StateDB;
+
+mod acars_labels;
+mod aircraft_callsigns;
+mod aircraft_events;
+mod aircraft_prune;
 mod cleanup;
+mod coverage_stations;
+mod es_reindex;
 mod extremities;
 mod flight;
 mod flight_events;
+mod frequency_activity;
+mod frequency_history;
 mod frequency_stats;
 mod ground_station_active;
+mod ground_station_aircraft;
 mod ground_station_events;
+mod ground_station_records;
 mod ground_station_stats;
+mod timeseries;
+mod vacuum;
 
 #[derive(Serialize)]
-pub struct ServerServiceResponse {
+pub struct ServerServiceResponse<T: Serialize = ()> {
     ok: bool,
+    db_enabled: bool,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     message: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<T>,
+}
+
+pub const AIRCRAFT_EVENTS_ETAG_QUERY: &'static str = "
+    SELECT printf('%s-%d-%d-%d', max(ts), min(id), max(id), count(*)) FROM aircraft_events
+";
+
+pub struct ServerServiceResponseBuilder {
+    db_enabled: bool,
+    etag: Option<String>,
+}
+
+impl ServerServiceResponseBuilder {
+    pub fn new(state_db: Option<&StateDB>) -> ServerServiceResponseBuilder {
+        ServerServiceResponseBuilder {
+            db_enabled: state_db.map_or(false, |x| x.db_pool().is_some()),
+            etag: None,
+        }
+    }
+
+    // NOTE: query should return a single text value that changes whenever the endpoint's data does;
+    //       it is hashed, so it may be as long as it needs to be to capture every change
+    pub async fn with_etag(mut self, db: &SqlitePool, query: &str) -> ServerServiceResponseBuilder {
+        match sqlx::query_scalar::<_, Option<String>>(query).fetch_one(db).await {
+            Ok(Some(x)) => {
+                self.etag = Some(format!(
+                    "W/\"{}\"",
+                    hex::encode(&Sha256::digest(x.as_bytes())[..16])
+                ))
+            }
+            Ok(None) => {}
+            Err(e) => debug!("Failed to compute ETag: {}", e.to_string()),
+        }
+        self
+    }
+
+    pub fn not_modified(&self, req: &HttpRequest) -> Option<HttpResponse> {
+        let etag = self.etag.as_ref()?;
+        let if_none_match = req.headers().get(header::IF_NONE_MATCH)?.to_str().ok()?;
+
+        // NOTE: weak comparison, so W/ prefixes are ignored on both sides
+        let matched = if_none_match
+            .split(',')
+            .map(|x| x.trim().trim_start_matches("W/"))
+            .any(|x| x == "*" || x == etag.trim_start_matches("W/"));
+        if !matched {
+            return None;
+        }
+
+        Some(
+            HttpResponse::NotModified()
+                .insert_header((header::ETAG, etag.clone()))
+                .finish(),
+        )
+    }
+
+    pub fn body<T: Serialize>(&self, body: T) -> HttpResponse {
+        let mut builder = HttpResponse::Ok();
+        if let Some(ref etag) = self.etag {
+            builder.insert_header((header::ETAG, etag.clone()));
+        }
+
+        builder.json(ServerServiceResponse {
+            ok: true,
+            db_enabled: self.db_enabled,
+            message: None,
+            body: Some(body),
+        })
+    }
+
+    pub fn message(&self, mut builder: HttpResponseBuilder, ok: bool, message: String) -> HttpResponse {
+        builder.json(ServerServiceResponse::<()> {
+            ok,
+            db_enabled: self.db_enabled,
+            message: Some(message),
+            body: None,
+        })
+    }
+
+    pub fn error(&self, builder: HttpResponseBuilder, message: String) -> HttpResponse {
+        self.message(builder, false, message)
+    }
+
+    pub fn db_disabled(&self) -> HttpResponse {
+        self.error(HttpResponse::NotImplemented(), format!("State DB is disabled"))
+    }
 }
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::resource(flight::ROUTE)
-            .guard(guard::Header("content-type", "application/json"))
             .route(web::get().to(flight::get)),
     );
+    cfg.service(
+        web::resource(acars_labels::ROUTE)
+            .route(web::get().to(acars_labels::get)),
+    );
+    cfg.service(
+        web::resource(aircraft_callsigns::ROUTE)
+            .route(web::get().to(aircraft_callsigns::get)),
+    );
+    cfg.service(
+        web::resource(aircraft_events::ROUTE)
+            .guard(guard::Header("content-type", "application/json"))
+            .route(web::delete().to(aircraft_events::delete)),
+    );
+    cfg.service(
+        web::resource(aircraft_prune::ROUTE)
+            .guard(guard::Header("content-type", "application/json"))
+            .route(web::delete().to(aircraft_prune::delete)),
+    );
     cfg.service(
         web::resource(cleanup::ROUTE)
             .guard(guard::Header("content-type", "application/json"))
@@ -31,34 +155,68 @@ pub fn config(cfg: &mut web::ServiceConfig) {
     );
 
     cfg.service(
-        web::resource(extremities::ROUTE)
+        web::resource(coverage_stations::ROUTE)
+            .route(web::get().to(coverage_stations::get)),
+    );
+
+    cfg.service(
+        web::resource(es_reindex::ROUTE)
             .guard(guard::Header("content-type", "application/json"))
+            .route(web::post().to(es_reindex::post)),
+    );
+
+    cfg.service(
+        web::resource(extremities::ROUTE)
             .route(web::get().to(extremities::get)),
     );
 
     cfg.service(
         web::resource(flight_events::ROUTE)
-            .guard(guard::Header("content-type", "application/json"))
             .route(web::get().to(flight_events::get)),
     );
+    cfg.service(
+        web::resource(frequency_activity::ROUTE)
+            .route(web::get().to(frequency_activity::get)),
+    );
+    cfg.service(
+        web::resource(frequency_history::ROUTE)
+            .route(web::get().to(frequency_history::get)),
+    );
     cfg.service(
         web::resource(frequency_stats::ROUTE)
-            .guard(guard::Header("content-type", "application/json"))
             .route(web::get().to(frequency_stats::get)),
     );
     cfg.service(
         web::resource(ground_station_events::ROUTE)
-            .guard(guard::Header("content-type", "application/json"))
             .route(web::get().to(ground_station_events::get)),
     );
+    cfg.service(
+        web::resource(ground_station_events::STREAM_ROUTE)
+            .route(web::get().to(ground_station_events::stream)),
+    );
     cfg.service(
         web::resource(ground_station_stats::ROUTE)
-            .guard(guard::Header("content-type", "application/json"))
             .route(web::get().to(ground_station_stats::get)),
     );
+    cfg.service(
+        web::resource(ground_station_aircraft::ROUTE)
+            .route(web::get().to(ground_station_aircraft::get)),
+    );
+    cfg.service(
+        web::resource(ground_station_records::ROUTE)
+            .route(web::get().to(ground_station_records::get)),
+    );
     cfg.service(
         web::resource(ground_station_active::ROUTE)
-            .guard(guard::Header("content-type", "application/json"))
             .route(web::get().to(ground_station_active::get)),
     );
+    cfg.service(
+        web::resource(timeseries::ROUTE)
+            .route(web::get().to(timeseries::get)),
+    );
+    cfg.service(
+        web::resource(vacuum::ROUTE)
+            .guard(guard::Header("content-type", "application/json"))
+            .route(web::post().to(vacuum::post)),
+    );
 }
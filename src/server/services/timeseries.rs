@@ -0,0 +1,121 @@
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::{StateDB, HOURLY_STATS_METRIC_FREQ, HOURLY_STATS_METRIC_GS};
+
+use super::ServerServiceResponseBuilder;
+
+pub const ROUTE: &'static str = "/api/stats/timeseries/";
+
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+const ETAG_QUERY: &'static str = "
+    SELECT printf('%s-%d', max(hour), total(count)) FROM hourly_stats
+";
+
+#[derive(Debug, Deserialize)]
+struct TimeseriesParam {
+    metric: String,
+    since: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct BucketRow {
+    hour: DateTime<Utc>,
+    key: u32,
+    count: u32,
+}
+
+#[derive(Serialize)]
+struct Point {
+    hour: DateTime<Utc>,
+    count: u32,
+}
+
+#[derive(Serialize)]
+struct Series {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    freq_mhz: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gs_id: Option<u32>,
+
+    points: Vec<Point>,
+}
+
+pub async fn get(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .read()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    let params = match web::Query::<TimeseriesParam>::from_query(req.query_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            return response.error(HttpResponse::InternalServerError(), format!("Failed to get query params: {}", e.to_string()))
+        }
+    };
+
+    let metric = params.metric.to_lowercase();
+    if metric != HOURLY_STATS_METRIC_FREQ && metric != HOURLY_STATS_METRIC_GS {
+        return response.error(
+            HttpResponse::BadRequest(),
+            format!("Invalid metric '{}', expected one of: {}, {}", params.metric, HOURLY_STATS_METRIC_FREQ, HOURLY_STATS_METRIC_GS)
+        );
+    }
+    let since = params.since.unwrap_or(Utc::now() - Duration::hours(DEFAULT_WINDOW_HOURS));
+
+    if let Some(db) = state_db.db_read_pool() {
+        let response = response.with_etag(db, ETAG_QUERY).await;
+        if let Some(not_modified) = response.not_modified(&req) {
+            return not_modified;
+        }
+
+        let results = match sqlx::query_as::<_, BucketRow>(
+            "
+            SELECT hour, key, count FROM hourly_stats
+            WHERE metric = ? AND hour >= strftime('%Y-%m-%dT%H:00:00Z', ?)
+            ORDER BY key ASC, hour ASC
+            ",
+        )
+        .bind(&metric)
+        .bind(since)
+        .fetch_all(db)
+        .await
+        {
+            Ok(x) => x,
+            Err(e) => {
+                return response.error(HttpResponse::InternalServerError(), format!("Query failed: {}", e.to_string()))
+            }
+        };
+
+        let mut series: Vec<(u32, Vec<Point>)> = Vec::new();
+        for row in results.into_iter() {
+            let point = Point { hour: row.hour, count: row.count };
+            match series.last_mut() {
+                Some((key, points)) if *key == row.key => points.push(point),
+                _ => series.push((row.key, vec![point])),
+            }
+        }
+
+        response.body(
+            series
+                .into_iter()
+                .map(|(key, points)| Series {
+                    freq_mhz: if metric == HOURLY_STATS_METRIC_FREQ { Some(key as f64 / 1000.0) } else { None },
+                    gs_id: if metric == HOURLY_STATS_METRIC_GS { Some(key) } else { None },
+                    points,
+                })
+                .collect::<Vec<Series>>(),
+        )
+    } else {
+        response.db_disabled()
+    }
+}
@@ -0,0 +1,38 @@
+use actix_web::web::Data;
+use actix_web::{HttpRequest, HttpResponse};
+use tokio::sync::RwLock;
+
+use crate::common::middleware::Authorized;
+use crate::server::db::StateDB;
+
+use super::ServerServiceResponseBuilder;
+
+pub const ROUTE: &'static str = "/api/vacuum/";
+
+// NOTE: frame updates wait on the StateDB write lock until the vacuum completes
+pub async fn post(req: HttpRequest, _: Authorized) -> HttpResponse {
+    let state_db = req
+        .app_data::<Data<RwLock<StateDB>>>()
+        .unwrap()
+        .write()
+        .await;
+    let response = ServerServiceResponseBuilder::new(Some(&state_db));
+
+    match state_db.vacuum().await {
+        Ok(Some((size_before, size_after))) => response.message(
+            HttpResponse::Ok(),
+            true,
+            format!(
+                "Vacuum succeeded: reclaimed {} bytes ({} -> {} bytes)",
+                size_before - size_after,
+                size_before,
+                size_after
+            ),
+        ),
+        Ok(None) => response.db_disabled(),
+        Err(e) => response.error(
+            HttpResponse::InternalServerError(),
+            format!("Vacuum failed: {}", e.to_string()),
+        ),
+    }
+}
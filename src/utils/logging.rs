@@ -0,0 +1,145 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use chrono::{SecondsFormat, Utc};
+use log::LevelFilter;
+
+pub const DEFAULT_LOG_FILE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+pub const DEFAULT_LOG_FILE_COUNT: usize = 5;
+
+pub struct LogSettings {
+    pub quiet: bool,
+    pub verbose_level: u8,
+    pub log_file: Option<PathBuf>,
+    pub log_file_only: bool,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+// NOTE: rotated files are named <path>.1 (newest) through <path>.<max_files> (oldest)
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFileWriter {
+    pub fn new(path: PathBuf, max_bytes: u64, max_files: usize) -> Result<RotatingFileWriter, io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(RotatingFileWriter {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    fn rotated_path(&self, idx: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", idx));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self) -> Result<(), io::Error> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            self.file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?;
+            self.written = 0;
+            return Ok(());
+        }
+
+        let oldest = self.rotated_path(self.max_files);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for idx in (1..self.max_files).rev() {
+            let src = self.rotated_path(idx);
+            if src.exists() {
+                fs::rename(&src, self.rotated_path(idx + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = 0;
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.max_bytes > 0 && self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let size = self.file.write(buf)?;
+        self.written += size as u64;
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+pub fn init_logging(settings: LogSettings) -> Result<(), io::Error> {
+    // NOTE: mirrors the stderrlog semantics used previously, default level is warn and each -v raises it
+    let level = if settings.quiet {
+        LevelFilter::Off
+    } else {
+        match settings.verbose_level {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    };
+    let timestamp = settings.verbose_level > 1;
+
+    let mut dispatch = fern::Dispatch::new()
+        .format(move |out, message, record| {
+            if timestamp {
+                out.finish(format_args!(
+                    "{} - {} - {}",
+                    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+                    record.level(),
+                    message
+                ))
+            } else {
+                out.finish(format_args!("{} - {}", record.level(), message))
+            }
+        })
+        .level(LevelFilter::Off)
+        .level_for(env!("CARGO_CRATE_NAME"), level);
+
+    if settings.log_file.is_none() || !settings.log_file_only {
+        dispatch = dispatch.chain(io::stderr());
+    }
+
+    if let Some(path) = settings.log_file {
+        let writer = RotatingFileWriter::new(path.clone(), settings.max_bytes, settings.max_files)
+            .map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Failed to open log file {}: {}", path.display(), e.to_string()),
+                )
+            })?;
+        dispatch = dispatch.chain(fern::Output::writer(Box::new(writer), "\n"));
+    }
+
+    dispatch
+        .apply()
+        .map_err(|e| io::Error::new(io::ErrorKind::AlreadyExists, e.to_string()))
+}
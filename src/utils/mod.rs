@@ -1,7 +1,10 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 
+pub mod geo;
+pub mod logging;
 pub mod timestamp;
+pub mod version;
 
 pub fn normalize_tail(tail: &String) -> String {
     lazy_static! {
@@ -10,3 +13,18 @@ pub fn normalize_tail(tail: &String) -> String {
 
     TAIL_NORM_RE.replace_all(&tail, "").to_string()
 }
+
+// NOTE: a human readable label for a band of frequencies, e.g. "8912-8977" or "8912" for a single frequency
+pub fn band_name<T: Copy + Default + PartialEq + std::fmt::Display>(band: &[T]) -> String {
+    let first = band.first().copied().unwrap_or_default();
+    let last = band.last().copied().unwrap_or_default();
+
+    if first == T::default() {
+        return format!("{}", last);
+    }
+    if last == T::default() || first == last {
+        return format!("{}", first);
+    }
+
+    format!("{}-{}", first, last)
+}
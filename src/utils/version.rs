@@ -0,0 +1,100 @@
+use lazy_static::lazy_static;
+use log::*;
+use regex::Regex;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io;
+use tokio::process::Command;
+use tokio::time;
+
+pub type Version = (u32, u32, u32);
+
+const DECODER_VERSION_TIMEOUT_SECS: u64 = 10;
+
+pub fn parse_version(value: &str) -> Option<Version> {
+    lazy_static! {
+        static ref VERSION_RE: Regex = Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").unwrap();
+    }
+
+    let captures = VERSION_RE.captures(value)?;
+    Some((
+        captures.get(1)?.as_str().parse().ok()?,
+        captures.get(2)?.as_str().parse().ok()?,
+        captures
+            .get(3)
+            .map_or(Some(0), |x| x.as_str().parse().ok())?,
+    ))
+}
+
+pub async fn get_decoder_version(bin: &PathBuf) -> Result<String, io::Error> {
+    // NOTE: kill_on_drop makes sure a decoder that hangs on --version doesn't outlive the timeout
+    let output = Command::new(bin).arg("--version").kill_on_drop(true).output();
+    let output = match time::timeout(Duration::from_secs(DECODER_VERSION_TIMEOUT_SECS), output).await {
+        Ok(x) => x?,
+        Err(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "{} --version did not exit within {} seconds",
+                    bin.to_string_lossy(),
+                    DECODER_VERSION_TIMEOUT_SECS
+                ),
+            ))
+        }
+    };
+
+    // NOTE: dumphfdl and dumpvdl2 print their version banner to stderr
+    let banner = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let Some((major, minor, patch)) = parse_version(banner.as_str()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unable to find version in output of {} --version", bin.to_string_lossy()),
+        ));
+    };
+
+    Ok(format!("{}.{}.{}", major, minor, patch))
+}
+
+pub async fn check_decoder_version(
+    bin: &PathBuf,
+    min_version: &str,
+    require_min_version: bool,
+) -> Result<Option<String>, io::Error> {
+    let min = parse_version(min_version).unwrap_or((0, 0, 0));
+
+    let version = match get_decoder_version(bin).await {
+        Ok(x) => x,
+        Err(e) => {
+            if require_min_version {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Failed to detect decoder version: {}", e.to_string()),
+                ));
+            }
+
+            warn!("Failed to detect decoder version: {}", e.to_string());
+            return Ok(None);
+        }
+    };
+
+    if parse_version(version.as_str()).unwrap_or((0, 0, 0)) < min {
+        let msg = format!(
+            "{} version {} is older than the minimum supported version {}, decoded output may fail to parse",
+            bin.to_string_lossy(),
+            version,
+            min_version
+        );
+        if require_min_version {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, msg));
+        }
+        warn!("{}", msg);
+    } else {
+        debug!("Detected {} version {}", bin.to_string_lossy(), version);
+    }
+
+    Ok(Some(version))
+}